@@ -0,0 +1,392 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::{
+    parsing::BigEndianToken,
+    properties::{cells::CellSizes, reg::RegEntry},
+    util::align4,
+    FdtError,
+};
+
+const HEADER_SIZE: usize = core::mem::size_of::<crate::FdtHeader>();
+const MEMORY_RESERVATION_SIZE: usize = 16;
+const FDT_MAGIC: u32 = 0xd00dfeed;
+const FDT_VERSION: u32 = 17;
+const FDT_LAST_COMPATIBLE_VERSION: u32 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BuilderState {
+    MemoryReservations,
+    Nodes,
+    RootClosed,
+}
+
+/// Serializes a flattened devicetree into a caller-provided buffer.
+///
+/// The devicetree is written sequentially: memory reservations are added
+/// first, followed by the node hierarchy, which is described by matching
+/// [`FdtBuilder::begin_node`] and [`FdtBuilder::end_node`] calls with
+/// properties added in between. The root node has an empty name (`"/"` is
+/// accepted as an alias). Property names are deduplicated in the strings
+/// block. Calling [`FdtBuilder::finish`] writes the header and lays out the
+/// final blob, which can then be read back with [`Fdt::new`](crate::Fdt::new)
+/// or [`Fdt::new_unaligned`](crate::Fdt::new_unaligned).
+///
+/// Example:
+///
+/// ```rust
+/// use fdt::{builder::FdtBuilder, Fdt};
+///
+/// let mut buffer = [0u32; 64];
+/// let mut builder = FdtBuilder::new_aligned(&mut buffer);
+///
+/// builder.add_memory_reservation(0x8000_0000, 0x10_0000).unwrap();
+/// builder.begin_node("/").unwrap();
+/// builder.property_u32("#address-cells", 2).unwrap();
+/// builder.property_string("model", "my-board").unwrap();
+/// builder.begin_node("chosen").unwrap();
+/// builder.property_string("bootargs", "console=ttyS0").unwrap();
+/// builder.end_node().unwrap();
+/// builder.end_node().unwrap();
+/// builder.finish().unwrap();
+///
+/// let fdt = Fdt::new(&buffer).unwrap();
+/// assert_eq!(fdt.root().model(), "my-board");
+/// assert_eq!(fdt.root().chosen().bootargs(), Some("console=ttyS0"));
+/// ```
+pub struct FdtBuilder<'b> {
+    buffer: &'b mut [u8],
+    /// End of the data written to the front of the buffer (the memory
+    /// reservation block followed by the structs block).
+    cursor: usize,
+    structs_offset: usize,
+    /// The strings block is built backwards from the end of the buffer and
+    /// moved into place by [`FdtBuilder::finish`].
+    strings_size: usize,
+    depth: usize,
+    state: BuilderState,
+    boot_cpuid: u32,
+}
+
+impl<'b> FdtBuilder<'b> {
+    /// Create a new [`FdtBuilder`] which will serialize the devicetree into
+    /// `buffer`.
+    pub fn new(buffer: &'b mut [u8]) -> Self {
+        Self {
+            buffer,
+            cursor: HEADER_SIZE,
+            structs_offset: 0,
+            strings_size: 0,
+            depth: 0,
+            state: BuilderState::MemoryReservations,
+            boot_cpuid: 0,
+        }
+    }
+
+    /// Create a new [`FdtBuilder`] which will serialize the devicetree into a
+    /// `u32`-aligned buffer, so that the finished devicetree can be passed
+    /// directly to [`Fdt::new`](crate::Fdt::new).
+    pub fn new_aligned(buffer: &'b mut [u32]) -> Self {
+        Self::new(crate::util::cast_slice_mut(buffer))
+    }
+
+    /// Set the physical ID of the system's boot CPU, written to the
+    /// `boot_cpuid_phys` header field. Defaults to `0`.
+    pub fn set_boot_cpuid(&mut self, boot_cpuid: u32) {
+        self.boot_cpuid = boot_cpuid;
+    }
+
+    /// Current depth in the node hierarchy, where `0` means no node is
+    /// currently open and `1` means the root node is open.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Add an entry to the memory reservation block. All memory reservations
+    /// must be added before the root node is started.
+    pub fn add_memory_reservation(&mut self, address: u64, size: u64) -> Result<(), FdtError> {
+        if self.state != BuilderState::MemoryReservations {
+            return Err(FdtError::InvalidBuilderState);
+        }
+
+        let mut entry = [0u8; MEMORY_RESERVATION_SIZE];
+        entry[..8].copy_from_slice(&address.to_be_bytes());
+        entry[8..].copy_from_slice(&size.to_be_bytes());
+
+        self.write(&entry)
+    }
+
+    /// Start a new node with the given name, including the unit address if
+    /// any (e.g. `memory@80000000`). The first node started is the root node.
+    pub fn begin_node(&mut self, name: &str) -> Result<(), FdtError> {
+        match self.state {
+            BuilderState::MemoryReservations => {
+                self.write(&[0; MEMORY_RESERVATION_SIZE])?;
+                self.structs_offset = self.cursor;
+                self.state = BuilderState::Nodes;
+            }
+            BuilderState::Nodes => {}
+            BuilderState::RootClosed => return Err(FdtError::InvalidBuilderState),
+        }
+
+        let name = match (self.depth, name) {
+            (0, "/") => "",
+            (0, "") => "",
+            (0, _) | (_, "") => return Err(FdtError::InvalidNodeName),
+            (_, name) => name,
+        };
+
+        if name.contains(['\0', '/']) {
+            return Err(FdtError::InvalidNodeName);
+        }
+
+        self.ensure_space(4 + align4(name.len() + 1))?;
+        self.write_token(BigEndianToken::BEGIN_NODE)?;
+        self.write(name.as_bytes())?;
+        self.write(&[0])?;
+        self.pad()?;
+        self.depth += 1;
+
+        Ok(())
+    }
+
+    /// End the most recently started node.
+    pub fn end_node(&mut self) -> Result<(), FdtError> {
+        if self.depth == 0 {
+            return Err(FdtError::InvalidBuilderState);
+        }
+
+        self.write_token(BigEndianToken::END_NODE)?;
+        self.depth -= 1;
+
+        if self.depth == 0 {
+            self.state = BuilderState::RootClosed;
+        }
+
+        Ok(())
+    }
+
+    /// Add a property with a raw value to the current node. Pass an empty
+    /// value to create an empty (boolean) property.
+    pub fn property(&mut self, name: &str, value: &[u8]) -> Result<(), FdtError> {
+        self.property_with(name, value.len(), |buffer| buffer.copy_from_slice(value))
+    }
+
+    /// Add a property with a single `<u32>` value to the current node.
+    pub fn property_u32(&mut self, name: &str, value: u32) -> Result<(), FdtError> {
+        self.property(name, &value.to_be_bytes())
+    }
+
+    /// Add a property with a single `<u64>` value to the current node.
+    pub fn property_u64(&mut self, name: &str, value: u64) -> Result<(), FdtError> {
+        self.property(name, &value.to_be_bytes())
+    }
+
+    /// Add a property with a `<prop-encoded-array>` of `<u32>` cells to the
+    /// current node.
+    pub fn property_cells(&mut self, name: &str, cells: &[u32]) -> Result<(), FdtError> {
+        self.property_with(name, cells.len() * 4, |buffer| {
+            for (chunk, cell) in buffer.chunks_exact_mut(4).zip(cells) {
+                chunk.copy_from_slice(&cell.to_be_bytes());
+            }
+        })
+    }
+
+    /// Add a property with a single `<string>` value to the current node.
+    pub fn property_string(&mut self, name: &str, value: &str) -> Result<(), FdtError> {
+        self.property_string_list(name, &[value])
+    }
+
+    /// Add a property with a `<stringlist>` value to the current node.
+    pub fn property_string_list(&mut self, name: &str, values: &[&str]) -> Result<(), FdtError> {
+        if values.iter().any(|s| s.contains('\0')) {
+            return Err(FdtError::InvalidPropertyValue);
+        }
+
+        let len = values.iter().map(|s| s.len() + 1).sum();
+        self.property_with(name, len, |mut buffer| {
+            for value in values {
+                let (string, rest) = buffer.split_at_mut(value.len() + 1);
+                string[..value.len()].copy_from_slice(value.as_bytes());
+                string[value.len()] = 0;
+                buffer = rest;
+            }
+        })
+    }
+
+    /// Add a `reg` property to the current node, encoding each entry with the
+    /// given [`CellSizes`], which should be the cell sizes of the parent node.
+    /// Returns [`FdtError::CollectCellsError`] if a value doesn't fit into
+    /// the number of cells available, and [`FdtError::InvalidPropertyValue`]
+    /// if there are entries but the cell sizes leave no room to encode them.
+    pub fn property_reg(&mut self, cell_sizes: CellSizes, entries: &[RegEntry<u64, u64>]) -> Result<(), FdtError> {
+        let entry_size = (cell_sizes.address_cells + cell_sizes.size_cells) * 4;
+        if entry_size == 0 && !entries.is_empty() {
+            return Err(FdtError::InvalidPropertyValue);
+        }
+
+        for entry in entries {
+            if !fits_in_cells(entry.address, cell_sizes.address_cells)
+                || !fits_in_cells(entry.len, cell_sizes.size_cells)
+            {
+                return Err(FdtError::CollectCellsError);
+            }
+        }
+
+        self.property_with("reg", entries.len() * entry_size, |buffer| {
+            for (chunk, entry) in buffer.chunks_exact_mut(entry_size).zip(entries) {
+                let (address, len) = chunk.split_at_mut(cell_sizes.address_cells * 4);
+                write_cells(address, entry.address);
+                write_cells(len, entry.len);
+            }
+        })
+    }
+
+    /// Finish serializing the devicetree, returning its total size in bytes.
+    /// The root node must have been ended before calling this method.
+    pub fn finish(mut self) -> Result<usize, FdtError> {
+        if self.state != BuilderState::RootClosed {
+            return Err(FdtError::InvalidBuilderState);
+        }
+
+        self.write_token(BigEndianToken::END)?;
+
+        let structs_size = self.cursor - self.structs_offset;
+        let strings_offset = self.cursor;
+        let strings_start = self.buffer.len() - self.strings_size;
+        self.buffer.copy_within(strings_start.., strings_offset);
+
+        // Property name offsets were recorded relative to the end of the
+        // strings block while it was being built, so now they need to be
+        // fixed up to be relative to its start.
+        let mut offset = self.structs_offset;
+        while offset < strings_offset {
+            let token = self.read_u32(offset);
+            offset += 4;
+
+            match BigEndianToken(crate::parsing::BigEndianU32::from_ne(token)) {
+                BigEndianToken::BEGIN_NODE => {
+                    let name_len = self.buffer[offset..].iter().position(|b| *b == 0).unwrap();
+                    offset += align4(name_len + 1);
+                }
+                BigEndianToken::PROP => {
+                    let len = self.read_u32(offset) as usize;
+                    let from_end = self.read_u32(offset + 4) as usize;
+                    let name_offset = (self.strings_size - from_end) as u32;
+                    self.buffer[offset + 4..offset + 8].copy_from_slice(&name_offset.to_be_bytes());
+                    offset += 8 + align4(len);
+                }
+                _ => {}
+            }
+        }
+
+        let total_size = strings_offset + self.strings_size;
+        let header = [
+            FDT_MAGIC,
+            total_size as u32,
+            self.structs_offset as u32,
+            strings_offset as u32,
+            HEADER_SIZE as u32,
+            FDT_VERSION,
+            FDT_LAST_COMPATIBLE_VERSION,
+            self.boot_cpuid,
+            self.strings_size as u32,
+            structs_size as u32,
+        ];
+
+        for (chunk, field) in self.buffer[..HEADER_SIZE].chunks_exact_mut(4).zip(header) {
+            chunk.copy_from_slice(&field.to_be_bytes());
+        }
+
+        Ok(total_size)
+    }
+
+    fn property_with(&mut self, name: &str, len: usize, fill: impl FnOnce(&mut [u8])) -> Result<(), FdtError> {
+        if self.depth == 0 {
+            return Err(FdtError::InvalidBuilderState);
+        } else if name.is_empty() || name.contains('\0') {
+            return Err(FdtError::InvalidPropertyValue);
+        }
+
+        let name_offset = self.add_string(name)?;
+
+        self.ensure_space(12 + align4(len))?;
+        self.write_token(BigEndianToken::PROP)?;
+        self.write(&(len as u32).to_be_bytes())?;
+        self.write(&(name_offset as u32).to_be_bytes())?;
+        fill(&mut self.buffer[self.cursor..][..len]);
+        self.cursor += len;
+        self.pad()
+    }
+
+    /// Returns the offset of `name` from the end of the strings block, adding
+    /// it to the block if an existing string (or string suffix) can't be
+    /// reused.
+    fn add_string(&mut self, name: &str) -> Result<usize, FdtError> {
+        let strings = &self.buffer[self.buffer.len() - self.strings_size..];
+        let needle_len = name.len() + 1;
+        let existing = strings
+            .windows(needle_len)
+            .position(|window| &window[..name.len()] == name.as_bytes() && window[name.len()] == 0);
+
+        if let Some(position) = existing {
+            return Ok(self.strings_size - position);
+        }
+
+        if self.free_space() < needle_len {
+            return Err(FdtError::SliceTooSmall);
+        }
+
+        self.strings_size += needle_len;
+        let start = self.buffer.len() - self.strings_size;
+        self.buffer[start..][..name.len()].copy_from_slice(name.as_bytes());
+        self.buffer[start + name.len()] = 0;
+
+        Ok(self.strings_size)
+    }
+
+    fn free_space(&self) -> usize {
+        self.buffer.len().saturating_sub(self.cursor + self.strings_size)
+    }
+
+    fn ensure_space(&self, len: usize) -> Result<(), FdtError> {
+        match self.free_space() >= len {
+            true => Ok(()),
+            false => Err(FdtError::SliceTooSmall),
+        }
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), FdtError> {
+        self.ensure_space(data.len())?;
+        self.buffer[self.cursor..][..data.len()].copy_from_slice(data);
+        self.cursor += data.len();
+
+        Ok(())
+    }
+
+    fn write_token(&mut self, token: BigEndianToken) -> Result<(), FdtError> {
+        self.write(&token.0.to_ne().to_be_bytes())
+    }
+
+    fn pad(&mut self) -> Result<(), FdtError> {
+        let padding = align4(self.cursor) - self.cursor;
+        self.write(&[0; 3][..padding])
+    }
+
+    fn read_u32(&self, offset: usize) -> u32 {
+        u32::from_be_bytes(self.buffer[offset..offset + 4].try_into().unwrap())
+    }
+}
+
+fn fits_in_cells(value: u64, cells: usize) -> bool {
+    cells >= 2 || value >> (cells * 32) == 0
+}
+
+fn write_cells(buffer: &mut [u8], value: u64) {
+    buffer.fill(0);
+    let bytes = value.to_be_bytes();
+    let len = buffer.len().min(8);
+    let start = buffer.len() - len;
+    buffer[start..].copy_from_slice(&bytes[8 - len..]);
+}
@@ -257,3 +257,133 @@ impl<Int: Copy + Default + core::ops::Shl<u32, Output = Int> + core::ops::BitOr<
         builder_out
     }
 }
+
+/// A fixed-capacity array of up to `N` cells, for values where the number of
+/// cells is only known at runtime, such as interrupt specifiers. Collecting
+/// more than `N` cells results in a [`CollectCellsError`].
+#[derive(Debug, Clone, Copy)]
+pub struct CellArray<const N: usize> {
+    cells: [u32; N],
+    len: usize,
+}
+
+impl<const N: usize> CellArray<N> {
+    /// Create a new [`CellArray`] containing the given cells, returning `None`
+    /// if there are more than `N`.
+    pub fn new(cells: &[u32]) -> Option<Self> {
+        let mut this = Self::default();
+        this.cells.get_mut(..cells.len())?.copy_from_slice(cells);
+        this.len = cells.len();
+
+        Some(this)
+    }
+
+    /// The collected cells.
+    pub fn as_slice(&self) -> &[u32] {
+        &self.cells[..self.len]
+    }
+
+    /// Read `cells` cells from the front of `bytes`, returning them along with
+    /// the remaining bytes.
+    pub(crate) fn read_cells(bytes: &[u8], cells: usize) -> Result<(Self, &[u8]), FdtError> {
+        let Some((value, rest)) = cells.checked_mul(4).and_then(|len| bytes.split_at_checked(len)) else {
+            return Err(FdtError::InvalidPropertyValue);
+        };
+
+        let mut this = Self::default();
+        for chunk in value.chunks_exact(4) {
+            this.push(u32::from_be_bytes(chunk.try_into().unwrap()))?;
+        }
+
+        Ok((this, rest))
+    }
+
+    /// The collected cells, mutably.
+    pub(crate) fn cells_mut(&mut self) -> &mut [u32] {
+        &mut self.cells[..self.len]
+    }
+
+    /// Change the number of cells, truncating or padding with zeroes at the
+    /// end.
+    pub(crate) fn resize(mut self, len: usize) -> Result<Self, CollectCellsError> {
+        if len > N {
+            return Err(CollectCellsError);
+        }
+
+        self.cells[self.len.min(len)..].fill(0);
+        self.len = len;
+
+        Ok(self)
+    }
+}
+
+impl<const N: usize> Default for CellArray<N> {
+    fn default() -> Self {
+        Self { cells: [0; N], len: 0 }
+    }
+}
+
+impl<const N: usize> core::ops::Deref for CellArray<N> {
+    type Target = [u32];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> PartialEq for CellArray<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<const N: usize> Eq for CellArray<N> {}
+
+impl<const N: usize> core::hash::Hash for CellArray<N> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+/// Cell-wise `AND`, used for masks such as `interrupt-map-mask`. The result
+/// has as many cells as the shorter of the two arrays.
+impl<const N: usize> core::ops::BitAnd for CellArray<N> {
+    type Output = Self;
+
+    fn bitand(mut self, rhs: Self) -> Self::Output {
+        self.len = self.len.min(rhs.len);
+        for (cell, mask) in self.cells.iter_mut().zip(rhs.cells).take(self.len) {
+            *cell &= mask;
+        }
+
+        self.cells[self.len..].fill(0);
+        self
+    }
+}
+
+impl<const N: usize> BuildCellCollector for CellArray<N> {
+    type Output = Self;
+
+    #[inline(always)]
+    fn push(&mut self, component: u32) -> Result<(), CollectCellsError> {
+        *self.cells.get_mut(self.len).ok_or(CollectCellsError)? = component;
+        self.len += 1;
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn finish(self) -> Self::Output {
+        self
+    }
+}
+
+impl<const N: usize> CellCollector for CellArray<N> {
+    type Output = Self;
+    type Builder = Self;
+
+    #[inline(always)]
+    fn map(builder_out: Self) -> Self::Output {
+        builder_out
+    }
+}
@@ -0,0 +1,414 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+mod parser;
+
+use crate::{builder::FdtBuilder, util::align4, FdtError};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Compile devicetree source into a flattened devicetree blob, similar to
+/// `dtc -I dts -O dtb`.
+///
+/// The source must begin with `/dts-v1/;` and has already been run through
+/// the C preprocessor, if needed (`#include` and macros aren't supported).
+/// Supported syntax:
+///
+/// * `/memreserve/` entries
+/// * Labels on nodes, properties, and within values
+/// * `&label` and `&{/path}` references, which expand to a phandle within
+///   `<...>` and to the full path of the node otherwise. Nodes referenced by
+///   phandle which don't have a `phandle` property get one assigned.
+/// * `<...>` cell arrays, including `/bits/ 8`, `16`, `32`, and `64`,
+///   character literals, and parenthesized integer expressions
+/// * Strings, `[..]` byte strings, and comma separated lists of values
+/// * Node merging, both by repeating a path (e.g. `/ { ... };`) and with
+///   `&label { ... };`
+/// * `/delete-node/` and `/delete-property/`
+///
+/// Example:
+///
+/// ```rust
+/// use fdt::properties::interrupts::InterruptParent;
+///
+/// let source = r#"
+///     /dts-v1/;
+///
+///     / {
+///         model = "my-board";
+///
+///         intc: interrupt-controller {
+///             interrupt-controller;
+///         };
+///
+///         uart@10000000 {
+///             interrupt-parent = <&intc>;
+///         };
+///     };
+/// "#;
+///
+/// let dtb = fdt::dts::compile(source).unwrap();
+/// let fdt = fdt::Fdt::new_unaligned(&dtb).unwrap();
+///
+/// assert_eq!(fdt.root().model(), "my-board");
+/// let uart = fdt.find_node("/uart@10000000").unwrap();
+/// let interrupt_parent = uart.property::<InterruptParent<_>>().unwrap();
+/// assert_eq!(interrupt_parent.name().name, "interrupt-controller");
+/// ```
+pub fn compile(source: &str) -> Result<Vec<u8>, DtsError> {
+    let mut tree = parser::Parser::new(source).parse()?;
+    tree.resolve_references(source)?;
+
+    tree.to_dtb().map_err(|e| DtsError { line: 0, column: 0, kind: DtsErrorKind::Fdt(e) })
+}
+
+/// An error encountered while compiling devicetree source.
+#[derive(Debug, Clone)]
+pub struct DtsError {
+    /// The line the error occurred on, starting at 1. Errors which aren't
+    /// associated with a location in the source, such as those returned when
+    /// serializing the devicetree, have a line of `0`.
+    pub line: usize,
+    /// The column the error occurred at, starting at 1.
+    pub column: usize,
+    /// What went wrong.
+    pub kind: DtsErrorKind,
+}
+
+impl core::fmt::Display for DtsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.line {
+            0 => write!(f, "{}", self.kind),
+            line => write!(f, "{}:{}: {}", line, self.column, self.kind),
+        }
+    }
+}
+
+/// The kinds of errors which can be encountered while compiling devicetree
+/// source.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum DtsErrorKind {
+    /// The source doesn't begin with `/dts-v1/;`.
+    MissingVersion,
+    /// The source ended in the middle of a definition.
+    UnexpectedEof,
+    /// An unexpected character was encountered.
+    UnexpectedCharacter(char),
+    /// A specific token was expected but not found.
+    Expected(&'static str),
+    /// A `/directive/` which isn't supported.
+    UnsupportedDirective(String),
+    /// An integer literal couldn't be parsed.
+    InvalidNumber,
+    /// A value doesn't fit in the size of the cells it's contained in.
+    ValueOutOfRange,
+    /// Division or remainder by zero in an integer expression.
+    DivisionByZero,
+    /// An invalid escape sequence in a string or character literal.
+    InvalidEscape,
+    /// `/bits/` was given a size other than 8, 16, 32, or 64.
+    InvalidBits,
+    /// A phandle reference within a cell array which isn't 32 bits wide.
+    InvalidReference,
+    /// A label was defined more than once.
+    DuplicateLabel(String),
+    /// A reference to a label which isn't defined.
+    UndefinedLabel(String),
+    /// A reference to a path which doesn't exist.
+    UndefinedPath(String),
+    /// Serializing the devicetree failed.
+    Fdt(FdtError),
+}
+
+impl core::fmt::Display for DtsErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DtsErrorKind::MissingVersion => write!(f, "missing `/dts-v1/;` header"),
+            DtsErrorKind::UnexpectedEof => write!(f, "unexpected end of file"),
+            DtsErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character {c:?}"),
+            DtsErrorKind::Expected(what) => write!(f, "expected {what}"),
+            DtsErrorKind::UnsupportedDirective(directive) => write!(f, "unsupported directive `{directive}`"),
+            DtsErrorKind::InvalidNumber => write!(f, "invalid integer literal"),
+            DtsErrorKind::ValueOutOfRange => write!(f, "value out of range for the cell size"),
+            DtsErrorKind::DivisionByZero => write!(f, "division by zero"),
+            DtsErrorKind::InvalidEscape => write!(f, "invalid escape sequence"),
+            DtsErrorKind::InvalidBits => write!(f, "`/bits/` size must be 8, 16, 32, or 64"),
+            DtsErrorKind::InvalidReference => write!(f, "phandle references are only allowed in 32-bit cells"),
+            DtsErrorKind::DuplicateLabel(label) => write!(f, "duplicate label `{label}`"),
+            DtsErrorKind::UndefinedLabel(label) => write!(f, "reference to undefined label `{label}`"),
+            DtsErrorKind::UndefinedPath(path) => write!(f, "reference to non-existent node `{path}`"),
+            DtsErrorKind::Fdt(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Tree {
+    memory_reservations: Vec<(u64, u64)>,
+    root: Node,
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    name: String,
+    labels: Vec<String>,
+    properties: Vec<Property>,
+    children: Vec<Node>,
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    labels: Vec<String>,
+    value: Vec<u8>,
+    references: Vec<Reference>,
+}
+
+/// A `&label` or `&{/path}` reference within a property value which is
+/// resolved once the whole tree has been parsed.
+#[derive(Debug)]
+struct Reference {
+    /// Offset within the property value. Phandle references have 4 bytes
+    /// reserved at the offset, while path references are inserted.
+    offset: usize,
+    target: Target,
+    kind: ReferenceKind,
+    /// Byte position within the source, for error reporting.
+    position: usize,
+}
+
+#[derive(Debug)]
+enum Target {
+    Label(String),
+    Path(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReferenceKind {
+    Phandle,
+    Path,
+}
+
+impl Tree {
+    /// Find the node referred to by `target`, returning the indices of the
+    /// children to follow from the root node.
+    fn find(&self, target: &Target) -> Option<Vec<usize>> {
+        match target {
+            Target::Label(label) => {
+                let mut indices = Vec::new();
+                self.root.find_label(label, &mut indices).then_some(indices)
+            }
+            Target::Path(path) => {
+                let mut indices = Vec::new();
+                let mut node = &self.root;
+                for component in path.split('/').filter(|c| !c.is_empty()) {
+                    let index = node.child_index(component)?;
+                    indices.push(index);
+                    node = &node.children[index];
+                }
+
+                Some(indices)
+            }
+        }
+    }
+
+    fn node(&self, indices: &[usize]) -> &Node {
+        indices.iter().fold(&self.root, |node, i| &node.children[*i])
+    }
+
+    fn node_mut(&mut self, indices: &[usize]) -> &mut Node {
+        indices.iter().fold(&mut self.root, |node, i| &mut node.children[*i])
+    }
+
+    fn path(&self, indices: &[usize]) -> String {
+        let mut path = String::new();
+        let mut node = &self.root;
+        for i in indices {
+            node = &node.children[*i];
+            path.push('/');
+            path.push_str(&node.name);
+        }
+
+        match path.is_empty() {
+            true => String::from("/"),
+            false => path,
+        }
+    }
+
+    /// Returns the phandle of the node at `indices`, assigning it one if it
+    /// doesn't already have one. New phandles are assigned the same way `dtc`
+    /// does, using the lowest unused value greater than the previously
+    /// assigned one.
+    fn phandle(&mut self, indices: &[usize], next_phandle: &mut u32) -> u32 {
+        if let Some(phandle) = self.node(indices).phandle() {
+            return phandle;
+        }
+
+        while self.root.has_phandle(*next_phandle) {
+            *next_phandle += 1;
+        }
+
+        let phandle = *next_phandle;
+        self.node_mut(indices).properties.push(Property {
+            name: String::from("phandle"),
+            labels: Vec::new(),
+            value: phandle.to_be_bytes().to_vec(),
+            references: Vec::new(),
+        });
+
+        phandle
+    }
+
+    fn resolve_references(&mut self, source: &str) -> Result<(), DtsError> {
+        let mut labels = Vec::new();
+        self.root.collect_labels(&mut labels);
+        labels.sort_unstable();
+        if let Some(duplicate) = labels.windows(2).find(|w| w[0] == w[1]) {
+            return Err(DtsError { line: 0, column: 0, kind: DtsErrorKind::DuplicateLabel(duplicate[0].to_string()) });
+        }
+
+        // Gather the locations of every reference up front, as resolving them
+        // can add `phandle` properties to the tree
+        let mut locations = Vec::new();
+        self.root.collect_references(&mut Vec::new(), &mut locations);
+
+        let mut next_phandle = 1;
+        for (indices, property, reference) in locations {
+            let target = &self.node(&indices).properties[property].references[reference];
+            let Some(target_indices) = self.find(&target.target) else {
+                let kind = match &target.target {
+                    Target::Label(label) => DtsErrorKind::UndefinedLabel(label.clone()),
+                    Target::Path(path) => DtsErrorKind::UndefinedPath(path.clone()),
+                };
+
+                return Err(parser::error_at(source, target.position, kind));
+            };
+
+            match target.kind {
+                ReferenceKind::Phandle => {
+                    let phandle = self.phandle(&target_indices, &mut next_phandle);
+                    let property = &mut self.node_mut(&indices).properties[property];
+                    let offset = property.references[reference].offset;
+                    property.value[offset..offset + 4].copy_from_slice(&phandle.to_be_bytes());
+                }
+                ReferenceKind::Path => {
+                    let path = self.path(&target_indices);
+                    let property = &mut self.node_mut(&indices).properties[property];
+                    let offset = property.references[reference].offset;
+                    property.value.splice(offset..offset, path.bytes().chain([0]));
+
+                    for later in &mut property.references[reference + 1..] {
+                        later.offset += path.len() + 1;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn to_dtb(&self) -> Result<Vec<u8>, FdtError> {
+        let size = 40 + 16 * (self.memory_reservations.len() + 1) + self.root.serialized_size() + 4;
+        let mut buffer = vec![0; size];
+        let mut builder = FdtBuilder::new(&mut buffer);
+
+        for (address, size) in &self.memory_reservations {
+            builder.add_memory_reservation(*address, *size)?;
+        }
+
+        self.root.build(&mut builder)?;
+        let size = builder.finish()?;
+        buffer.truncate(size);
+
+        Ok(buffer)
+    }
+}
+
+impl Node {
+    fn child_index(&self, name: &str) -> Option<usize> {
+        let exact = self.children.iter().position(|child| child.name == name);
+        match name.contains('@') {
+            true => exact,
+            false => exact.or_else(|| {
+                self.children.iter().position(|child| child.name.split_once('@').is_some_and(|(base, _)| base == name))
+            }),
+        }
+    }
+
+    fn find_label(&self, label: &str, indices: &mut Vec<usize>) -> bool {
+        if self.labels.iter().any(|l| l == label) {
+            return true;
+        }
+
+        for (i, child) in self.children.iter().enumerate() {
+            indices.push(i);
+            if child.find_label(label, indices) {
+                return true;
+            }
+            indices.pop();
+        }
+
+        false
+    }
+
+    fn phandle(&self) -> Option<u32> {
+        self.properties
+            .iter()
+            .find(|p| p.name == "phandle" || p.name == "linux,phandle")
+            .and_then(|p| Some(u32::from_be_bytes(p.value.as_slice().try_into().ok()?)))
+    }
+
+    fn has_phandle(&self, phandle: u32) -> bool {
+        self.phandle() == Some(phandle) || self.children.iter().any(|c| c.has_phandle(phandle))
+    }
+
+    fn collect_labels<'a>(&'a self, labels: &mut Vec<&'a str>) {
+        labels.extend(self.labels.iter().map(String::as_str));
+        for property in &self.properties {
+            labels.extend(property.labels.iter().map(String::as_str));
+        }
+
+        for child in &self.children {
+            child.collect_labels(labels);
+        }
+    }
+
+    fn collect_references(&self, indices: &mut Vec<usize>, locations: &mut Vec<(Vec<usize>, usize, usize)>) {
+        for (p, property) in self.properties.iter().enumerate() {
+            locations.extend((0..property.references.len()).map(|r| (indices.clone(), p, r)));
+        }
+
+        for (i, child) in self.children.iter().enumerate() {
+            indices.push(i);
+            child.collect_references(indices, locations);
+            indices.pop();
+        }
+    }
+
+    /// Upper bound on the number of bytes the node takes up in the structs
+    /// and strings blocks.
+    fn serialized_size(&self) -> usize {
+        let properties: usize = self.properties.iter().map(|p| 12 + align4(p.value.len()) + p.name.len() + 1).sum();
+        let children: usize = self.children.iter().map(Node::serialized_size).sum();
+
+        8 + align4(self.name.len() + 1) + properties + children
+    }
+
+    fn build(&self, builder: &mut FdtBuilder<'_>) -> Result<(), FdtError> {
+        builder.begin_node(&self.name)?;
+        for property in &self.properties {
+            builder.property(&property.name, &property.value)?;
+        }
+
+        for child in &self.children {
+            child.build(builder)?;
+        }
+
+        builder.end_node()
+    }
+}
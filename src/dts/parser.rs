@@ -0,0 +1,615 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use super::{DtsError, DtsErrorKind, Node, Property, Reference, ReferenceKind, Target, Tree};
+use alloc::{string::String, vec::Vec};
+
+/// Build a [`DtsError`] pointing at the byte `position` within `source`.
+pub(super) fn error_at(source: &str, position: usize, kind: DtsErrorKind) -> DtsError {
+    let before = &source[..position];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+
+    DtsError { line, column, kind }
+}
+
+fn is_name_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || b",._+*#?@-".contains(&c)
+}
+
+fn is_label_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+/// Binary operators in order of increasing precedence, as in C.
+const BINARY_OPERATORS: &[&[&str]] = &[
+    &["||"],
+    &["&&"],
+    &["|"],
+    &["^"],
+    &["&"],
+    &["==", "!="],
+    &["<=", ">=", "<", ">"],
+    &["<<", ">>"],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+
+pub(super) struct Parser<'s> {
+    source: &'s str,
+    bytes: &'s [u8],
+    position: usize,
+}
+
+impl<'s> Parser<'s> {
+    pub(super) fn new(source: &'s str) -> Self {
+        Self { source, bytes: source.as_bytes(), position: 0 }
+    }
+
+    pub(super) fn parse(mut self) -> Result<Tree, DtsError> {
+        let mut tree = Tree::default();
+
+        self.skip_whitespace()?;
+        if self.peek_directive() != Some("/dts-v1/") {
+            return Err(self.error(DtsErrorKind::MissingVersion));
+        }
+
+        while self.peek_directive() == Some("/dts-v1/") {
+            self.position += "/dts-v1/".len();
+            self.expect(b';', "`;`")?;
+            self.skip_whitespace()?;
+        }
+
+        loop {
+            self.skip_whitespace()?;
+            let labels = self.labels()?;
+
+            match self.peek() {
+                None if labels.is_empty() => return Ok(tree),
+                None => return Err(self.error(DtsErrorKind::UnexpectedEof)),
+                Some(b'&') => {
+                    let (target, position) = self.reference()?;
+                    let Some(indices) = tree.find(&target) else {
+                        return Err(self.undefined(target, position));
+                    };
+
+                    let node = tree.node_mut(&indices);
+                    node.labels.extend(labels);
+                    self.node_body(node)?;
+                }
+                Some(b'/') => match self.peek_directive() {
+                    Some("/memreserve/") => {
+                        self.position += "/memreserve/".len();
+                        let address = self.prim()?;
+                        let size = self.prim()?;
+                        self.expect(b';', "`;`")?;
+                        tree.memory_reservations.push((address, size));
+                    }
+                    Some("/delete-node/") => {
+                        self.position += "/delete-node/".len();
+                        self.skip_whitespace()?;
+                        let (target, position) = self.reference()?;
+                        self.expect(b';', "`;`")?;
+
+                        match tree.find(&target) {
+                            Some(indices) => match indices.split_last() {
+                                Some((last, parent)) => _ = tree.node_mut(parent).children.remove(*last),
+                                None => tree.root = Node::default(),
+                            },
+                            None => return Err(self.undefined(target, position)),
+                        }
+                    }
+                    Some(directive) => {
+                        return Err(self.error(DtsErrorKind::UnsupportedDirective(String::from(directive))))
+                    }
+                    None => {
+                        self.position += 1;
+                        tree.root.labels.extend(labels);
+                        self.node_body(&mut tree.root)?;
+                    }
+                },
+                Some(c) => return Err(self.error(DtsErrorKind::UnexpectedCharacter(c as char))),
+            }
+        }
+    }
+
+    /// Parse `{ ... };` into `node`, merging with its existing contents.
+    fn node_body(&mut self, node: &mut Node) -> Result<(), DtsError> {
+        self.expect(b'{', "`{`")?;
+
+        loop {
+            self.skip_whitespace()?;
+            if self.eat(b'}') {
+                return self.expect(b';', "`;`");
+            }
+
+            if self.peek() == Some(b'/') {
+                let directive = self.peek_directive();
+                match directive {
+                    Some("/delete-property/") | Some("/delete-node/") => {
+                        self.position += directive.unwrap().len();
+                        self.skip_whitespace()?;
+                        let name = self.name()?;
+                        self.expect(b';', "`;`")?;
+
+                        match directive {
+                            Some("/delete-property/") => node.properties.retain(|p| p.name != name),
+                            _ => node.children.retain(|c| c.name != name),
+                        }
+
+                        continue;
+                    }
+                    Some(directive) => {
+                        return Err(self.error(DtsErrorKind::UnsupportedDirective(String::from(directive))))
+                    }
+                    None => return Err(self.error(DtsErrorKind::UnexpectedCharacter('/'))),
+                }
+            }
+
+            let labels = self.labels()?;
+            let name = self.name()?;
+            self.skip_whitespace()?;
+
+            match self.peek() {
+                Some(b'{') => {
+                    let index = match node.children.iter().position(|c| c.name == name) {
+                        Some(index) => index,
+                        None => {
+                            node.children.push(Node { name: String::from(name), ..Node::default() });
+                            node.children.len() - 1
+                        }
+                    };
+
+                    let child = &mut node.children[index];
+                    child.labels.extend(labels);
+                    self.node_body(child)?;
+                }
+                Some(b'=') | Some(b';') => {
+                    let mut property =
+                        Property { name: String::from(name), labels, value: Vec::new(), references: Vec::new() };
+
+                    if self.eat(b'=') {
+                        self.property_value(&mut property)?;
+                    }
+                    self.expect(b';', "`;`")?;
+
+                    match node.properties.iter_mut().find(|p| p.name == name) {
+                        Some(existing) => {
+                            property.labels.append(&mut existing.labels);
+                            *existing = property;
+                        }
+                        None => node.properties.push(property),
+                    }
+                }
+                Some(c) => return Err(self.error(DtsErrorKind::UnexpectedCharacter(c as char))),
+                None => return Err(self.error(DtsErrorKind::UnexpectedEof)),
+            }
+        }
+    }
+
+    /// Parse a comma separated list of strings, cell arrays, byte strings, and
+    /// path references.
+    fn property_value(&mut self, property: &mut Property) -> Result<(), DtsError> {
+        loop {
+            self.skip_whitespace()?;
+            self.labels()?;
+
+            match self.peek() {
+                Some(b'"') => {
+                    self.string(&mut property.value)?;
+                    property.value.push(0);
+                }
+                Some(b'<') => self.cells(32, property)?,
+                Some(b'[') => self.bytes(&mut property.value)?,
+                Some(b'&') => {
+                    let (target, position) = self.reference()?;
+                    let offset = property.value.len();
+                    property.references.push(Reference { offset, target, kind: ReferenceKind::Path, position });
+                }
+                Some(b'/') => match self.peek_directive() {
+                    Some("/bits/") => {
+                        self.position += "/bits/".len();
+                        let bits = match self.prim()? {
+                            bits @ (8 | 16 | 32 | 64) => bits as u32,
+                            _ => return Err(self.error(DtsErrorKind::InvalidBits)),
+                        };
+
+                        self.skip_whitespace()?;
+                        self.cells(bits, property)?;
+                    }
+                    Some(directive) => {
+                        return Err(self.error(DtsErrorKind::UnsupportedDirective(String::from(directive))))
+                    }
+                    None => return Err(self.error(DtsErrorKind::UnexpectedCharacter('/'))),
+                },
+                Some(c) => return Err(self.error(DtsErrorKind::UnexpectedCharacter(c as char))),
+                None => return Err(self.error(DtsErrorKind::UnexpectedEof)),
+            }
+
+            self.skip_whitespace()?;
+            self.labels()?;
+            if !self.eat(b',') {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Parse `<...>` with each cell being `bits` wide.
+    fn cells(&mut self, bits: u32, property: &mut Property) -> Result<(), DtsError> {
+        self.expect(b'<', "`<`")?;
+
+        loop {
+            self.skip_whitespace()?;
+            self.labels()?;
+
+            match self.peek() {
+                Some(b'>') => {
+                    self.position += 1;
+                    return Ok(());
+                }
+                Some(b'&') if bits == 32 => {
+                    let (target, position) = self.reference()?;
+                    let offset = property.value.len();
+                    property.references.push(Reference { offset, target, kind: ReferenceKind::Phandle, position });
+                    property.value.extend_from_slice(&[0; 4]);
+                }
+                Some(b'&') => return Err(self.error(DtsErrorKind::InvalidReference)),
+                _ => {
+                    let start = self.position;
+                    let value = self.prim()?;
+                    let mask = u64::MAX >> (64 - bits);
+
+                    // Negative values are allowed as long as they sign extend
+                    // into the cell size
+                    if value & !mask != 0 && !value & !mask != 0 {
+                        return Err(error_at(self.source, start, DtsErrorKind::ValueOutOfRange));
+                    }
+
+                    let bytes = value.to_be_bytes();
+                    property.value.extend_from_slice(&bytes[8 - bits as usize / 8..]);
+                }
+            }
+        }
+    }
+
+    /// Parse `[..]`, a list of hexadecimal bytes optionally separated by
+    /// whitespace.
+    fn bytes(&mut self, value: &mut Vec<u8>) -> Result<(), DtsError> {
+        self.expect(b'[', "`[`")?;
+
+        loop {
+            self.skip_whitespace()?;
+            self.labels()?;
+
+            match self.peek() {
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(());
+                }
+                Some(_) => {
+                    let byte = self
+                        .source
+                        .get(self.position..self.position + 2)
+                        .filter(|digits| digits.bytes().all(|c| c.is_ascii_hexdigit()))
+                        .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                        .ok_or_else(|| self.error(DtsErrorKind::Expected("a pair of hexadecimal digits")))?;
+
+                    value.push(byte);
+                    self.position += 2;
+                }
+                None => return Err(self.error(DtsErrorKind::UnexpectedEof)),
+            }
+        }
+    }
+
+    /// Parse a double quoted string, appending its contents to `value`.
+    fn string(&mut self, value: &mut Vec<u8>) -> Result<(), DtsError> {
+        self.expect(b'"', "`\"`")?;
+
+        loop {
+            match self.next_byte()? {
+                b'"' => return Ok(()),
+                b'\\' => value.push(self.escape()?),
+                c => value.push(c),
+            }
+        }
+    }
+
+    /// Parse the escape sequence following a `\`.
+    fn escape(&mut self) -> Result<u8, DtsError> {
+        let start = self.position;
+        let radix_digits = |this: &mut Self, radix: u32, max: usize| {
+            let digits =
+                this.bytes[this.position..].iter().take(max).take_while(|c| (**c as char).is_digit(radix)).count();
+            let value = u32::from_str_radix(&this.source[this.position..this.position + digits], radix);
+            this.position += digits;
+
+            match value {
+                Ok(value) if value <= 0xff => Ok(value as u8),
+                _ => Err(error_at(this.source, start, DtsErrorKind::InvalidEscape)),
+            }
+        };
+
+        Ok(match self.next_byte()? {
+            b'a' => 0x07,
+            b'b' => 0x08,
+            b't' => b'\t',
+            b'n' => b'\n',
+            b'v' => 0x0b,
+            b'f' => 0x0c,
+            b'r' => b'\r',
+            b'x' => radix_digits(self, 16, 2)?,
+            b'0'..=b'7' => {
+                self.position -= 1;
+                radix_digits(self, 8, 3)?
+            }
+            c @ (b'\\' | b'"' | b'\'') => c,
+            _ => return Err(error_at(self.source, start, DtsErrorKind::InvalidEscape)),
+        })
+    }
+
+    /// Parse `&label` or `&{/path}`, returning the target and the position of
+    /// the reference.
+    fn reference(&mut self) -> Result<(Target, usize), DtsError> {
+        let position = self.position;
+        self.expect(b'&', "`&`")?;
+
+        if self.eat(b'{') {
+            let len = self.bytes[self.position..]
+                .iter()
+                .position(|c| *c == b'}')
+                .ok_or_else(|| self.error(DtsErrorKind::UnexpectedEof))?;
+            let path = &self.source[self.position..self.position + len];
+            self.position += len + 1;
+
+            return Ok((Target::Path(String::from(path)), position));
+        }
+
+        let len = self.bytes[self.position..].iter().take_while(|c| is_label_char(**c)).count();
+        if len == 0 {
+            return Err(self.error(DtsErrorKind::Expected("a label")));
+        }
+
+        let label = &self.source[self.position..self.position + len];
+        self.position += len;
+
+        Ok((Target::Label(String::from(label)), position))
+    }
+
+    /// Parse any number of `label:` definitions.
+    fn labels(&mut self) -> Result<Vec<String>, DtsError> {
+        let mut labels = Vec::new();
+
+        loop {
+            let rest = &self.bytes[self.position..];
+            let len = rest.iter().take_while(|c| is_label_char(**c)).count();
+
+            match rest.get(len) {
+                Some(b':') if len > 0 && !rest[0].is_ascii_digit() => {
+                    labels.push(String::from(&self.source[self.position..self.position + len]));
+                    self.position += len + 1;
+                    self.skip_whitespace()?;
+                }
+                _ => return Ok(labels),
+            }
+        }
+    }
+
+    /// Parse a node or property name.
+    fn name(&mut self) -> Result<&'s str, DtsError> {
+        let len = self.bytes[self.position..].iter().take_while(|c| is_name_char(**c)).count();
+        if len == 0 {
+            return Err(match self.peek() {
+                Some(c) => self.error(DtsErrorKind::UnexpectedCharacter(c as char)),
+                None => self.error(DtsErrorKind::UnexpectedEof),
+            });
+        }
+
+        let name = &self.source[self.position..self.position + len];
+        self.position += len;
+
+        Ok(name)
+    }
+
+    /// Parse an integer literal, character literal, or parenthesized
+    /// expression.
+    fn prim(&mut self) -> Result<u64, DtsError> {
+        self.skip_whitespace()?;
+
+        match self.peek() {
+            Some(b'(') => {
+                self.position += 1;
+                let value = self.expression()?;
+                self.expect(b')', "`)`")?;
+
+                Ok(value)
+            }
+            Some(b'\'') => {
+                self.position += 1;
+                let value = match self.next_byte()? {
+                    b'\\' => self.escape()?,
+                    c => c,
+                };
+                self.expect(b'\'', "`'`")?;
+
+                Ok(u64::from(value))
+            }
+            Some(c) if c.is_ascii_digit() => self.integer(),
+            Some(c) => Err(self.error(DtsErrorKind::UnexpectedCharacter(c as char))),
+            None => Err(self.error(DtsErrorKind::UnexpectedEof)),
+        }
+    }
+
+    fn integer(&mut self) -> Result<u64, DtsError> {
+        let start = self.position;
+        let len = self.bytes[start..].iter().take_while(|c| c.is_ascii_alphanumeric()).count();
+        self.position += len;
+
+        let literal = self.source[start..start + len].trim_end_matches(['u', 'U', 'l', 'L']);
+        let (digits, radix) = match literal.strip_prefix("0x").or_else(|| literal.strip_prefix("0X")) {
+            Some(hex) => (hex, 16),
+            None if literal.len() > 1 && literal.starts_with('0') => (&literal[1..], 8),
+            None => (literal, 10),
+        };
+
+        u64::from_str_radix(digits, radix).map_err(|_| error_at(self.source, start, DtsErrorKind::InvalidNumber))
+    }
+
+    /// Parse a C-style integer expression, with wrapping 64-bit arithmetic.
+    fn expression(&mut self) -> Result<u64, DtsError> {
+        let condition = self.binary(0)?;
+
+        self.skip_whitespace()?;
+        if !self.eat(b'?') {
+            return Ok(condition);
+        }
+
+        let if_true = self.expression()?;
+        self.expect(b':', "`:`")?;
+        let if_false = self.expression()?;
+
+        Ok(if condition != 0 { if_true } else { if_false })
+    }
+
+    fn binary(&mut self, level: usize) -> Result<u64, DtsError> {
+        let Some(operators) = BINARY_OPERATORS.get(level) else {
+            return self.unary();
+        };
+
+        let mut lhs = self.binary(level + 1)?;
+        loop {
+            self.skip_whitespace()?;
+            let rest = &self.source[self.position..];
+            // Don't mistake the first character of a longer operator (e.g.
+            // `<` in `<<`) for one with a lower precedence
+            let Some(operator) = operators.iter().find(|op| {
+                rest.starts_with(**op)
+                    && !BINARY_OPERATORS
+                        .iter()
+                        .flat_map(|ops| ops.iter())
+                        .any(|longer| longer.len() > op.len() && rest.starts_with(longer))
+            }) else {
+                return Ok(lhs);
+            };
+
+            let position = self.position;
+            self.position += operator.len();
+            let rhs = self.binary(level + 1)?;
+
+            lhs = match *operator {
+                "||" => u64::from(lhs != 0 || rhs != 0),
+                "&&" => u64::from(lhs != 0 && rhs != 0),
+                "|" => lhs | rhs,
+                "^" => lhs ^ rhs,
+                "&" => lhs & rhs,
+                "==" => u64::from(lhs == rhs),
+                "!=" => u64::from(lhs != rhs),
+                "<=" => u64::from(lhs <= rhs),
+                ">=" => u64::from(lhs >= rhs),
+                "<" => u64::from(lhs < rhs),
+                ">" => u64::from(lhs > rhs),
+                "<<" => lhs.wrapping_shl(rhs as u32),
+                ">>" => lhs.wrapping_shr(rhs as u32),
+                "+" => lhs.wrapping_add(rhs),
+                "-" => lhs.wrapping_sub(rhs),
+                "*" => lhs.wrapping_mul(rhs),
+                "/" | "%" if rhs == 0 => return Err(error_at(self.source, position, DtsErrorKind::DivisionByZero)),
+                "/" => lhs / rhs,
+                _ => lhs % rhs,
+            };
+        }
+    }
+
+    fn unary(&mut self) -> Result<u64, DtsError> {
+        self.skip_whitespace()?;
+
+        if self.eat(b'-') {
+            Ok(self.unary()?.wrapping_neg())
+        } else if self.eat(b'~') {
+            Ok(!self.unary()?)
+        } else if self.eat(b'!') {
+            Ok(u64::from(self.unary()? == 0))
+        } else {
+            self.prim()
+        }
+    }
+
+    /// Skip whitespace and comments.
+    fn skip_whitespace(&mut self) -> Result<(), DtsError> {
+        loop {
+            let rest = &self.bytes[self.position..];
+            match rest {
+                [c, ..] if c.is_ascii_whitespace() => self.position += 1,
+                [b'/', b'/', ..] => {
+                    self.position += rest.iter().position(|c| *c == b'\n').unwrap_or(rest.len());
+                }
+                [b'/', b'*', ..] => {
+                    let len = rest
+                        .windows(2)
+                        .skip(2)
+                        .position(|w| w == b"*/")
+                        .ok_or_else(|| self.error(DtsErrorKind::UnexpectedEof))?;
+                    self.position += len + 4;
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Returns the `/directive/` at the current position, if any.
+    fn peek_directive(&self) -> Option<&'s str> {
+        let rest = self.bytes.get(self.position..)?;
+        if rest.first() != Some(&b'/') {
+            return None;
+        }
+
+        let len = rest[1..].iter().take_while(|c| c.is_ascii_alphanumeric() || **c == b'-').count();
+        match rest.get(len + 1) {
+            Some(b'/') if len > 0 => Some(&self.source[self.position..self.position + len + 2]),
+            _ => None,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn next_byte(&mut self) -> Result<u8, DtsError> {
+        let c = self.peek().ok_or_else(|| self.error(DtsErrorKind::UnexpectedEof))?;
+        self.position += 1;
+
+        Ok(c)
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        match self.peek() == Some(c) {
+            true => {
+                self.position += 1;
+                true
+            }
+            false => false,
+        }
+    }
+
+    fn expect(&mut self, c: u8, what: &'static str) -> Result<(), DtsError> {
+        self.skip_whitespace()?;
+        match self.eat(c) {
+            true => Ok(()),
+            false => Err(self.error(DtsErrorKind::Expected(what))),
+        }
+    }
+
+    fn error(&self, kind: DtsErrorKind) -> DtsError {
+        error_at(self.source, self.position, kind)
+    }
+
+    fn undefined(&self, target: Target, position: usize) -> DtsError {
+        let kind = match target {
+            Target::Label(label) => DtsErrorKind::UndefinedLabel(label),
+            Target::Path(path) => DtsErrorKind::UndefinedPath(path),
+        };
+
+        error_at(self.source, position, kind)
+    }
+}
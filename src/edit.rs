@@ -0,0 +1,563 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::{
+    parsing::{unaligned::UnalignedParser, BigEndianToken, Panic, ParseError},
+    util::align4,
+    Fdt, FdtError,
+};
+
+/// Applying devicetree overlays.
+pub mod overlay;
+
+/// A flattened devicetree backed by a mutable buffer, allowing for the tree to
+/// be modified.
+///
+/// Nodes are located using the same path logic as
+/// [`Root::find_node`](crate::nodes::root::Root::find_node), and properties
+/// by name within that node. The current state of the devicetree can be read
+/// back at any point with [`FdtMut::as_fdt`].
+///
+/// Edits which change the size of the devicetree, such as
+/// [`FdtMut::set_property`] and [`FdtMut::add_node`], require the buffer to be
+/// larger than the devicetree's current
+/// [`total_size`](crate::FdtHeader::total_size), and update the header
+/// accordingly.
+///
+/// Example:
+///
+/// ```rust
+/// # let mut buffer = [0u32; 64];
+/// # let mut builder = fdt::builder::FdtBuilder::new_aligned(&mut buffer);
+/// # builder.begin_node("/").unwrap();
+/// # builder.begin_node("chosen").unwrap();
+/// # builder.property_string("bootargs", "console=ttyS0").unwrap();
+/// # builder.end_node().unwrap();
+/// # builder.end_node().unwrap();
+/// # builder.finish().unwrap();
+/// use fdt::edit::FdtMut;
+///
+/// let mut fdt = FdtMut::new(&mut buffer).unwrap();
+/// fdt.set_property_inplace("/chosen", "bootargs", b"console=ttyS1\0").unwrap();
+///
+/// assert_eq!(fdt.as_fdt().root().chosen().bootargs(), Some("console=ttyS1"));
+/// ```
+pub struct FdtMut<'b> {
+    data: &'b mut [u8],
+}
+
+impl<'b> FdtMut<'b> {
+    /// Create a new [`FdtMut`] from a `u32`-aligned buffer containing a valid
+    /// flattened devicetree.
+    pub fn new(data: &'b mut [u32]) -> Result<Self, FdtError> {
+        Self::new_unaligned(crate::util::cast_slice_mut(data))
+    }
+
+    /// Create a new [`FdtMut`] from a byte buffer containing a valid flattened
+    /// devicetree.
+    pub fn new_unaligned(data: &'b mut [u8]) -> Result<Self, FdtError> {
+        Fdt::new_unaligned_fallible(data)?;
+        Ok(Self { data })
+    }
+
+    /// Returns an [`Fdt`] for reading the current state of the devicetree.
+    pub fn as_fdt(&self) -> Fdt<'_, (UnalignedParser<'_>, Panic)> {
+        // Unwrap: the devicetree is validated on creation and every edit keeps
+        // it valid
+        Fdt::new_unaligned(self.data).unwrap()
+    }
+
+    /// Total size of the devicetree in bytes.
+    pub fn total_size(&self) -> usize {
+        self.header_field(HeaderField::TotalSize) as usize
+    }
+
+    /// Overwrite the value of the property `name` on the node at `path`
+    /// without changing its size, equivalent to libfdt's
+    /// `fdt_setprop_inplace`. Returns
+    /// [`FdtError::PropertyLengthMismatch`] if `value` is not the same length
+    /// as the existing value.
+    pub fn set_property_inplace(&mut self, path: &str, name: &str, value: &[u8]) -> Result<(), FdtError> {
+        let property = self.find_property(path, name)?;
+        let existing_len = property.end - property.value_offset;
+
+        if existing_len != value.len() {
+            return Err(FdtError::PropertyLengthMismatch { expected: existing_len, found: value.len() });
+        }
+
+        self.data[property.value_offset..property.end].copy_from_slice(value);
+
+        Ok(())
+    }
+
+    /// Overwrite a property containing a single `<u32>` value in place. See
+    /// [`FdtMut::set_property_inplace`].
+    pub fn set_property_u32_inplace(&mut self, path: &str, name: &str, value: u32) -> Result<(), FdtError> {
+        self.set_property_inplace(path, name, &value.to_be_bytes())
+    }
+
+    /// Overwrite a property containing a single `<u64>` value in place. See
+    /// [`FdtMut::set_property_inplace`].
+    pub fn set_property_u64_inplace(&mut self, path: &str, name: &str, value: u64) -> Result<(), FdtError> {
+        self.set_property_inplace(path, name, &value.to_be_bytes())
+    }
+
+    /// Remove the property `name` from the node at `path` by replacing it
+    /// with [`BigEndianToken::NOP`] tokens, equivalent to libfdt's
+    /// `fdt_nop_property`. The size of the devicetree is unchanged.
+    pub fn nop_property(&mut self, path: &str, name: &str) -> Result<(), FdtError> {
+        let property = self.find_property(path, name)?;
+        self.nop(property.start, align4(property.end));
+
+        Ok(())
+    }
+
+    /// Remove the node at `path`, along with all of its properties and
+    /// children, by replacing it with [`BigEndianToken::NOP`] tokens,
+    /// equivalent to libfdt's `fdt_nop_node`. The size of the devicetree is
+    /// unchanged. Returns [`FdtError::MissingParent`] if `path` refers to the
+    /// root node, as it can't be removed.
+    pub fn nop_node(&mut self, path: &str) -> Result<(), FdtError> {
+        let (start, is_root) = self.find_node(path)?;
+        if is_root {
+            return Err(FdtError::MissingParent);
+        }
+
+        let end = self.node_end(start)?;
+        self.nop(start, end);
+
+        Ok(())
+    }
+
+    /// Set the value of the property `name` on the node at `path`, adding the
+    /// property if it doesn't exist or resizing it if the length of `value`
+    /// differs from the existing value, equivalent to libfdt's `fdt_setprop`.
+    /// New properties are added after the existing properties of the node.
+    ///
+    /// Resizing requires the buffer to have enough space past the current
+    /// [`FdtMut::total_size`], otherwise [`FdtError::SliceTooSmall`] is
+    /// returned and the devicetree is left unchanged.
+    pub fn set_property(&mut self, path: &str, name: &str, value: &[u8]) -> Result<(), FdtError> {
+        let value_offset = self.resize_property(path, name, value.len())?;
+        self.data[value_offset..value_offset + value.len()].copy_from_slice(value);
+
+        Ok(())
+    }
+
+    /// Set a property to a single `<u32>` value. See [`FdtMut::set_property`].
+    pub fn set_property_u32(&mut self, path: &str, name: &str, value: u32) -> Result<(), FdtError> {
+        self.set_property(path, name, &value.to_be_bytes())
+    }
+
+    /// Set a property to a single `<u64>` value. See [`FdtMut::set_property`].
+    pub fn set_property_u64(&mut self, path: &str, name: &str, value: u64) -> Result<(), FdtError> {
+        self.set_property(path, name, &value.to_be_bytes())
+    }
+
+    /// Set a property to a single `<string>` value. See
+    /// [`FdtMut::set_property`].
+    pub fn set_property_string(&mut self, path: &str, name: &str, value: &str) -> Result<(), FdtError> {
+        if value.contains('\0') {
+            return Err(FdtError::InvalidPropertyValue);
+        }
+
+        let value_offset = self.resize_property(path, name, value.len() + 1)?;
+        self.data[value_offset..value_offset + value.len()].copy_from_slice(value.as_bytes());
+
+        Ok(())
+    }
+
+    /// Remove the property `name` from the node at `path`, shrinking the
+    /// devicetree, equivalent to libfdt's `fdt_delprop`. The property name is
+    /// left in the strings block.
+    pub fn remove_property(&mut self, path: &str, name: &str) -> Result<(), FdtError> {
+        let property = self.find_property(path, name)?;
+        self.splice(property.start, align4(property.end) - property.start, 0, Block::Structs)
+    }
+
+    /// Add a new, empty node named `name` (including the unit address, if
+    /// any) as the last child of the node at `parent_path`, equivalent to
+    /// libfdt's `fdt_add_subnode`. Returns [`FdtError::NodeAlreadyExists`] if
+    /// the parent already has a child with the same name.
+    pub fn add_node(&mut self, parent_path: &str, name: &str) -> Result<(), FdtError> {
+        let (parent, _) = self.find_node(parent_path)?;
+        self.add_node_at(parent, name).map(|_| ())
+    }
+
+    /// Remove the node at `path`, along with all of its properties and
+    /// children, shrinking the devicetree, equivalent to libfdt's
+    /// `fdt_del_node`. Returns [`FdtError::MissingParent`] if `path` refers to
+    /// the root node, as it can't be removed.
+    pub fn remove_node(&mut self, path: &str) -> Result<(), FdtError> {
+        let (start, is_root) = self.find_node(path)?;
+        if is_root {
+            return Err(FdtError::MissingParent);
+        }
+
+        let end = self.node_end(start)?;
+        self.splice(start, end - start, 0, Block::Structs)
+    }
+
+    /// Resize (or add) the property `name` on the node at `path` to hold `len`
+    /// bytes, returning the offset of its zeroed value.
+    fn resize_property(&mut self, path: &str, name: &str, len: usize) -> Result<usize, FdtError> {
+        let (node, _) = self.find_node(path)?;
+        self.resize_property_at(node, name, len)
+    }
+
+    /// Set the property `name` on the node beginning at `node`, returning the
+    /// offset of its value.
+    pub(crate) fn set_property_at(&mut self, node: usize, name: &str, value: &[u8]) -> Result<usize, FdtError> {
+        let value_offset = self.resize_property_at(node, name, value.len())?;
+        self.data[value_offset..value_offset + value.len()].copy_from_slice(value);
+
+        Ok(value_offset)
+    }
+
+    /// See [`FdtMut::resize_property`]. Adding a new property name to the
+    /// strings block can move the structs block, so `node` is relative to
+    /// the structs block offset at the time of the call.
+    pub(crate) fn resize_property_at(&mut self, node: usize, name: &str, len: usize) -> Result<usize, FdtError> {
+        match self.property_at(node, name)? {
+            Some(property) => {
+                let old_len = align4(property.end) - property.value_offset;
+                self.splice(property.value_offset, old_len, align4(len), Block::Structs)?;
+                self.write_u32(property.start + 4, len as u32);
+                self.data[property.value_offset..property.value_offset + align4(len)].fill(0);
+
+                Ok(property.value_offset)
+            }
+            None => {
+                if name.is_empty() || name.contains('\0') {
+                    return Err(FdtError::InvalidPropertyValue);
+                }
+
+                // Check for space up front so that a new string isn't added if
+                // the property itself won't fit
+                let property_len = 12 + align4(len);
+                let string_len = match self.find_string(name) {
+                    Some(_) => 0,
+                    None => self.string_insert_len(name),
+                };
+
+                if self.total_size() + property_len + string_len > self.data.len() {
+                    return Err(FdtError::SliceTooSmall);
+                }
+
+                let structs_offset = self.structs_offset();
+                let name_offset = self.find_or_add_string(name)?;
+                let node = node - structs_offset + self.structs_offset();
+                let offset = self.properties_end(node)?;
+
+                self.splice(offset, 0, property_len, Block::Structs)?;
+                self.write_u32(offset, BigEndianToken::PROP.0.to_ne());
+                self.write_u32(offset + 4, len as u32);
+                self.write_u32(offset + 8, name_offset as u32);
+                self.data[offset + 12..offset + property_len].fill(0);
+
+                Ok(offset + 12)
+            }
+        }
+    }
+
+    /// Add a child named `name` to the end of the node beginning at `parent`,
+    /// returning the offset of the new node.
+    pub(crate) fn add_node_at(&mut self, parent: usize, name: &str) -> Result<usize, FdtError> {
+        if name.is_empty() || name.contains(['\0', '/']) {
+            return Err(FdtError::InvalidNodeName);
+        }
+
+        if self.child_at(parent, name)?.is_some() {
+            return Err(FdtError::NodeAlreadyExists);
+        }
+
+        // Insert the new node right before the parent's `END_NODE` token
+        let offset = self.node_end(parent)? - 4;
+        let name_len = align4(name.len() + 1);
+
+        self.splice(offset, 0, 8 + name_len, Block::Structs)?;
+        self.write_u32(offset, BigEndianToken::BEGIN_NODE.0.to_ne());
+        self.data[offset + 4..offset + 4 + name_len].fill(0);
+        self.data[offset + 4..offset + 4 + name.len()].copy_from_slice(name.as_bytes());
+        self.write_u32(offset + 4 + name_len, BigEndianToken::END_NODE.0.to_ne());
+
+        Ok(offset)
+    }
+
+    /// Returns the location of the property `name` on the node beginning at
+    /// `node`, if it has one.
+    pub(crate) fn property_at(&self, node: usize, name: &str) -> Result<Option<PropertyLocation>, FdtError> {
+        let strings_offset = self.header_field(HeaderField::StringsOffset) as usize;
+        let strings = &self.data[strings_offset..][..self.header_field(HeaderField::StringsSize) as usize];
+
+        let (_, mut offset) = self.next_token(node)?;
+        loop {
+            match self.next_token(offset)? {
+                (BigEndianToken::PROP, next) => {
+                    let name_offset = self.read_u32(offset + 8) as usize;
+                    let property_name = strings
+                        .get(name_offset..)
+                        .and_then(|s| s.split(|b| *b == 0).next())
+                        .ok_or(FdtError::ParseError(ParseError::UnexpectedEndOfData))?;
+
+                    if property_name == name.as_bytes() {
+                        let len = self.read_u32(offset + 4) as usize;
+                        return Ok(Some(PropertyLocation {
+                            start: offset,
+                            value_offset: offset + 12,
+                            end: offset + 12 + len,
+                        }));
+                    }
+
+                    offset = next;
+                }
+                (BigEndianToken::NOP, next) => offset = next,
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    /// Returns the offset of the child named `name` (including the unit
+    /// address, if any) of the node beginning at `node`, if it has one.
+    pub(crate) fn child_at(&self, node: usize, name: &str) -> Result<Option<usize>, FdtError> {
+        let mut offset = self.properties_end(node)?;
+        loop {
+            match self.next_token(offset)? {
+                (BigEndianToken::BEGIN_NODE, _) => {
+                    let child_name = &self.data[offset + 4..];
+                    if child_name.starts_with(name.as_bytes()) && child_name.get(name.len()) == Some(&0) {
+                        return Ok(Some(offset));
+                    }
+
+                    offset = self.node_end(offset)?;
+                }
+                (BigEndianToken::NOP, next) => offset = next,
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    pub(crate) fn structs_offset(&self) -> usize {
+        self.header_field(HeaderField::StructsOffset) as usize
+    }
+
+    /// Returns the offset of `name` within the strings block, if it (or a
+    /// string ending with it) is present.
+    fn find_string(&self, name: &str) -> Option<usize> {
+        let start = self.header_field(HeaderField::StringsOffset) as usize;
+        let strings = &self.data[start..][..self.header_field(HeaderField::StringsSize) as usize];
+
+        strings
+            .windows(name.len() + 1)
+            .position(|window| &window[..name.len()] == name.as_bytes() && window[name.len()] == 0)
+    }
+
+    /// Number of bytes the strings block grows by when adding `name`. If
+    /// another block follows the strings block, the growth is padded to keep
+    /// that block aligned.
+    fn string_insert_len(&self, name: &str) -> usize {
+        let strings_offset = self.header_field(HeaderField::StringsOffset);
+        let followed = [HeaderField::StructsOffset, HeaderField::MemoryReservationsOffset]
+            .into_iter()
+            .any(|field| self.header_field(field) > strings_offset);
+
+        match followed {
+            true => (name.len() + 1).next_multiple_of(8),
+            false => name.len() + 1,
+        }
+    }
+
+    fn find_or_add_string(&mut self, name: &str) -> Result<usize, FdtError> {
+        if let Some(offset) = self.find_string(name) {
+            return Ok(offset);
+        }
+
+        let strings_size = self.header_field(HeaderField::StringsSize) as usize;
+        let offset = self.header_field(HeaderField::StringsOffset) as usize + strings_size;
+        let len = self.string_insert_len(name);
+
+        self.splice(offset, 0, len, Block::Strings)?;
+        self.data[offset..offset + len].fill(0);
+        self.data[offset..offset + name.len()].copy_from_slice(name.as_bytes());
+
+        Ok(strings_size)
+    }
+
+    /// Replace `remove` bytes at `offset` with `insert` bytes, moving
+    /// everything after them and updating the header, where `block` is the
+    /// block containing `offset`. The contents of the inserted bytes are left
+    /// for the caller to fill in.
+    fn splice(&mut self, offset: usize, remove: usize, insert: usize, block: Block) -> Result<(), FdtError> {
+        let total_size = self.total_size();
+        let new_total_size = total_size - remove + insert;
+        if new_total_size > self.data.len() {
+            return Err(FdtError::SliceTooSmall);
+        }
+
+        self.data.copy_within(offset + remove..total_size, offset + insert);
+        if new_total_size < total_size {
+            self.data[new_total_size..total_size].fill(0);
+        }
+
+        let adjust = |value: u32| (value as usize + insert - remove) as u32;
+        let (edited_offset, edited_size) = match block {
+            Block::Structs => (HeaderField::StructsOffset, HeaderField::StructsSize),
+            Block::Strings => (HeaderField::StringsOffset, HeaderField::StringsSize),
+        };
+
+        for field in [HeaderField::StructsOffset, HeaderField::StringsOffset, HeaderField::MemoryReservationsOffset] {
+            let value = self.header_field(field);
+            if field != edited_offset && value as usize >= offset {
+                self.set_header_field(field, adjust(value));
+            }
+        }
+
+        self.set_header_field(edited_size, adjust(self.header_field(edited_size)));
+        self.set_header_field(HeaderField::TotalSize, new_total_size as u32);
+
+        Ok(())
+    }
+
+    /// Returns the offset of the `BEGIN_NODE` token of the node at `path`, and
+    /// whether the node is the root node.
+    fn find_node(&self, path: &str) -> Result<(usize, bool), FdtError> {
+        let fdt = Fdt::new_unaligned_fallible(self.data)?;
+        let node = fdt.find_node(path)?.ok_or(FdtError::NodeNotFound)?;
+
+        // The node data begins with its name, directly after the token
+        Ok((self.offset_of(node.this.as_slice()) - 4, node.parent.is_none()))
+    }
+
+    fn find_property(&self, path: &str, name: &str) -> Result<PropertyLocation, FdtError> {
+        let fdt = Fdt::new_unaligned_fallible(self.data)?;
+        let node = fdt.find_node(path)?.ok_or(FdtError::NodeNotFound)?;
+        let property = node.properties()?.find(name)?.ok_or(FdtError::PropertyNotFound)?;
+        let value_offset = self.offset_of(property.value);
+
+        // The property value is preceded by the `PROP` token, the value length,
+        // and the name offset
+        Ok(PropertyLocation { start: value_offset - 12, value_offset, end: value_offset + property.value.len() })
+    }
+
+    fn offset_of(&self, data: &[u8]) -> usize {
+        data.as_ptr() as usize - self.data.as_ptr() as usize
+    }
+
+    /// Returns the offset directly after the `END_NODE` token which closes the
+    /// node beginning at `start`.
+    fn node_end(&self, start: usize) -> Result<usize, FdtError> {
+        let mut offset = start;
+        let mut depth = 0usize;
+        loop {
+            let (token, next) = self.next_token(offset)?;
+            offset = next;
+
+            match token {
+                BigEndianToken::BEGIN_NODE => depth += 1,
+                BigEndianToken::END_NODE => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(offset);
+                    }
+                }
+                BigEndianToken::PROP | BigEndianToken::NOP => {}
+                _ => return Err(FdtError::ParseError(ParseError::UnexpectedToken)),
+            }
+        }
+    }
+
+    /// Returns the offset directly after the last property of the node
+    /// beginning at `start`.
+    fn properties_end(&self, start: usize) -> Result<usize, FdtError> {
+        let (_, mut offset) = self.next_token(start)?;
+        loop {
+            match self.next_token(offset)? {
+                (BigEndianToken::PROP | BigEndianToken::NOP, next) => offset = next,
+                _ => return Ok(offset),
+            }
+        }
+    }
+
+    /// Returns the token at `offset` within the structs block along with the
+    /// offset of the token following it.
+    fn next_token(&self, offset: usize) -> Result<(BigEndianToken, usize), FdtError> {
+        let structs_end = self.header_field(HeaderField::StructsOffset) as usize
+            + self.header_field(HeaderField::StructsSize) as usize;
+        let read = |offset: usize| -> Result<u32, FdtError> {
+            match self.data.get(offset..offset + 4) {
+                Some(bytes) if offset + 4 <= structs_end => Ok(u32::from_be_bytes(bytes.try_into().unwrap())),
+                _ => Err(FdtError::ParseError(ParseError::UnexpectedEndOfData)),
+            }
+        };
+
+        let token = BigEndianToken(crate::parsing::BigEndianU32::from_ne(read(offset)?));
+        let next = match token {
+            BigEndianToken::BEGIN_NODE => {
+                let name_len = self.data[offset + 4..structs_end]
+                    .iter()
+                    .position(|b| *b == 0)
+                    .ok_or(FdtError::ParseError(ParseError::UnexpectedEndOfData))?;
+                align4(offset + 4 + name_len + 1)
+            }
+            BigEndianToken::PROP => align4(offset + 12 + read(offset + 4)? as usize),
+            _ => offset + 4,
+        };
+
+        Ok((token, next))
+    }
+
+    fn nop(&mut self, start: usize, end: usize) {
+        for token in self.data[start..end].chunks_exact_mut(4) {
+            token.copy_from_slice(&BigEndianToken::NOP.0.to_ne().to_be_bytes());
+        }
+    }
+
+    fn header_field(&self, field: HeaderField) -> u32 {
+        let offset = field as usize * 4;
+        u32::from_be_bytes(self.data[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn set_header_field(&mut self, field: HeaderField, value: u32) {
+        self.write_u32(field as usize * 4, value);
+    }
+
+    pub(crate) fn read_u32(&self, offset: usize) -> u32 {
+        u32::from_be_bytes(self.data[offset..offset + 4].try_into().unwrap())
+    }
+
+    pub(crate) fn write_u32(&mut self, offset: usize, value: u32) {
+        self.data[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+    }
+}
+
+impl core::fmt::Debug for FdtMut<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FdtMut").finish_non_exhaustive()
+    }
+}
+
+/// Indices of the [`FdtHeader`](crate::FdtHeader) fields edited by
+/// [`FdtMut`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum HeaderField {
+    TotalSize = 1,
+    StructsOffset = 2,
+    StringsOffset = 3,
+    MemoryReservationsOffset = 4,
+    StringsSize = 8,
+    StructsSize = 9,
+}
+
+/// Blocks of the devicetree which can be resized.
+#[derive(Clone, Copy)]
+enum Block {
+    Structs,
+    Strings,
+}
+
+pub(crate) struct PropertyLocation {
+    start: usize,
+    value_offset: usize,
+    end: usize,
+}
@@ -0,0 +1,559 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use super::FdtMut;
+use crate::{
+    nodes::{root::Root, Node, NodeName},
+    parsing::{BigEndianToken, NoPanic, ParseError, Parser, ParserWithMode},
+    properties::PHandle,
+    Fdt, FdtError,
+};
+
+/// An error encountered while applying an overlay with
+/// [`FdtMut::apply_overlay`].
+#[derive(Debug, Clone, Copy)]
+pub enum OverlayError<'o> {
+    /// The base or overlay devicetree is malformed, or the buffer doesn't
+    /// have enough space for the result.
+    Fdt(FdtError),
+    /// A fragment has neither a `target` nor a `target-path` property.
+    MissingTarget {
+        /// Name of the fragment node.
+        fragment: NodeName<'o>,
+    },
+    /// The `target` phandle of a fragment doesn't refer to a node in the base
+    /// devicetree.
+    TargetPhandleNotFound {
+        /// Name of the fragment node.
+        fragment: NodeName<'o>,
+        /// The target phandle, after resolving any fixups.
+        phandle: u32,
+    },
+    /// The `target-path` of a fragment doesn't exist in the base devicetree.
+    TargetPathNotFound {
+        /// Name of the fragment node.
+        fragment: NodeName<'o>,
+        /// The target path.
+        path: &'o str,
+    },
+    /// A label referenced by the overlay's `__fixups__` isn't defined in the
+    /// base devicetree's `__symbols__`.
+    UnresolvedLabel(&'o str),
+    /// A label referenced by the overlay's `__fixups__` refers to a node
+    /// without a `phandle` property.
+    MissingPhandle(&'o str),
+    /// An entry for the given label in `__fixups__`, or the given property in
+    /// `__local_fixups__`, is malformed or points outside of the property
+    /// being fixed up.
+    InvalidFixup(&'o str),
+}
+
+impl From<FdtError> for OverlayError<'_> {
+    fn from(e: FdtError) -> Self {
+        Self::Fdt(e)
+    }
+}
+
+impl core::fmt::Display for OverlayError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            OverlayError::Fdt(e) => write!(f, "{e}"),
+            OverlayError::MissingTarget { fragment } => {
+                write!(f, "fragment `{fragment}` has no `target` or `target-path` property")
+            }
+            OverlayError::TargetPhandleNotFound { fragment, phandle } => {
+                write!(f, "target phandle {phandle:#x} of fragment `{fragment}` not found")
+            }
+            OverlayError::TargetPathNotFound { fragment, path } => {
+                write!(f, "target path `{path}` of fragment `{fragment}` not found")
+            }
+            OverlayError::UnresolvedLabel(label) => write!(f, "label `{label}` not found in `__symbols__`"),
+            OverlayError::MissingPhandle(label) => write!(f, "node for label `{label}` has no phandle"),
+            OverlayError::InvalidFixup(name) => write!(f, "invalid fixup for `{name}`"),
+        }
+    }
+}
+
+/// A parsed entry of a `__fixups__` property, `path:property:offset`.
+struct Fixup<'o> {
+    path: &'o str,
+    property: &'o str,
+    offset: usize,
+}
+
+fn parse_fixups(value: &[u8]) -> impl Iterator<Item = Option<Fixup<'_>>> {
+    value.split(|b| *b == 0).filter(|entry| !entry.is_empty()).map(|entry| {
+        let entry = core::str::from_utf8(entry).ok()?;
+        let (path, rest) = entry.split_once(':')?;
+        let (property, offset) = rest.split_once(':')?;
+
+        Some(Fixup { path, property, offset: offset.parse().ok()? })
+    })
+}
+
+/// Find the node at `path` by following exact child names from the root.
+fn find_node<'o, Q: Parser<'o>>(
+    root: Root<'o, (Q, NoPanic)>,
+    path: &str,
+) -> Result<Option<Node<'o, (Q, NoPanic)>>, FdtError> {
+    let mut node = root.node;
+    for component in path.split('/').filter(|c| !c.is_empty()) {
+        match child(node, component)? {
+            Some(child) => node = child,
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(node))
+}
+
+fn child<'o, Q: Parser<'o>>(
+    node: Node<'o, (Q, NoPanic)>,
+    name: &str,
+) -> Result<Option<Node<'o, (Q, NoPanic)>>, FdtError> {
+    for child in node.children()? {
+        let child = child?;
+        if child.raw_name()? == name {
+            return Ok(Some(child));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Overlay nodes needed while merging fragments.
+struct Context<'o, Q: Parser<'o>> {
+    root: Root<'o, (Q, NoPanic)>,
+    fixups: Option<Node<'o, (Q, NoPanic)>>,
+    phandle_delta: u32,
+}
+
+impl<'b> FdtMut<'b> {
+    /// Apply a devicetree overlay, compiled with symbols (e.g. `dtc -@`), to
+    /// this devicetree, equivalent to libfdt's `fdt_overlay_apply`.
+    ///
+    /// For each `fragment@N` node of the overlay, the properties and children
+    /// of its `__overlay__` node are merged into the node given by the
+    /// fragment's `target` phandle or `target-path`. While merging:
+    ///
+    /// * The overlay's phandles are renumbered to be greater than the largest
+    ///   phandle in this devicetree, updating references listed in
+    ///   `__local_fixups__`
+    /// * References to labels in this devicetree listed in `__fixups__` are
+    ///   resolved using this devicetree's `__symbols__` node
+    ///
+    /// Finally, the overlay's `__symbols__` are added to this devicetree with
+    /// paths relative to the fragment targets, so that later overlays can
+    /// refer to them.
+    ///
+    /// Targets, labels, fixups and renumbered phandles are checked before
+    /// anything is modified, so errors due to a mismatched overlay leave the
+    /// devicetree unchanged. If the buffer runs out of space while merging,
+    /// [`FdtError::SliceTooSmall`] is returned and the devicetree is left
+    /// valid, but with the overlay partially applied.
+    pub fn apply_overlay<'o, P: ParserWithMode<'o>>(&mut self, overlay: &Fdt<'o, P>) -> Result<(), OverlayError<'o>> {
+        let mut parser: (P::Parser, NoPanic) = <_>::new(overlay.structs.0, overlay.strings, overlay.structs);
+        let root = Root { node: parser.parse_root()? };
+        let context = Context { root, fixups: child(root.node, "__fixups__")?, phandle_delta: self.max_phandle()? };
+        let local_fixups = child(root.node, "__local_fixups__")?;
+
+        if let Some(fixups) = context.fixups {
+            for fixup in fixups.properties()? {
+                let fixup = fixup?;
+                self.resolve_label(fixup.name)?;
+
+                for entry in parse_fixups(fixup.value) {
+                    let Some(entry) = entry else { return Err(OverlayError::InvalidFixup(fixup.name)) };
+                    let property = match find_node(root, entry.path)? {
+                        Some(node) => node.raw_property(entry.property)?,
+                        None => None,
+                    };
+
+                    if property.is_some_and(|property| entry.offset + 4 > property.value.len()) {
+                        return Err(OverlayError::InvalidFixup(fixup.name));
+                    }
+                }
+            }
+        }
+
+        check_renumbering(root.node, local_fixups, context.phandle_delta)?;
+
+        for fragment in root.node.children()? {
+            let fragment = fragment?;
+            if child(fragment, "__overlay__")?.is_some() {
+                self.fragment_target(&context, fragment)?;
+            }
+        }
+
+        for fragment in root.node.children()? {
+            let fragment = fragment?;
+            let Some(contents) = child(fragment, "__overlay__")? else { continue };
+
+            let target = self.fragment_target(&context, fragment)?;
+            let local_fixups = match local_fixups {
+                Some(local_fixups) => match child(local_fixups, fragment.raw_name()?)? {
+                    Some(fragment) => child(fragment, "__overlay__")?,
+                    None => None,
+                },
+                None => None,
+            };
+
+            self.merge_node(&context, target - self.structs_offset(), contents, local_fixups)?;
+        }
+
+        if let Some(symbols) = child(root.node, "__symbols__")? {
+            for symbol in symbols.properties()? {
+                let symbol = symbol?;
+                self.merge_symbol(&context, symbol.name, symbol.as_value::<&str>().map_err(FdtError::from)?)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Merge the properties and children of `overlay` into the node at
+    /// `node`, relative to the structs block.
+    fn merge_node<'o, Q: Parser<'o>>(
+        &mut self,
+        context: &Context<'o, Q>,
+        node: usize,
+        overlay: Node<'o, (Q, NoPanic)>,
+        local_fixups: Option<Node<'o, (Q, NoPanic)>>,
+    ) -> Result<(), OverlayError<'o>> {
+        for property in overlay.properties()? {
+            let property = property?;
+            let value = self.set_property_at(self.structs_offset() + node, property.name, property.value)?;
+            let len = property.value.len();
+
+            if (property.name == "phandle" || property.name == "linux,phandle") && len == 4 {
+                self.write_u32(value, renumber(self.read_u32(value), context.phandle_delta)?);
+            }
+
+            if let Some(offsets) = local_fixups.map(|l| l.raw_property(property.name)).transpose()?.flatten() {
+                for offset in offsets.value.chunks_exact(4) {
+                    let offset = u32::from_be_bytes(offset.try_into().unwrap()) as usize;
+                    if offset + 4 > len {
+                        return Err(OverlayError::InvalidFixup(property.name));
+                    }
+
+                    self.write_u32(value + offset, renumber(self.read_u32(value + offset), context.phandle_delta)?);
+                }
+            }
+
+            let Some(fixups) = context.fixups else { continue };
+            for label in fixups.properties()? {
+                let label = label?;
+                for fixup in parse_fixups(label.value).flatten() {
+                    if fixup.property != property.name || !self::is_node(context.root, fixup.path, overlay)? {
+                        continue;
+                    }
+
+                    if fixup.offset + 4 > len {
+                        return Err(OverlayError::InvalidFixup(label.name));
+                    }
+
+                    let phandle = self.resolve_label(label.name)?;
+                    self.write_u32(value + fixup.offset, phandle);
+                }
+            }
+        }
+
+        for child in overlay.children()? {
+            let child = child?;
+            let name = child.raw_name()?;
+            let base = self.structs_offset() + node;
+            let child_node = match self.child_at(base, name)? {
+                Some(child_node) => child_node,
+                None => self.add_node_at(base, name)?,
+            };
+
+            let local_fixups = match local_fixups {
+                Some(local_fixups) => self::child(local_fixups, name)?,
+                None => None,
+            };
+
+            self.merge_node(context, child_node - self.structs_offset(), child, local_fixups)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add an entry from the overlay's `__symbols__` to this devicetree's
+    /// `__symbols__`, translating the path from within the overlay to the
+    /// path the node was merged into.
+    fn merge_symbol<'o, Q: Parser<'o>>(
+        &mut self,
+        context: &Context<'o, Q>,
+        label: &'o str,
+        path: &'o str,
+    ) -> Result<(), OverlayError<'o>> {
+        // Symbols which don't refer to a node within a fragment's overlay
+        // can't be translated, so they're skipped like libfdt does
+        let Some((fragment, rest)) = path.strip_prefix('/').and_then(|p| p.split_once('/')) else { return Ok(()) };
+        let Some(rest) = rest.strip_prefix("__overlay__") else { return Ok(()) };
+        let Some(fragment) = child(context.root.node, fragment)? else { return Ok(()) };
+
+        let root = self.structs_offset();
+        let symbols = match self.child_at(root, "__symbols__")? {
+            Some(symbols) => symbols,
+            None => self.add_node_at(root, "__symbols__")?,
+        } - self.structs_offset();
+
+        match fragment.raw_property("target-path")? {
+            Some(target_path) => {
+                let target_path = target_path.as_value::<&str>().map_err(FdtError::from)?.trim_end_matches('/');
+                let value = self.resize_property_at(
+                    self.structs_offset() + symbols,
+                    label,
+                    target_path.len().max(rest.is_empty() as usize) + rest.len() + 1,
+                )?;
+
+                match (target_path, rest) {
+                    ("", "") => self.data[value] = b'/',
+                    _ => self.data[value..value + target_path.len()].copy_from_slice(target_path.as_bytes()),
+                }
+
+                let rest_offset = value + target_path.len();
+                self.data[rest_offset..rest_offset + rest.len()].copy_from_slice(rest.as_bytes());
+            }
+            None => {
+                let target = self.fragment_target(context, fragment)? - self.structs_offset();
+                let structs_size = self.header_field(super::HeaderField::StructsSize);
+                let depth = self.depth_of(self.structs_offset() + target)?;
+                let path_len: usize = (1..=depth)
+                    .map(|level| Ok(self.name_at(self.ancestor_at(self.structs_offset() + target, level)?)?.len() + 1))
+                    .sum::<Result<usize, FdtError>>()?;
+
+                let value = self.resize_property_at(
+                    self.structs_offset() + symbols,
+                    label,
+                    path_len.max(rest.is_empty() as usize) + rest.len() + 1,
+                )?;
+
+                // The value was added to `__symbols__`, which may come before
+                // the target
+                let target = match target > symbols {
+                    true => {
+                        target + self.header_field(super::HeaderField::StructsSize) as usize - structs_size as usize
+                    }
+                    false => target,
+                };
+
+                let mut offset = value;
+                for level in 1..=depth {
+                    let ancestor = self.ancestor_at(self.structs_offset() + target, level)?;
+                    let name_len = self.name_at(ancestor)?.len();
+                    self.data[offset] = b'/';
+                    self.data.copy_within(ancestor + 4..ancestor + 4 + name_len, offset + 1);
+                    offset += name_len + 1;
+                }
+
+                if depth == 0 && rest.is_empty() {
+                    self.data[offset] = b'/';
+                }
+
+                self.data[offset..offset + rest.len()].copy_from_slice(rest.as_bytes());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the offset of the node targeted by `fragment`.
+    fn fragment_target<'o, Q: Parser<'o>>(
+        &self,
+        context: &Context<'o, Q>,
+        fragment: Node<'o, (Q, NoPanic)>,
+    ) -> Result<usize, OverlayError<'o>> {
+        let fragment_name = fragment.name()?;
+
+        if let Some(target) = fragment.raw_property("target")? {
+            let mut phandle = target.as_value::<u32>().map_err(FdtError::from)?;
+
+            if let Some(fixups) = context.fixups {
+                for label in fixups.properties()? {
+                    let label = label?;
+                    for fixup in parse_fixups(label.value).flatten() {
+                        if fixup.property == "target" && is_node(context.root, fixup.path, fragment)? {
+                            phandle = self.resolve_label(label.name)?;
+                        }
+                    }
+                }
+            }
+
+            return match self.node_with_phandle(phandle)? {
+                Some(target) => Ok(target),
+                None => Err(OverlayError::TargetPhandleNotFound { fragment: fragment_name, phandle }),
+            };
+        }
+
+        match fragment.raw_property("target-path")? {
+            Some(path) => {
+                let path = path.as_value::<&str>().map_err(FdtError::from)?;
+                match self.find_node(path) {
+                    Ok((target, _)) => Ok(target),
+                    Err(FdtError::NodeNotFound) => {
+                        Err(OverlayError::TargetPathNotFound { fragment: fragment_name, path })
+                    }
+                    Err(e) => Err(e.into()),
+                }
+            }
+            None => Err(OverlayError::MissingTarget { fragment: fragment_name }),
+        }
+    }
+
+    /// Resolve `label` to a phandle using this devicetree's `__symbols__`.
+    fn resolve_label<'o>(&self, label: &'o str) -> Result<u32, OverlayError<'o>> {
+        let fdt = Fdt::new_unaligned_fallible(self.data)?;
+        let root = fdt.root()?;
+        let Some(symbols) = child(root.node, "__symbols__")? else {
+            return Err(OverlayError::UnresolvedLabel(label));
+        };
+
+        let Some(path) = symbols.raw_property(label)? else {
+            return Err(OverlayError::UnresolvedLabel(label));
+        };
+
+        let path = path.as_value::<&str>().map_err(FdtError::from)?;
+        let Some(node) = find_node(root, path)? else {
+            return Err(OverlayError::UnresolvedLabel(label));
+        };
+
+        match node.property::<PHandle>()? {
+            Some(phandle) => Ok(phandle.as_u32()),
+            None => Err(OverlayError::MissingPhandle(label)),
+        }
+    }
+
+    fn max_phandle(&self) -> Result<u32, FdtError> {
+        let fdt = Fdt::new_unaligned_fallible(self.data)?;
+        let mut max = 0;
+
+        for node in fdt.all_nodes()? {
+            let (_, node) = node?;
+            for name in ["phandle", "linux,phandle"] {
+                if let Some(phandle) = node.raw_property(name)? {
+                    max = max.max(phandle.as_value::<u32>()?);
+                }
+            }
+        }
+
+        Ok(max)
+    }
+
+    fn node_with_phandle(&self, phandle: u32) -> Result<Option<usize>, FdtError> {
+        let fdt = Fdt::new_unaligned_fallible(self.data)?;
+        match fdt.root()?.resolve_phandle(PHandle::new(phandle))? {
+            Some(node) => Ok(Some(self.offset_of(node.this.as_slice()) - 4)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the depth of the node beginning at `node`, with the root node
+    /// at depth 0.
+    fn depth_of(&self, node: usize) -> Result<usize, FdtError> {
+        let mut offset = self.structs_offset();
+        let mut depth = 0usize;
+        loop {
+            let (token, next) = self.next_token(offset)?;
+            match token {
+                BigEndianToken::BEGIN_NODE if offset == node => return Ok(depth),
+                BigEndianToken::BEGIN_NODE => depth += 1,
+                BigEndianToken::END_NODE => depth = depth.checked_sub(1).ok_or(FdtError::NodeNotFound)?,
+                BigEndianToken::PROP | BigEndianToken::NOP => {}
+                _ => return Err(FdtError::NodeNotFound),
+            }
+
+            offset = next;
+        }
+    }
+
+    /// Returns the offset of the ancestor of the node beginning at `node`
+    /// which is at depth `level`.
+    fn ancestor_at(&self, node: usize, level: usize) -> Result<usize, FdtError> {
+        let mut offset = self.structs_offset();
+        let mut depth = 0usize;
+        let mut ancestor = None;
+        loop {
+            let (token, next) = self.next_token(offset)?;
+            match token {
+                BigEndianToken::BEGIN_NODE => {
+                    if depth == level {
+                        ancestor = Some(offset);
+                    }
+
+                    if offset == node {
+                        return ancestor.ok_or(FdtError::NodeNotFound);
+                    }
+
+                    depth += 1;
+                }
+                BigEndianToken::END_NODE => depth = depth.checked_sub(1).ok_or(FdtError::NodeNotFound)?,
+                BigEndianToken::PROP | BigEndianToken::NOP => {}
+                _ => return Err(FdtError::NodeNotFound),
+            }
+
+            offset = next;
+        }
+    }
+
+    fn name_at(&self, node: usize) -> Result<&str, FdtError> {
+        let name = self.data[node + 4..].split(|b| *b == 0).next().unwrap_or_default();
+        core::str::from_utf8(name).map_err(|_| FdtError::ParseError(ParseError::InvalidCStrValue))
+    }
+}
+
+/// Check that every offset in `local_fixups`, which mirrors the structure of
+/// `overlay`, lies within the property of `overlay` it fixes up, and that the
+/// phandles of `overlay` and the references to them remain valid phandles once
+/// renumbered by `phandle_delta`.
+fn check_renumbering<'o, Q: Parser<'o>>(
+    overlay: Node<'o, (Q, NoPanic)>,
+    local_fixups: Option<Node<'o, (Q, NoPanic)>>,
+    phandle_delta: u32,
+) -> Result<(), OverlayError<'o>> {
+    for property in overlay.properties()? {
+        let property = property?;
+        if let ("phandle" | "linux,phandle", [a, b, c, d]) = (property.name, property.value) {
+            renumber(u32::from_be_bytes([*a, *b, *c, *d]), phandle_delta)?;
+        }
+
+        let Some(fixup) = local_fixups.map(|l| l.raw_property(property.name)).transpose()?.flatten() else { continue };
+        for offset in fixup.value.chunks_exact(4) {
+            let offset = u32::from_be_bytes(offset.try_into().unwrap()) as usize;
+            let Some(phandle) = property.value.get(offset..).and_then(|value| value.first_chunk::<4>()) else {
+                return Err(OverlayError::InvalidFixup(fixup.name));
+            };
+
+            renumber(u32::from_be_bytes(*phandle), phandle_delta)?;
+        }
+    }
+
+    for overlay_child in overlay.children()? {
+        let overlay_child = overlay_child?;
+        let local_fixups = match local_fixups {
+            Some(local_fixups) => child(local_fixups, overlay_child.raw_name()?)?,
+            None => None,
+        };
+
+        check_renumbering(overlay_child, local_fixups, phandle_delta)?;
+    }
+
+    Ok(())
+}
+
+/// Renumber an overlay phandle to come after the phandles of the base
+/// devicetree, rejecting results which aren't valid phandles.
+fn renumber(phandle: u32, phandle_delta: u32) -> Result<u32, FdtError> {
+    phandle.checked_add(phandle_delta).filter(|p| *p != 0 && *p != u32::MAX).ok_or(FdtError::InvalidPropertyValue)
+}
+
+/// Whether `path` refers to `node` within the overlay.
+fn is_node<'o, Q: Parser<'o>>(
+    root: Root<'o, (Q, NoPanic)>,
+    path: &str,
+    node: Node<'o, (Q, NoPanic)>,
+) -> Result<bool, FdtError> {
+    Ok(find_node(root, path)?.is_some_and(|n| core::ptr::eq(n.this, node.this)))
+}
@@ -56,36 +56,49 @@
 #![no_std]
 #![warn(missing_docs)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
 #[cfg(test)]
 extern crate std;
 
 #[cfg(test)]
 mod tests;
 
+/// Serialization of flattened devicetrees.
+pub mod builder;
 /// Trait and types for working with `*-cells` values.
 pub mod cell_collector;
+/// Compiling devicetree source into flattened devicetrees.
+#[cfg(feature = "alloc")]
+pub mod dts;
+/// Editing of flattened devicetrees in a mutable buffer.
+pub mod edit;
 /// Helper type aliases.
 pub mod helpers;
+/// Computing the physical memory usable by the client program.
+pub mod memory_map;
 /// Devicetree node abstractions.
 pub mod nodes;
 /// Traits, types, and helpers for parsing flattened devicetrees. The helper types are not meant
 /// to be used by end users but need to be public. If you have a need to create
 /// a custom parser or other behavior, please open an issue.
 pub mod parsing;
+/// Printing flattened devicetrees as devicetree source.
 #[cfg(feature = "pretty-printing")]
-mod pretty_print;
+pub mod pretty_print;
 /// Devicetree property abstractions.
 pub mod properties;
 mod util;
 
 use helpers::FallibleParser;
 use nodes::{
+    memory::MemoryReservationsIter,
     root::{AllCompatibleIter, AllNodesIter, AllNodesWithNameIter, Root},
     Node,
 };
 use parsing::{
-    aligned::AlignedParser, unaligned::UnalignedParser, NoPanic, Panic, ParseError, Parser, ParserWithMode,
-    StringsBlock, StructsBlock,
+    aligned::AlignedParser, unaligned::UnalignedParser, MemoryReservationBlock, NoPanic, Panic, ParseError, Parser,
+    ParserWithMode, StringsBlock, StructsBlock,
 };
 // use standard_nodes::{Aliases, Chosen, Cpu, Memory, MemoryRange, MemoryRegion, Root};
 
@@ -120,6 +133,32 @@ pub enum FdtError {
     /// A `-cells` property value was unable to be collected into the specified
     /// type.
     CollectCellsError,
+    /// An [`FdtBuilder`](builder::FdtBuilder) method was called out of order,
+    /// such as ending a node which was never started or adding a memory
+    /// reservation after the root node.
+    InvalidBuilderState,
+    /// A node with the given path wasn't found.
+    NodeNotFound,
+    /// A property with the given name wasn't found.
+    PropertyNotFound,
+    /// A new property value didn't have the same length as the value it was
+    /// replacing.
+    PropertyLengthMismatch {
+        /// Length of the existing value
+        expected: usize,
+        /// Length of the new value
+        found: usize,
+    },
+    /// A node with the given name already exists.
+    NodeAlreadyExists,
+    /// An interrupt couldn't be followed to an interrupt controller, either
+    /// because no `interrupt-map` entry of a nexus node matched it or because
+    /// the interrupt tree contains a cycle.
+    UnresolvedInterrupt,
+    /// A specifier, such as a GPIO specifier, couldn't be followed through a
+    /// nexus node, either because no entry of its `*-map` property matched it
+    /// or because the nexus nodes form a cycle.
+    UnresolvedSpecifier,
 }
 
 impl From<ParseError> for FdtError {
@@ -152,6 +191,18 @@ impl core::fmt::Display for FdtError {
             FdtError::CollectCellsError => {
                 write!(f, "overflow occurred while collecting `#<specifier>-cells` size values into the desired type")
             }
+            FdtError::InvalidBuilderState => write!(f, "FDT builder method was called in an invalid state"),
+            FdtError::NodeNotFound => write!(f, "node with the given path was not found"),
+            FdtError::PropertyNotFound => write!(f, "property with the given name was not found"),
+            FdtError::PropertyLengthMismatch { expected, found } => {
+                write!(
+                    f,
+                    "new property value has a length of {found} bytes, but the existing value is {expected} bytes"
+                )
+            }
+            FdtError::NodeAlreadyExists => write!(f, "node with the given name already exists"),
+            FdtError::UnresolvedInterrupt => write!(f, "interrupt could not be mapped to an interrupt controller"),
+            FdtError::UnresolvedSpecifier => write!(f, "specifier could not be mapped through a nexus node"),
         }
     }
 }
@@ -159,16 +210,25 @@ impl core::fmt::Display for FdtError {
 /// A flattened devicetree located somewhere in memory
 ///
 /// Note on `Debug` impl: by default the `Debug` impl of this struct will not
-/// print any useful information, if you would like a best-effort tree print
-/// which looks similar to `dtc`'s output, enable the `pretty-printing` feature
-/// and use the `Display` impl.
-#[derive(Clone, Copy)]
+/// print any useful information, if you would like to print the tree as
+/// devicetree source in the same format as `dtc`'s output, enable the
+/// `pretty-printing` feature and use the `Display` impl or
+/// [`Fdt::display_dts`].
 pub struct Fdt<'a, P: ParserWithMode<'a>> {
     structs: StructsBlock<'a, P::Granularity>,
     strings: StringsBlock<'a>,
+    memory_reservations: MemoryReservationBlock<'a>,
     header: FdtHeader,
 }
 
+impl<'a, P: ParserWithMode<'a>> Clone for Fdt<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for Fdt<'a, P> {}
+
 impl<'a, P: ParserWithMode<'a>> core::fmt::Debug for Fdt<'a, P> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Fdt").finish_non_exhaustive()
@@ -178,13 +238,7 @@ impl<'a, P: ParserWithMode<'a>> core::fmt::Debug for Fdt<'a, P> {
 #[cfg(feature = "pretty-printing")]
 impl<'a, P: ParserWithMode<'a>> core::fmt::Display for Fdt<'a, P> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let mut parser: (P::Parser, NoPanic) = <_>::new(self.structs.0, self.strings, self.structs);
-
-        let Ok(node) = parser.parse_root() else {
-            return Err(core::fmt::Error);
-        };
-
-        pretty_print::print_fdt(f, Root { node })
+        self.display_dts().fmt(f)
     }
 }
 
@@ -242,7 +296,12 @@ impl<'a> Fdt<'a, (UnalignedParser<'a>, Panic)> {
             return Err(FdtError::SliceTooSmall);
         }
 
-        Ok(Self { header, structs, strings })
+        let memory_reservations = MemoryReservationBlock(
+            data.get(header.memory_reserve_map_offset as usize..header.total_size as usize)
+                .ok_or(FdtError::ParseError(ParseError::UnexpectedEndOfData))?,
+        );
+
+        Ok(Self { header, structs, strings, memory_reservations })
     }
 
     /// # Safety
@@ -295,7 +354,13 @@ impl<'a> Fdt<'a, (AlignedParser<'a>, Panic)> {
             return Err(FdtError::ParseError(ParseError::UnexpectedEndOfData));
         }
 
-        Ok(Self { header, strings, structs })
+        let memory_reservations = MemoryReservationBlock(
+            util::cast_slice(data)
+                .get(header.memory_reserve_map_offset as usize..header.total_size as usize)
+                .ok_or(FdtError::ParseError(ParseError::UnexpectedEndOfData))?,
+        );
+
+        Ok(Self { header, strings, structs, memory_reservations })
     }
 
     /// # Safety
@@ -319,32 +384,32 @@ impl<'a> Fdt<'a, (AlignedParser<'a>, Panic)> {
 impl<'a> Fdt<'a, (UnalignedParser<'a>, NoPanic)> {
     /// Construct a new `Fdt` from a byte buffer
     pub fn new_unaligned_fallible(data: &'a [u8]) -> Result<Self, FdtError> {
-        let Fdt { header, strings, structs } = Fdt::new_unaligned(data)?;
-        Ok(Self { header, strings, structs })
+        let Fdt { header, strings, structs, memory_reservations } = Fdt::new_unaligned(data)?;
+        Ok(Self { header, strings, structs, memory_reservations })
     }
 
     /// # Safety
     /// This function performs a read to verify the magic value. If the pointer
     /// is invalid this can result in undefined behavior.
     pub unsafe fn from_ptr_unaligned_fallible(ptr: *const u8) -> Result<Self, FdtError> {
-        let Fdt { header, strings, structs } = Fdt::from_ptr_unaligned(ptr)?;
-        Ok(Self { header, strings, structs })
+        let Fdt { header, strings, structs, memory_reservations } = Fdt::from_ptr_unaligned(ptr)?;
+        Ok(Self { header, strings, structs, memory_reservations })
     }
 }
 
 impl<'a> Fdt<'a, (AlignedParser<'a>, NoPanic)> {
     /// Construct a new `Fdt` from a `u32`-aligned buffer which won't panic on invalid data
     pub fn new_fallible(data: &'a [u32]) -> Result<Self, FdtError> {
-        let Fdt { header, strings, structs } = Fdt::new(data)?;
-        Ok(Self { header, strings, structs })
+        let Fdt { header, strings, structs, memory_reservations } = Fdt::new(data)?;
+        Ok(Self { header, strings, structs, memory_reservations })
     }
 
     /// # Safety
     /// This function performs a read to verify the magic value. If the pointer
     /// is invalid this can result in undefined behavior.
     pub unsafe fn from_ptr_fallible(ptr: *const u32) -> Result<Self, FdtError> {
-        let Fdt { header, strings, structs } = Fdt::from_ptr(ptr)?;
-        Ok(Self { header, strings, structs })
+        let Fdt { header, strings, structs, memory_reservations } = Fdt::from_ptr(ptr)?;
+        Ok(Self { header, strings, structs, memory_reservations })
     }
 }
 
@@ -429,6 +494,26 @@ impl<'a, P: ParserWithMode<'a>> Fdt<'a, P> {
         }))
     }
 
+    /// [Devicetree 5.3. Memory Reservation
+    /// Block](https://devicetree-specification.readthedocs.io/en/latest/chapter5-flattened-format.html#memory-reservation-block)
+    ///
+    /// Returns an iterator over the entries of the memory reservation block,
+    /// which describe physical memory that the client program must not use for
+    /// general allocations. Iteration stops at the terminating all-zero entry,
+    /// and an entry which would extend past the end of the devicetree is
+    /// reported as an error.
+    pub fn memory_reservations(&self) -> MemoryReservationsIter<'a, P> {
+        MemoryReservationsIter { block: self.memory_reservations.0, finished: false, _mode: core::marker::PhantomData }
+    }
+
+    /// Returns a type which prints the devicetree as devicetree source with
+    /// [`core::fmt::Display`], with options for how it's printed. See
+    /// [`DisplayDts`](pretty_print::DisplayDts).
+    #[cfg(feature = "pretty-printing")]
+    pub fn display_dts(&self) -> pretty_print::DisplayDts<'_, 'a, P> {
+        pretty_print::DisplayDts { fdt: self, phandle_labels: false }
+    }
+
     /// Total size of the devicetree in bytes
     pub fn total_size(&self) -> usize {
         self.header.total_size as usize
@@ -444,6 +529,12 @@ impl<'a, P: ParserWithMode<'a>> Fdt<'a, P> {
         self.strings.0
     }
 
+    /// Slice pointing to the raw memory reservation block, extending to the
+    /// end of the devicetree.
+    pub fn memory_reservation_block(&self) -> &'a [u8] {
+        self.memory_reservations.0
+    }
+
     /// Slice pointing to the raw structs block.
     pub fn structs_block(&self) -> &'a [P::Granularity] {
         self.structs.0
@@ -0,0 +1,419 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use crate::{
+    helpers::{FallibleParser, FallibleRoot},
+    nodes::{
+        chosen::{Chosen, Initrd},
+        memory::{MemoryReservationsIter, ReservedMemoryChild},
+        NodeChildrenIter, NodeName,
+    },
+    parsing::{NoPanic, ParserWithMode},
+    properties::{reg::RegIter, Status},
+    Fdt, FdtError,
+};
+
+/// A range of physical memory.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MemoryRange {
+    /// Physical address of the start of the range.
+    pub address: u64,
+    /// Size of the range in bytes.
+    pub size: u64,
+}
+
+impl MemoryRange {
+    /// Physical address of the end of the range, exclusive. Ranges which
+    /// extend past the end of the address space end at [`u64::MAX`].
+    pub fn end(&self) -> u64 {
+        self.address.saturating_add(self.size)
+    }
+}
+
+/// Why a [`CarveOut`] is excluded from usable memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CarveOutReason<'a> {
+    /// An entry of the memory reservation block (`/memreserve/`).
+    MemoryReservation,
+    /// A statically placed `/reserved-memory` child node with the given name.
+    ReservedMemory(NodeName<'a>),
+    /// The initial ramdisk given by [`Chosen::initrd`].
+    Initrd,
+    /// The devicetree blob itself.
+    Devicetree,
+}
+
+/// A region of physical memory which is excluded from usable memory. See
+/// [`MemoryMap::carve_outs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CarveOut<'a> {
+    /// The excluded physical memory.
+    pub range: MemoryRange,
+    /// Why the memory is excluded.
+    pub reason: CarveOutReason<'a>,
+}
+
+/// Default alignment of dynamically allocated `/reserved-memory` regions
+/// without an `alignment` property. See [`MemoryMap::plan_reserved_memory`].
+pub const DEFAULT_RESERVED_MEMORY_ALIGNMENT: u64 = 4096;
+
+/// A dynamically allocated `/reserved-memory` region placed by
+/// [`MemoryMap::plan_reserved_memory`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DynamicRegion<'a> {
+    /// Name of the `/reserved-memory` child node.
+    pub name: NodeName<'a>,
+    /// The physical memory chosen for the region.
+    pub range: MemoryRange,
+    /// The region must not be mapped as part of the standard mapping of
+    /// system memory. See [`ReservedMemoryChild::no_map`].
+    pub no_map: bool,
+    /// The region may be used by the operating system as long as the owning
+    /// driver can reclaim it. See [`ReservedMemoryChild::reusable`].
+    pub reusable: bool,
+}
+
+/// The number of entries written by [`MemoryMap::plan_reserved_memory`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReservedMemoryPlan {
+    /// Number of usable ranges remaining after placing the regions.
+    pub usable: usize,
+    /// Number of dynamically allocated regions placed.
+    pub regions: usize,
+}
+
+/// An error encountered while placing dynamically allocated
+/// `/reserved-memory` regions with [`MemoryMap::plan_reserved_memory`].
+#[derive(Debug, Clone, Copy)]
+pub enum PlanError<'a> {
+    /// The devicetree is malformed, or one of the output slices doesn't have
+    /// enough space.
+    Fdt(FdtError),
+    /// No usable range has enough aligned space within the `alloc-ranges` of
+    /// the child with the given name.
+    DoesNotFit(NodeName<'a>),
+    /// The child with the given name has both the `no-map` and `reusable`
+    /// properties, which are mutually exclusive.
+    NoMapAndReusable(NodeName<'a>),
+}
+
+impl From<FdtError> for PlanError<'_> {
+    fn from(e: FdtError) -> Self {
+        Self::Fdt(e)
+    }
+}
+
+impl core::fmt::Display for PlanError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PlanError::Fdt(e) => write!(f, "{e}"),
+            PlanError::DoesNotFit(name) => write!(f, "reserved memory region `{name}` does not fit in usable memory"),
+            PlanError::NoMapAndReusable(name) => {
+                write!(f, "reserved memory region `{name}` is both `no-map` and `reusable`")
+            }
+        }
+    }
+}
+
+/// Computes the physical memory usable by the client program, without
+/// allocating, created with [`Fdt::memory_map`].
+///
+/// Usable memory is all of the `reg` entries of the memory nodes (see
+/// [`Root::all_memory`]), minus every [`CarveOut`]:
+///
+/// * The entries of the memory reservation block
+/// * The `reg` entries of `/reserved-memory` children which aren't disabled.
+///   Dynamically allocated regions, which only have a `size`, aren't included
+///   and can be placed with [`MemoryMap::plan_reserved_memory`]
+/// * The initial ramdisk given by `/chosen`
+/// * The devicetree blob itself
+///
+/// [`Root::all_memory`]: crate::nodes::root::Root::all_memory
+pub struct MemoryMap<'a, P: ParserWithMode<'a>> {
+    fdt: Fdt<'a, P>,
+    fdt_address: u64,
+}
+
+impl<'a, P: ParserWithMode<'a>> MemoryMap<'a, P> {
+    /// Set the physical address the devicetree blob is located at, which
+    /// otherwise defaults to the address of the buffer it was parsed from.
+    /// This must be set if the devicetree isn't accessed through an identity
+    /// mapping.
+    pub fn with_fdt_address(self, fdt_address: u64) -> Self {
+        Self { fdt_address, ..self }
+    }
+
+    /// Returns an iterator over every region of memory which is excluded
+    /// from usable memory, along with the reason it was excluded. The regions
+    /// may overlap each other and aren't sorted.
+    #[track_caller]
+    pub fn carve_outs(&self) -> P::Output<CarveOutsIter<'a, P>> {
+        P::to_output(self.fallible_carve_outs().map(|iter| CarveOutsIter {
+            reservations: iter.reservations,
+            reserved_memory: iter.reserved_memory,
+            reg: iter.reg,
+            rest: iter.rest,
+        }))
+    }
+
+    /// Write the usable ranges of physical memory into `ranges`, returning
+    /// the number of ranges written. The ranges are sorted by address, and
+    /// overlapping or adjacent ranges are merged.
+    ///
+    /// Returns [`FdtError::SliceTooSmall`] if `ranges` isn't large enough to
+    /// hold every memory node range at once, or every usable range.
+    #[track_caller]
+    pub fn usable(&self, ranges: &mut [MemoryRange]) -> P::Output<usize> {
+        P::to_output(crate::tryblock!({
+            let root: FallibleRoot<'a, P> = self.fdt.fallible_root()?;
+            let mut len = root.all_memory()?.merged_regions(ranges)?;
+            for carve_out in self.fallible_carve_outs()? {
+                len = carve(ranges, len, carve_out?.range)?;
+            }
+
+            Ok(len)
+        }))
+    }
+
+    /// Place every dynamically allocated `/reserved-memory` child, which has a
+    /// `size` but no `reg`, within the first `len` entries of `usable`, such as
+    /// the ranges written by [`MemoryMap::usable`].
+    ///
+    /// Like Linux, each region is placed at the highest suitably aligned
+    /// address within both a usable range and one of the child's
+    /// `alloc-ranges`, if it has any. Children without an `alignment` are
+    /// aligned to [`DEFAULT_RESERVED_MEMORY_ALIGNMENT`]. The placed regions
+    /// are written to `regions` in the order of the children, and are removed
+    /// from `usable` so that later regions don't overlap them. Regions marked
+    /// [`reusable`](DynamicRegion::reusable) may be handed back to the
+    /// allocator by the caller once placed.
+    ///
+    /// Returns the number of usable ranges and placed regions written.
+    pub fn plan_reserved_memory(
+        &self,
+        usable: &mut [MemoryRange],
+        len: usize,
+        regions: &mut [DynamicRegion<'a>],
+    ) -> Result<ReservedMemoryPlan, PlanError<'a>> {
+        let root: FallibleRoot<'a, P> = self.fdt.fallible_root()?;
+        let mut plan = ReservedMemoryPlan { usable: len, regions: 0 };
+
+        let Some(reserved_memory) = root.find_node("/reserved-memory")? else { return Ok(plan) };
+        for child in reserved_memory.children()? {
+            let child = ReservedMemoryChild::<(P::Parser, NoPanic)> { node: child? };
+            if child.node.raw_property("reg")?.is_some()
+                || child.node.property::<Status>()?.is_some_and(Status::is_disabled)
+            {
+                continue;
+            }
+
+            let Some(size) = child.size::<u64>()? else { continue };
+            let name = child.name()?;
+            let size = size.map_err(|_| FdtError::CollectCellsError)?;
+            let alignment = match child.alignment::<u64>()? {
+                Some(alignment) => alignment.map_err(|_| FdtError::CollectCellsError)?.max(1),
+                None => DEFAULT_RESERVED_MEMORY_ALIGNMENT,
+            };
+
+            let (no_map, reusable) = (child.no_map()?, child.reusable()?);
+            if no_map && reusable {
+                return Err(PlanError::NoMapAndReusable(name));
+            }
+
+            let alloc_ranges = child.alloc_ranges()?;
+            let mut best: Option<u64> = None;
+            for free in &usable[..plan.usable] {
+                let mut place = |window: MemoryRange| {
+                    let start = free.address.max(window.address);
+                    let end = free.end().min(window.end());
+                    let Some(base) = end.checked_sub(size).map(|base| base - base % alignment) else { return };
+                    if base >= start && best.is_none_or(|best| base > best) {
+                        best = Some(base);
+                    }
+                };
+
+                match alloc_ranges {
+                    Some(alloc_ranges) => {
+                        for window in alloc_ranges.iter::<u64, u64>() {
+                            let window = window.map_err(|_| FdtError::CollectCellsError)?;
+                            place(MemoryRange { address: window.address, size: window.len });
+                        }
+                    }
+                    None => place(MemoryRange { address: 0, size: u64::MAX }),
+                }
+            }
+
+            let Some(address) = best else { return Err(PlanError::DoesNotFit(name)) };
+            let range = MemoryRange { address, size };
+
+            *regions.get_mut(plan.regions).ok_or(FdtError::SliceTooSmall)? =
+                DynamicRegion { name, range, no_map, reusable };
+            plan.regions += 1;
+            plan.usable = carve(usable, plan.usable, range)?;
+        }
+
+        Ok(plan)
+    }
+
+    fn fallible_carve_outs(&self) -> Result<CarveOutsIter<'a, FallibleParser<'a, P>>, FdtError> {
+        let root: FallibleRoot<'a, P> = self.fdt.fallible_root()?;
+
+        let reserved_memory = match root.find_node("/reserved-memory")? {
+            Some(node) => Some(node.children()?.iter()),
+            None => None,
+        };
+
+        let initrd = match root.find_node("/chosen")? {
+            Some(node) => Chosen::<(P::Parser, NoPanic)> { node }.initrd()?,
+            None => None,
+        };
+
+        Ok(CarveOutsIter {
+            reservations: MemoryReservationsIter {
+                block: self.fdt.memory_reservation_block(),
+                finished: false,
+                _mode: core::marker::PhantomData,
+            },
+            reserved_memory,
+            reg: None,
+            rest: [
+                initrd.map(|Initrd { start, end }| CarveOut {
+                    range: MemoryRange { address: start, size: end - start },
+                    reason: CarveOutReason::Initrd,
+                }),
+                Some(CarveOut {
+                    range: MemoryRange { address: self.fdt_address, size: self.fdt.total_size() as u64 },
+                    reason: CarveOutReason::Devicetree,
+                }),
+            ],
+        })
+    }
+}
+
+/// Remove `carve_out` from the first `len` sorted, non-overlapping `ranges`,
+/// returning the new number of ranges.
+fn carve(ranges: &mut [MemoryRange], mut len: usize, carve_out: MemoryRange) -> Result<usize, FdtError> {
+    // An empty carve-out removes nothing, but would otherwise split a range
+    // it lies strictly inside of in two
+    if carve_out.size == 0 {
+        return Ok(len);
+    }
+
+    let mut i = 0;
+    while i < len {
+        let range = ranges[i];
+        if range.end() <= carve_out.address || range.address >= carve_out.end() {
+            i += 1;
+            continue;
+        }
+
+        let before = MemoryRange { address: range.address, size: carve_out.address.saturating_sub(range.address) };
+        let after = MemoryRange { address: carve_out.end(), size: range.end().saturating_sub(carve_out.end()) };
+
+        match (before.size, after.size) {
+            (0, 0) => {
+                ranges.copy_within(i + 1..len, i);
+                len -= 1;
+            }
+            (_, 0) => {
+                ranges[i] = before;
+                i += 1;
+            }
+            (0, _) => {
+                ranges[i] = after;
+                i += 1;
+            }
+            _ => {
+                if len == ranges.len() {
+                    return Err(FdtError::SliceTooSmall);
+                }
+
+                ranges.copy_within(i + 1..len, i + 2);
+                ranges[i] = before;
+                ranges[i + 1] = after;
+                len += 1;
+                i += 2;
+            }
+        }
+    }
+
+    Ok(len)
+}
+
+/// See [`MemoryMap::carve_outs`].
+pub struct CarveOutsIter<'a, P: ParserWithMode<'a>> {
+    reservations: MemoryReservationsIter<'a, (P::Parser, NoPanic)>,
+    reserved_memory: Option<NodeChildrenIter<'a, (P::Parser, NoPanic)>>,
+    reg: Option<(NodeName<'a>, RegIter<'a, u64, u64>)>,
+    rest: [Option<CarveOut<'a>>; 2],
+}
+
+impl<'a, P: ParserWithMode<'a>> CarveOutsIter<'a, P> {
+    fn next_reserved_memory(&mut self) -> Result<Option<CarveOut<'a>>, FdtError> {
+        loop {
+            if let Some((name, reg)) = &mut self.reg {
+                if let Some(entry) = reg.next() {
+                    let entry = entry.map_err(|_| FdtError::CollectCellsError)?;
+                    return Ok(Some(CarveOut {
+                        range: MemoryRange { address: entry.address, size: entry.len },
+                        reason: CarveOutReason::ReservedMemory(*name),
+                    }));
+                }
+            }
+
+            let Some(child) = self.reserved_memory.as_mut().and_then(Iterator::next) else { return Ok(None) };
+            let child = child?;
+
+            if child.property::<Status>()?.is_some_and(Status::is_disabled) {
+                continue;
+            }
+
+            self.reg = match child.reg()? {
+                Some(reg) => Some((child.name()?, reg.iter())),
+                None => None,
+            };
+        }
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for CarveOutsIter<'a, P> {
+    type Item = P::Output<CarveOut<'a>>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(reservation) = self.reservations.next() {
+            return Some(P::to_output(reservation.map(|r| CarveOut {
+                range: MemoryRange { address: r.address, size: r.size },
+                reason: CarveOutReason::MemoryReservation,
+            })));
+        }
+
+        match self.next_reserved_memory() {
+            Ok(Some(carve_out)) => return Some(P::to_output(Ok(carve_out))),
+            Ok(None) => {}
+            Err(e) => {
+                self.reserved_memory = None;
+                self.reg = None;
+                return Some(P::to_output(Err(e)));
+            }
+        }
+
+        self.rest.iter_mut().find_map(Option::take).map(|carve_out| P::to_output(Ok(carve_out)))
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Fdt<'a, P> {
+    /// Create a [`MemoryMap`] for computing the physical memory usable by the
+    /// client program, which is the memory described by the memory nodes
+    /// excluding reservations, the initial ramdisk, and the devicetree itself.
+    ///
+    /// The devicetree is assumed to be located at the address of the buffer it
+    /// was parsed from, see [`MemoryMap::with_fdt_address`] otherwise.
+    pub fn memory_map(&self) -> MemoryMap<'a, P> {
+        let fdt_address =
+            self.memory_reservation_block().as_ptr() as u64 - u64::from(self.header.memory_reserve_map_offset);
+
+        MemoryMap { fdt: *self, fdt_address }
+    }
+}
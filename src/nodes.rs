@@ -4,25 +4,53 @@ pub mod aliases;
 pub mod chosen;
 /// Description of the CPUs available on the system.
 pub mod cpus;
+/// ARM GIC interrupt controller nodes.
+pub mod gic;
+/// GPIO controller nodes.
+pub mod gpio;
+/// Ports and endpoints of the devicetree graph binding.
+pub mod graph;
 /// Memory region nodes and properties.
 pub mod memory;
+/// PCI host bridge nodes.
+pub mod pci;
+/// RISC-V interrupt controller nodes.
+pub mod riscv;
 /// Root devicetree node type and helpers.
 pub mod root;
+/// `/__symbols__` node.
+pub mod symbols;
 
 use crate::{
-    helpers::FallibleNode,
+    cell_collector::{CellArray, CellCollector},
+    helpers::{FallibleNode, FallibleParser},
     parsing::{
         aligned::AlignedParser, BigEndianToken, NoPanic, Panic, PanicMode, ParseError, Parser, ParserWithMode,
         StringsBlock, StructsBlock,
     },
     properties::{
-        ranges::Ranges,
-        reg::Reg,
+        clocks::{self, AssignedClocks, ClockNames, Clocks},
+        gpio::Gpios,
+        interrupts::{self, ResolvedInterrupt},
+        iommu::Iommus,
+        msi::{self, MsiParent, ResolvedMsi},
+        phandle_args::{PhandleArgs, PhandleArgsList},
+        pinctrl::{PinConfig, PinctrlStates},
+        power_domains::PowerDomains,
+        ranges::{DmaRanges, DmaTranslator, Ranges},
+        reg::{Reg, TranslatedRegIter},
+        regulators::{self, RegulatorConstraints, SuppliesIter},
+        resets::Resets,
         values::{InvalidPropertyValue, PropertyValue},
-        Property,
+        Compatible, Property,
     },
     FdtError,
 };
+use gic::Gic;
+use gpio::GpioController;
+use graph::{Port, PortsIter};
+use pci::PciHostBridge;
+use riscv::{Aplic, Clint, ClintKind, Imsic, Plic};
 use root::Root;
 
 #[macro_export]
@@ -172,6 +200,45 @@ impl<'a, P: ParserWithMode<'a>> Node<'a, P> {
         )
     }
 
+    /// The name of this node as it appears in the devicetree, including the
+    /// unit address. Unlike [`Node::name`], the root node's name is empty.
+    pub(crate) fn raw_name(&self) -> Result<&'a str, FdtError> {
+        P::new(&self.this.0, self.strings, self.structs)
+            .advance_cstr()
+            .and_then(|s| s.to_str().map_err(|_| FdtError::ParseError(ParseError::InvalidCStrValue)))
+    }
+
+    /// Find the parent of this node by searching from the root. Unlike
+    /// [`Node::parent`], the returned node is able to find its own parent.
+    ///
+    /// Each call parses the devicetree from the root down to this node, so
+    /// walking up through every ancestor of a node costs as many walks of the
+    /// tree as the node is deep.
+    pub(crate) fn full_parent(&self) -> Result<Option<FallibleNode<'a, P>>, FdtError> {
+        let target = self.this.as_slice().as_ptr();
+        let mut parent = self.make_root::<P::Parser>()?.node;
+
+        // Nodes are laid out in order, so this node is within the subtree of
+        // the last child which begins at or before it
+        loop {
+            let mut next = None;
+            for child in parent.children()? {
+                let child = child?;
+                if child.this.as_slice().as_ptr() > target {
+                    break;
+                }
+
+                next = Some(child);
+            }
+
+            match next {
+                Some(child) if core::ptr::eq(child.this.as_slice().as_ptr(), target) => return Ok(Some(parent)),
+                Some(child) => parent = child,
+                None => return Ok(None),
+            }
+        }
+    }
+
     /// [Devicetree 3.8.1 General Properties of `/cpus/cpu*`
     /// nodes](https://devicetree-specification.readthedocs.io/en/latest/chapter3-devicenodes.html#general-properties-of-cpus-cpu-nodes)
     ///
@@ -205,6 +272,40 @@ impl<'a, P: ParserWithMode<'a>> Node<'a, P> {
         self.property::<Reg<'a>>()
     }
 
+    /// The entries of this node's `reg` property with their addresses
+    /// translated into the CPU's physical address space, by applying the
+    /// `ranges` property of each ancestor bus node as described in
+    /// [`Node::ranges`].
+    ///
+    /// An empty `ranges` property maps addresses on the bus unchanged, while a
+    /// bus node without a `ranges` property makes the addresses of its
+    /// children untranslatable, which is reported by each entry as
+    /// [`TranslationError::Untranslatable`]. Addresses which don't fit in
+    /// `Addr`, or overflow during translation, are reported as
+    /// [`TranslationError::CollectCellsError`].
+    ///
+    /// [`TranslationError::Untranslatable`]: crate::properties::ranges::TranslationError::Untranslatable
+    /// [`TranslationError::CollectCellsError`]: crate::properties::ranges::TranslationError::CollectCellsError
+    ///
+    /// ```rust
+    /// # let fdt = fdt::Fdt::new_unaligned(include_bytes!("../dtb/test.dtb")).unwrap();
+    /// let uart = fdt.find_node("/soc/uart@10000000").unwrap();
+    /// let reg = uart.reg_translated::<u64, u64>().unwrap().next().unwrap().unwrap();
+    /// assert_eq!(reg.address, 0x1000_0000);
+    /// ```
+    #[track_caller]
+    pub fn reg_translated<Addr: CellCollector, Len: CellCollector>(
+        &self,
+    ) -> P::Output<Option<TranslatedRegIter<'a, Addr, Len>>> {
+        P::to_output(crate::tryblock!({
+            let this = self.fallible();
+            match this.reg()? {
+                Some(reg) => Ok(Some(TranslatedRegIter::new::<P>(this, reg)?)),
+                None => Ok(None),
+            }
+        }))
+    }
+
     /// [Devicetree 2.3.8
     /// `ranges`](https://devicetree-specification.readthedocs.io/en/latest/chapter2-devicetree-basics.html#sect-standard-properties-ranges)
     ///
@@ -280,6 +381,392 @@ impl<'a, P: ParserWithMode<'a>> Node<'a, P> {
         self.property()
     }
 
+    /// [Devicetree 2.3.9
+    /// `dma-ranges`](https://devicetree-specification.readthedocs.io/en/latest/chapter2-devicetree-basics.html#dma-ranges)
+    ///
+    /// The `dma-ranges` property describes how the address space of a bus's
+    /// children maps onto the address space of the bus's parent when the
+    /// children perform DMA. It has the same format as `ranges`, and likewise
+    /// an empty property means the address spaces are identical.
+    #[inline(always)]
+    #[track_caller]
+    pub fn dma_ranges(&self) -> P::Output<Option<DmaRanges<'a>>> {
+        self.property()
+    }
+
+    /// Create a [`DmaTranslator`] for converting between CPU physical
+    /// addresses and the DMA addresses this device uses, by applying the
+    /// `dma-ranges` of each of its ancestor buses.
+    #[track_caller]
+    pub fn dma_translator(&self) -> P::Output<DmaTranslator<'a>> {
+        P::to_output(DmaTranslator::new::<P>(self.fallible()))
+    }
+
+    /// Parse the property `property` as a list of phandles to provider nodes,
+    /// each followed by the number of argument cells given by the provider's
+    /// `cells` property, such as `clocks` with `#clock-cells`. If `names` is
+    /// given, the entries are named by the strings of that property, such as
+    /// `clock-names`.
+    #[track_caller]
+    pub fn phandle_args_list(
+        &self,
+        property: &str,
+        cells: &'static str,
+        names: Option<&str>,
+    ) -> P::Output<Option<PhandleArgsList<'a, P>>> {
+        P::to_output(PhandleArgsList::parse(self.fallible(), property, cells, names))
+    }
+
+    /// [Devicetree clock
+    /// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/clock/clock.yaml)
+    ///
+    /// The input clocks of this device, named by `clock-names`.
+    #[inline(always)]
+    #[track_caller]
+    pub fn clocks(&self) -> P::Output<Option<Clocks<'a, P>>> {
+        self.property()
+    }
+
+    /// [Devicetree clock
+    /// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/clock/clock.yaml)
+    ///
+    /// The names of the input clocks of this device.
+    #[inline(always)]
+    #[track_caller]
+    pub fn clock_names(&self) -> P::Output<Option<ClockNames<'a>>> {
+        self.property()
+    }
+
+    /// Look up the input clock named `name` in `clock-names`, returning the
+    /// clock provider node along with the clock specifier.
+    #[track_caller]
+    pub fn clock(&self, name: &str) -> P::Output<Option<PhandleArgs<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            match self.fallible().property::<Clocks<'a, FallibleParser<'a, P>>>()? {
+                Some(clocks) => Ok(clocks.find(name)?.map(PhandleArgs::alt)),
+                None => Ok(None),
+            }
+        }))
+    }
+
+    /// [Devicetree clock
+    /// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/clock/clock.yaml)
+    ///
+    /// The clocks to configure when initializing this device, along with
+    /// their parents and rates.
+    #[inline(always)]
+    #[track_caller]
+    pub fn assigned_clocks(&self) -> P::Output<Option<AssignedClocks<'a, P>>> {
+        self.property()
+    }
+
+    /// Compute the rate in Hz of the clock output by this node, if it's a
+    /// `fixed-clock` provider (using `clock-frequency`) or a
+    /// `fixed-factor-clock` provider (multiplying the rate of its parent clock
+    /// by `clock-mult` and dividing by `clock-div`).
+    ///
+    /// Returns `None` if this node or one of its parents is any other kind of
+    /// clock provider, or the chain of parents is too long or overflows.
+    #[track_caller]
+    pub fn fixed_clock_rate(&self) -> P::Output<Option<u64>> {
+        P::to_output(clocks::fixed_rate::<P>(self.fallible()))
+    }
+
+    /// [Devicetree GPIO
+    /// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/gpio/gpio.yaml)
+    ///
+    /// The GPIOs given by the property `property`, which is the full property
+    /// name such as `reset-gpios`. Each GPIO is resolved through any nexus
+    /// nodes with a `gpio-map` to the GPIO controller.
+    #[track_caller]
+    pub fn gpios(&self, property: &str) -> P::Output<Option<Gpios<'a, P>>> {
+        P::to_output(Gpios::parse(self.fallible(), property))
+    }
+
+    /// Returns this node as a [`GpioController`] if it has the
+    /// `gpio-controller` property.
+    #[track_caller]
+    pub fn gpio_controller(&self) -> P::Output<Option<GpioController<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            let this = self.fallible();
+            Ok(this.properties()?.find("gpio-controller")?.map(|_| GpioController { node: this }))
+        }))
+    }
+
+    /// [Devicetree IOMMU
+    /// binding](https://www.kernel.org/doc/Documentation/devicetree/bindings/iommu/iommu.txt)
+    ///
+    /// The IOMMUs which translate the DMA of this device, along with the IOMMU
+    /// specifiers identifying it.
+    #[inline(always)]
+    #[track_caller]
+    pub fn iommus(&self) -> P::Output<Option<Iommus<'a, P>>> {
+        self.property()
+    }
+
+    /// [Devicetree MSI
+    /// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/msi-consumer.yaml)
+    ///
+    /// The MSI controllers this device may send MSIs to, from its own
+    /// `msi-parent` property. See [`Node::msi_controller`] to also follow the
+    /// bus the device is on.
+    #[inline(always)]
+    #[track_caller]
+    pub fn msi_parent(&self) -> P::Output<Option<MsiParent<'a, P>>> {
+        self.property()
+    }
+
+    /// Find the MSI controller this device sends MSIs to, along with the MSI
+    /// specifier identifying it. This is the first entry of the device's own
+    /// `msi-parent` property, if any, and otherwise is inherited from the
+    /// closest ancestor bus with an `msi-map` or `msi-parent` property.
+    ///
+    /// For PCI buses, an `msi-map` is looked up with the requester ID of the
+    /// device, made up of the bus, device, and function numbers of its PCI
+    /// address, even when it's behind a bridge. For other buses, it's looked
+    /// up with the first cell of the `reg` property of the bus's child which
+    /// the device is below. The specifier is then the single mapped
+    /// device ID. Returns `None` if no MSI controller is found or the `msi-map`
+    /// doesn't cover the device.
+    #[track_caller]
+    pub fn msi_controller(&self) -> P::Output<Option<ResolvedMsi<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            let resolved = msi::resolve_msi::<P>(self.fallible())?;
+            Ok(resolved.map(|r| ResolvedMsi { controller: r.controller.alt(), specifier: r.specifier }))
+        }))
+    }
+
+    /// [Devicetree pin control
+    /// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/pinctrl/pinctrl-consumer.yaml)
+    ///
+    /// The pin configuration states of this device from its `pinctrl-N`
+    /// properties, named by `pinctrl-names`.
+    #[inline(always)]
+    #[track_caller]
+    pub fn pinctrl_states(&self) -> P::Output<Option<PinctrlStates<'a, P>>> {
+        self.property()
+    }
+
+    /// Decode the generic pin configuration properties of this node, which
+    /// should be a pin configuration node of a pin controller, such as one
+    /// returned by [`PinctrlState::configs`]. Pin controllers commonly group
+    /// the configuration into child nodes, which must be decoded separately.
+    ///
+    /// [`PinctrlState::configs`]: crate::properties::pinctrl::PinctrlState::configs
+    #[track_caller]
+    pub fn pin_config(&self) -> P::Output<PinConfig<'a>> {
+        P::to_output(PinConfig::parse::<P>(self.fallible()))
+    }
+
+    /// [Devicetree power domain
+    /// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/power-domain/power-domain-consumer.yaml)
+    ///
+    /// The power domains this device is a member of, named by
+    /// `power-domain-names`.
+    #[inline(always)]
+    #[track_caller]
+    pub fn power_domains(&self) -> P::Output<Option<PowerDomains<'a, P>>> {
+        self.property()
+    }
+
+    /// [Devicetree reset
+    /// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/reset/reset.yaml)
+    ///
+    /// The reset lines of this device, named by `reset-names`.
+    #[inline(always)]
+    #[track_caller]
+    pub fn resets(&self) -> P::Output<Option<Resets<'a, P>>> {
+        self.property()
+    }
+
+    /// Look up the reset line named `name` in `reset-names`, returning the
+    /// reset controller node along with the reset specifier.
+    #[track_caller]
+    pub fn reset(&self, name: &str) -> P::Output<Option<PhandleArgs<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            match self.fallible().property::<Resets<'a, FallibleParser<'a, P>>>()? {
+                Some(resets) => Ok(resets.find(name)?.map(PhandleArgs::alt)),
+                None => Ok(None),
+            }
+        }))
+    }
+
+    /// [Devicetree regulator
+    /// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/regulator/regulator.yaml)
+    ///
+    /// Returns an iterator over the `*-supply` properties of this device,
+    /// resolved to the regulator nodes.
+    #[track_caller]
+    pub fn supplies(&self) -> P::Output<SuppliesIter<'a, P>> {
+        P::to_output(crate::tryblock!({
+            let this = self.fallible();
+            Ok(SuppliesIter {
+                root: Root { node: this.make_root::<P::Parser>()?.node.alt() },
+                properties: Some(this.properties()?.iter()),
+            })
+        }))
+    }
+
+    /// Look up the regulator powering the supply input `name`, given by the
+    /// `<name>-supply` property, such as `vdd` for `vdd-supply`.
+    #[track_caller]
+    pub fn supply(&self, name: &str) -> P::Output<Option<Node<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            let this = self.fallible();
+            for property in this.properties()?.iter() {
+                let property = property?;
+                if property.name.strip_suffix("-supply") == Some(name) {
+                    let root = this.make_root::<P::Parser>()?;
+                    return Ok(Some(regulators::resolve_supply::<P>(root, property.value)?.alt()));
+                }
+            }
+
+            Ok(None)
+        }))
+    }
+
+    /// Decode the `regulator-*` constraint properties of this node, which
+    /// should be a regulator.
+    #[track_caller]
+    pub fn regulator_constraints(&self) -> P::Output<RegulatorConstraints<'a>> {
+        P::to_output(RegulatorConstraints::parse::<P>(self.fallible()))
+    }
+
+    /// [Devicetree graph
+    /// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/graph.yaml)
+    ///
+    /// Returns an iterator over the `port` nodes of this device, which are
+    /// either children of its `ports` child node, if it has one, or its own
+    /// children.
+    #[track_caller]
+    pub fn ports(&self) -> P::Output<PortsIter<'a, P>> {
+        P::to_output(crate::tryblock!({
+            let this = self.fallible();
+            let container = this.children()?.find("ports")?.unwrap_or(this);
+            Ok(PortsIter { children: Some(container.children()?.iter()) })
+        }))
+    }
+
+    /// Returns the port of this device with the port number `id`, given by
+    /// its `reg` property.
+    #[track_caller]
+    pub fn port(&self, id: u32) -> P::Output<Option<Port<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            let this = self.fallible();
+            let container = this.children()?.find("ports")?.unwrap_or(this);
+            for port in (PortsIter::<(P::Parser, NoPanic)> { children: Some(container.children()?.iter()) }) {
+                let port = port?;
+                if graph::graph_id::<P>(port.node)? == id {
+                    return Ok(Some(Port { node: port.node }));
+                }
+            }
+
+            Ok(None)
+        }))
+    }
+
+    /// Returns this node as a [`PciHostBridge`] if it has a `device_type` of
+    /// `"pci"`.
+    #[track_caller]
+    pub fn pci_host_bridge(&self) -> P::Output<Option<PciHostBridge<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            let this = self.fallible();
+            match this.properties()?.find("device_type")? {
+                Some(device_type) if device_type.as_value::<&str>()? == "pci" => Ok(Some(PciHostBridge { node: this })),
+                _ => Ok(None),
+            }
+        }))
+    }
+
+    /// Returns this node as a [`Gic`] if it's compatible with a GICv2 or
+    /// GICv3 interrupt controller, such as `arm,gic-400` or `arm,gic-v3`.
+    #[track_caller]
+    pub fn gic(&self) -> P::Output<Option<Gic<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            let this = self.fallible();
+            Ok(Gic::<P>::version_of(this)?.map(|version| Gic { node: this, version }))
+        }))
+    }
+
+    /// Returns this node as a [`Plic`] if it's compatible with a RISC-V PLIC,
+    /// such as `riscv,plic0` or `sifive,plic-1.0.0`.
+    #[track_caller]
+    pub fn plic(&self) -> P::Output<Option<Plic<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            let this = self.fallible();
+            Ok(riscv::compatible_with_any::<P>(this, riscv::PLIC_COMPATIBLES)?.then_some(Plic { node: this }))
+        }))
+    }
+
+    /// Returns this node as a [`Clint`] if it's compatible with a RISC-V CLINT
+    /// or one of the ACLINT devices, such as `riscv,aclint-mtimer`.
+    #[track_caller]
+    pub fn clint(&self) -> P::Output<Option<Clint<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            let this = self.fallible();
+            let kind = this.property::<Compatible>()?.and_then(ClintKind::from_compatible);
+            Ok(kind.map(|kind| Clint { node: this, kind }))
+        }))
+    }
+
+    /// Returns this node as an [`Aplic`] if it's compatible with
+    /// `riscv,aplic`.
+    #[track_caller]
+    pub fn aplic(&self) -> P::Output<Option<Aplic<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            let this = self.fallible();
+            Ok(riscv::compatible_with_any::<P>(this, &["riscv,aplic"])?.then_some(Aplic { node: this }))
+        }))
+    }
+
+    /// Returns this node as an [`Imsic`] if it's compatible with
+    /// `riscv,imsics`.
+    #[track_caller]
+    pub fn imsic(&self) -> P::Output<Option<Imsic<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            let this = self.fallible();
+            Ok(riscv::compatible_with_any::<P>(this, &["riscv,imsics"])?.then_some(Imsic { node: this }))
+        }))
+    }
+
+    /// Resolve the interrupt at `index` in this node's `interrupts-extended`
+    /// or `interrupts` property to the interrupt controller which receives it,
+    /// along with the interrupt specifier in that controller's interrupt
+    /// domain. Returns `None` if the node has fewer interrupts.
+    ///
+    /// The interrupt parent is found by following `interrupt-parent`
+    /// properties and devicetree parents, as with `interrupts`, and then any
+    /// nexus nodes are followed through their `interrupt-map`, with
+    /// `interrupt-map-mask` applied, until reaching a node with the
+    /// `interrupt-controller` property. Nexus nodes match on the unit address
+    /// of this node, taken from its `reg` property, as well as the interrupt
+    /// specifier, which is how interrupts from PCI devices are routed.
+    #[track_caller]
+    pub fn resolve_interrupt(&self, index: usize) -> P::Output<Option<ResolvedInterrupt<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            let resolved = interrupts::resolve_interrupt::<P>(self.fallible(), index)?;
+            Ok(resolved.map(|r| ResolvedInterrupt { controller: r.controller.alt(), specifier: r.specifier }))
+        }))
+    }
+
+    /// Map an interrupt in the interrupt domain of this node, given by the
+    /// unit address and interrupt specifier of its source, to the interrupt
+    /// controller which receives it. This is the same as
+    /// [`Node::resolve_interrupt`] after finding the interrupt parent, and
+    /// is useful for interrupt sources which aren't described in the
+    /// devicetree, such as discovered PCI devices, where `self` would be the
+    /// PCI host bridge, the unit address the PCI address of the device, and the
+    /// interrupt specifier the device's `INTx` pin.
+    #[track_caller]
+    pub fn map_interrupt(&self, unit_address: &[u32], specifier: &[u32]) -> P::Output<ResolvedInterrupt<'a, P>> {
+        P::to_output(crate::tryblock!({
+            let unit_address = CellArray::new(unit_address).ok_or(FdtError::CollectCellsError)?;
+            let specifier = CellArray::new(specifier).ok_or(FdtError::CollectCellsError)?;
+            let resolved = interrupts::map_interrupt::<P>(self.fallible(), unit_address, specifier)?;
+            Ok(ResolvedInterrupt { controller: resolved.controller.alt(), specifier: resolved.specifier })
+        }))
+    }
+
     /// Returns [`NodeProperties`] which allows searching and iterating over
     /// this node's properties.
     #[inline]
@@ -396,7 +883,7 @@ impl<'a, P: ParserWithMode<'a>> Copy for Node<'a, P> {}
 #[cfg(feature = "pretty-printing")]
 impl<'a, P: ParserWithMode<'a>> core::fmt::Display for Node<'a, P> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        crate::pretty_print::print_node(f, &self.fallible(), 0).map_err(|_| core::fmt::Error)
+        crate::pretty_print::print_node(f, &self.fallible(), None, 0).map_err(|_| core::fmt::Error)
     }
 }
 
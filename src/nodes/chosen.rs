@@ -42,6 +42,28 @@ impl<'a, P: ParserWithMode<'a>> Chosen<'a, P> {
         }))
     }
 
+    /// The physical address range of the initial ramdisk loaded by the
+    /// bootloader, given by the `linux,initrd-start` and `linux,initrd-end`
+    /// properties. Each may be either a 32-bit or 64-bit value.
+    #[track_caller]
+    pub fn initrd(self) -> P::Output<Option<Initrd>> {
+        P::to_output(crate::tryblock!({
+            let properties = self.node.properties()?;
+            let (start, end) = match (properties.find("linux,initrd-start")?, properties.find("linux,initrd-end")?) {
+                (Some(start), Some(end)) => (start, end),
+                (None, None) => return Ok(None),
+                (Some(_), None) => return Err(FdtError::MissingRequiredProperty("linux,initrd-end")),
+                (None, Some(_)) => return Err(FdtError::MissingRequiredProperty("linux,initrd-start")),
+            };
+
+            let initrd = Initrd { start: start.as_value::<u64>()?, end: end.as_value::<u64>()? };
+            match initrd.start <= initrd.end {
+                true => Ok(Some(initrd)),
+                false => Err(FdtError::InvalidPropertyValue),
+            }
+        }))
+    }
+
     /// Like [`Chosen::stdout_path`] but also attempts to resolve the path (also
     /// attempts to resolve the path to an alias if: the path does not look like
     /// a devicetree path, or the path is not found), and returns the stdout
@@ -213,6 +235,15 @@ impl<'a, P: ParserWithMode<'a>> core::fmt::Debug for Stdout<'a, P> {
     }
 }
 
+/// See [`Chosen::initrd`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Initrd {
+    /// Physical address of the start of the initial ramdisk.
+    pub start: u64,
+    /// Physical address of the end of the initial ramdisk, exclusive.
+    pub end: u64,
+}
+
 /// Like [`Stdout`] and [`Stdin`] but does not contain the resolved node, only
 /// its path and the optional parameters that may follow.
 pub struct StdInOutPath<'a> {
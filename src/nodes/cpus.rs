@@ -140,11 +140,18 @@ impl<'a, P: ParserWithMode<'a>> Iterator for CpusIter<'a, P> {
 /// but if an expected property is not found then it should look at the parent
 /// /cpus node. This results in a less verbose representation of properties
 /// which are identical across all CPUs.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug)]
 pub struct Cpu<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
     pub(crate) node: FallibleNode<'a, P>,
 }
 
+impl<'a, P: ParserWithMode<'a>> Copy for Cpu<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for Cpu<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
 impl<'a, P: ParserWithMode<'a>> Cpu<'a, P> {
     /// [Devicetree 3.8.1 General Properties of `/cpus/cpu*`
     /// nodes](https://devicetree-specification.readthedocs.io/en/latest/chapter3-devicenodes.html#general-properties-of-cpus-cpu-nodes)
@@ -0,0 +1,216 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use super::{AsNode, FallibleNode, Node};
+use crate::{
+    parsing::{aligned::AlignedParser, Panic, ParserWithMode},
+    properties::{
+        gic::GicInterrupt,
+        reg::{Reg, RegIter},
+        Compatible,
+    },
+    FdtError,
+};
+
+/// Compatible strings of GICv2 and earlier controllers with the GICv2 `reg`
+/// layout and interrupt specifiers.
+const GIC_V2_COMPATIBLES: &[&str] =
+    &["arm,gic-400", "arm,cortex-a15-gic", "arm,cortex-a9-gic", "arm,cortex-a7-gic", "arm,pl390"];
+
+/// [Devicetree ARM GIC
+/// binding](https://www.kernel.org/doc/Documentation/devicetree/bindings/interrupt-controller/arm%2Cgic-v3.yaml)
+/// interrupt controller node
+///
+/// An ARM Generic Interrupt Controller, such as an `arm,gic-400` or
+/// `arm,gic-v3` node, whose `reg` property lists its register regions in an
+/// order defined by the GIC version. See [`Node::gic`].
+pub struct Gic<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    pub(crate) node: FallibleNode<'a, P>,
+    pub(crate) version: GicVersion,
+}
+
+impl<'a, P: ParserWithMode<'a>> Gic<'a, P> {
+    /// The architecture version of the controller, which determines its
+    /// `reg` layout and interrupt specifier format.
+    pub fn version(&self) -> GicVersion {
+        self.version
+    }
+
+    /// Decode an interrupt specifier of this controller, such as the
+    /// specifier of a [`ResolvedInterrupt`], according to its version.
+    ///
+    /// [`ResolvedInterrupt`]: crate::properties::interrupts::ResolvedInterrupt
+    pub fn decode_interrupt(&self, specifier: &[u32]) -> Option<GicInterrupt> {
+        match self.version {
+            GicVersion::V2 => GicInterrupt::decode_v2(specifier),
+            GicVersion::V3 => GicInterrupt::decode_v3(specifier),
+        }
+    }
+
+    /// The distributor (GICD) register region, which is the first `reg`
+    /// entry.
+    #[track_caller]
+    pub fn distributor(&self) -> P::Output<Option<GicRegion>> {
+        P::to_output(self.region(0))
+    }
+
+    /// The CPU interface (GICC) register region. This is required for GICv2,
+    /// and optional for GICv3, where it's only present for legacy software.
+    #[track_caller]
+    pub fn cpu_interface(&self) -> P::Output<Option<GicRegion>> {
+        P::to_output(self.region_after_redistributors(1))
+    }
+
+    /// The virtual interface control (GICH) register region, which is present
+    /// if the controller supports virtualization.
+    #[track_caller]
+    pub fn hypervisor_interface(&self) -> P::Output<Option<GicRegion>> {
+        P::to_output(self.region_after_redistributors(2))
+    }
+
+    /// The virtual CPU interface (GICV) register region, which is present if
+    /// the controller supports virtualization.
+    #[track_caller]
+    pub fn virtual_cpu_interface(&self) -> P::Output<Option<GicRegion>> {
+        P::to_output(self.region_after_redistributors(3))
+    }
+
+    /// The number of GICv3 redistributor (GICR) regions, from
+    /// `#redistributor-regions`, which defaults to one. This is always zero
+    /// for GICv2.
+    #[track_caller]
+    pub fn redistributor_region_count(&self) -> P::Output<usize> {
+        P::to_output(self.fallible_redistributor_region_count())
+    }
+
+    /// Returns an iterator over the GICv3 redistributor (GICR) regions, which
+    /// follow the distributor in `reg`. Each region contains the
+    /// redistributors of a contiguous group of CPUs.
+    #[track_caller]
+    pub fn redistributor_regions(&self) -> P::Output<GicRegionsIter<'a, P>> {
+        P::to_output(crate::tryblock!({
+            let count = self.fallible_redistributor_region_count()?;
+            let reg = match count {
+                0 => None,
+                _ => self.node.property::<Reg>()?,
+            };
+
+            let mut entries = reg.map(|reg| reg.iter::<u64, u64>());
+            if let Some(entries) = entries.as_mut() {
+                entries.next();
+            }
+
+            Ok(GicRegionsIter { entries, remaining: count, _mode: core::marker::PhantomData })
+        }))
+    }
+
+    /// The distance in bytes between the redistributors of consecutive CPUs
+    /// within a redistributor region, from `redistributor-stride`. If this is
+    /// `None`, the redistributors are contiguous and their size is discovered
+    /// from the hardware.
+    #[track_caller]
+    pub fn redistributor_stride(&self) -> P::Output<Option<u64>> {
+        P::to_output(crate::tryblock!({
+            match self.node.properties()?.find("redistributor-stride")? {
+                Some(stride) => Ok(Some(stride.as_value()?)),
+                None => Ok(None),
+            }
+        }))
+    }
+
+    fn fallible_redistributor_region_count(&self) -> Result<usize, FdtError> {
+        if self.version == GicVersion::V2 {
+            return Ok(0);
+        }
+
+        match self.node.properties()?.find("#redistributor-regions")? {
+            Some(count) => Ok(count.as_value::<u32>()? as usize),
+            None => Ok(1),
+        }
+    }
+
+    /// The region `offset` entries after the first, skipping the
+    /// redistributor regions.
+    fn region_after_redistributors(&self, offset: usize) -> Result<Option<GicRegion>, FdtError> {
+        let count = self.fallible_redistributor_region_count()?;
+        self.region(count.checked_add(offset).ok_or(FdtError::InvalidPropertyValue)?)
+    }
+
+    fn region(&self, index: usize) -> Result<Option<GicRegion>, FdtError> {
+        let Some(reg) = self.node.property::<Reg>()? else { return Ok(None) };
+        match reg.iter::<u64, u64>().nth(index) {
+            Some(Ok(entry)) => Ok(Some(GicRegion { address: entry.address, size: entry.len })),
+            Some(Err(_)) => Err(FdtError::CollectCellsError),
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) fn version_of(node: FallibleNode<'a, P>) -> Result<Option<GicVersion>, FdtError> {
+        let Some(compatible) = node.property::<Compatible>()? else { return Ok(None) };
+        if compatible.compatible_with("arm,gic-v3") {
+            Ok(Some(GicVersion::V3))
+        } else if GIC_V2_COMPATIBLES.iter().any(|c| compatible.compatible_with(c)) {
+            Ok(Some(GicVersion::V2))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for Gic<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for Gic<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> AsNode<'a, P> for Gic<'a, P> {
+    fn as_node(&self) -> Node<'a, P> {
+        self.node.alt()
+    }
+}
+
+/// The architecture version of a [`Gic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GicVersion {
+    /// GICv2, or an earlier controller with the same layout, such as an
+    /// `arm,gic-400` or `arm,cortex-a15-gic`.
+    V2,
+    /// GICv3 or GICv4, an `arm,gic-v3`.
+    V3,
+}
+
+/// A register region of a [`Gic`], from its `reg` property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GicRegion {
+    /// Address of the region on the controller's parent bus.
+    pub address: u64,
+    /// Size of the region in bytes.
+    pub size: u64,
+}
+
+/// See [`Gic::redistributor_regions`].
+pub struct GicRegionsIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    entries: Option<RegIter<'a, u64, u64>>,
+    remaining: usize,
+    _mode: core::marker::PhantomData<*mut P>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for GicRegionsIter<'a, P> {
+    type Item = P::Output<GicRegion>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.remaining = self.remaining.checked_sub(1)?;
+        let entry = match self.entries.as_mut()?.next()? {
+            Ok(entry) => entry,
+            Err(_) => {
+                self.entries = None;
+                return Some(P::to_output(Err(FdtError::CollectCellsError)));
+            }
+        };
+
+        Some(P::to_output(Ok(GicRegion { address: entry.address, size: entry.len })))
+    }
+}
@@ -0,0 +1,68 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use super::{AsNode, FallibleNode, Node};
+use crate::{
+    parsing::{aligned::AlignedParser, Panic, ParserWithMode},
+    properties::gpio::{GpioCells, GpioLineNames, GpioRanges, Ngpios},
+    FdtError,
+};
+
+/// [Devicetree GPIO
+/// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/gpio/gpio.yaml)
+/// GPIO controller node
+///
+/// A node with the `gpio-controller` property, which provides the GPIOs
+/// referred to by the `*-gpios` properties of other nodes. See
+/// [`Node::gpio_controller`].
+pub struct GpioController<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    pub(crate) node: FallibleNode<'a, P>,
+}
+
+impl<'a, P: ParserWithMode<'a>> GpioController<'a, P> {
+    /// The number of cells in a GPIO specifier for this controller, from
+    /// `#gpio-cells`.
+    #[track_caller]
+    pub fn gpio_cells(&self) -> P::Output<usize> {
+        P::to_output(crate::tryblock!({
+            match self.node.property::<GpioCells>()? {
+                Some(GpioCells(cells)) => Ok(cells),
+                None => Err(FdtError::MissingRequiredProperty("#gpio-cells")),
+            }
+        }))
+    }
+
+    /// The number of GPIO lines usable on the controller, from `ngpios`, when
+    /// fewer than the hardware supports.
+    #[track_caller]
+    pub fn ngpios(&self) -> P::Output<Option<u32>> {
+        P::to_output(crate::tryblock!({ Ok(self.node.property::<Ngpios>()?.map(|Ngpios(ngpios)| ngpios)) }))
+    }
+
+    /// The names of the GPIO lines, from `gpio-line-names`.
+    #[track_caller]
+    pub fn line_names(&self) -> P::Output<Option<GpioLineNames<'a>>> {
+        P::to_output(self.node.property::<GpioLineNames<'a>>())
+    }
+
+    /// The ranges of GPIO lines routed to pins of a pin controller, from
+    /// `gpio-ranges`.
+    #[track_caller]
+    pub fn ranges(&self) -> P::Output<Option<GpioRanges<'a, P>>> {
+        self.node.alt::<P>().property::<GpioRanges<'a, P>>()
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for GpioController<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for GpioController<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> AsNode<'a, P> for GpioController<'a, P> {
+    fn as_node(&self) -> Node<'a, P> {
+        self.node.alt()
+    }
+}
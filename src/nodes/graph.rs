@@ -0,0 +1,250 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use super::{AsNode, FallibleNode, Node, NodeChildrenIter};
+use crate::{
+    helpers::{FallibleParser, FallibleRoot},
+    parsing::{aligned::AlignedParser, NoPanic, Panic, ParserWithMode},
+    properties::{values::U32List, PHandle},
+    FdtError,
+};
+
+/// [Devicetree graph
+/// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/graph.yaml)
+///
+/// A `port` node of a device, which describes one of its data interfaces, such
+/// as a display output or camera input. Ports are either direct children of
+/// the device node or children of a `ports` child node, and contain
+/// `endpoint` nodes which are linked to the endpoints of other devices.
+pub struct Port<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    pub(crate) node: FallibleNode<'a, P>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Port<'a, P> {
+    /// The port number, from the `reg` property, which defaults to zero for
+    /// a port without one.
+    #[track_caller]
+    pub fn id(&self) -> P::Output<u32> {
+        P::to_output(graph_id::<P>(self.node))
+    }
+
+    /// Returns an iterator over the endpoints of the port.
+    #[track_caller]
+    pub fn endpoints(&self) -> P::Output<EndpointsIter<'a, P>> {
+        P::to_output(crate::tryblock!({ Ok(EndpointsIter { children: Some(self.node.children()?.iter()) }) }))
+    }
+
+    /// Returns the endpoint with the endpoint number `id`.
+    #[track_caller]
+    pub fn endpoint(&self, id: u32) -> P::Output<Option<Endpoint<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            for endpoint in (EndpointsIter::<(P::Parser, NoPanic)> { children: Some(self.node.children()?.iter()) }) {
+                let endpoint = endpoint?;
+                if graph_id::<P>(endpoint.node)? == id {
+                    return Ok(Some(Endpoint { node: endpoint.node }));
+                }
+            }
+
+            Ok(None)
+        }))
+    }
+
+    /// The device node the port belongs to.
+    #[track_caller]
+    pub fn device(&self) -> P::Output<Node<'a, P>> {
+        P::to_output(crate::tryblock!({
+            let parent = parent_of::<P>(self.node)?;
+            match parent.name()?.name {
+                "ports" => Ok(parent_of::<P>(parent)?.alt()),
+                _ => Ok(parent.alt()),
+            }
+        }))
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> AsNode<'a, P> for Port<'a, P> {
+    fn as_node(&self) -> Node<'a, P> {
+        self.node.alt()
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for Port<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for Port<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+/// [Devicetree graph
+/// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/graph.yaml)
+///
+/// An `endpoint` node of a [`Port`], which is linked to the endpoint of
+/// another device's port by its `remote-endpoint` property.
+pub struct Endpoint<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    pub(crate) node: FallibleNode<'a, P>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Endpoint<'a, P> {
+    /// The endpoint number, from the `reg` property, which defaults to zero
+    /// for an endpoint without one.
+    #[track_caller]
+    pub fn id(&self) -> P::Output<u32> {
+        P::to_output(graph_id::<P>(self.node))
+    }
+
+    /// The port the endpoint belongs to.
+    #[track_caller]
+    pub fn port(&self) -> P::Output<Port<'a, P>> {
+        P::to_output(parent_of::<P>(self.node).map(|node| Port { node }))
+    }
+
+    /// Follow the `remote-endpoint` property to the linked endpoint, if any.
+    #[track_caller]
+    pub fn remote_endpoint(&self) -> P::Output<Option<Endpoint<'a, P>>> {
+        P::to_output(self.fallible_remote_endpoint().map(|e| e.map(|e| Endpoint { node: e.node })))
+    }
+
+    /// The port of the linked endpoint, if any.
+    #[track_caller]
+    pub fn remote_port(&self) -> P::Output<Option<Port<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            match self.fallible_remote_endpoint()? {
+                Some(remote) => Ok(Some(Port { node: parent_of::<P>(remote.node)? })),
+                None => Ok(None),
+            }
+        }))
+    }
+
+    /// The device node of the linked endpoint, if any.
+    #[track_caller]
+    pub fn remote_device(&self) -> P::Output<Option<Node<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            match self.fallible_remote_endpoint()? {
+                Some(remote) => Ok(Some(remote.port()?.device()?.alt())),
+                None => Ok(None),
+            }
+        }))
+    }
+
+    /// The `data-lanes` property of a video interface endpoint, which gives
+    /// the physical data lane used for each logical lane, starting from one.
+    #[track_caller]
+    pub fn data_lanes(&self) -> P::Output<Option<U32List<'a>>> {
+        P::to_output(self.u32_list("data-lanes"))
+    }
+
+    /// The `clock-lanes` property of a video interface endpoint, which gives
+    /// the physical clock lanes.
+    #[track_caller]
+    pub fn clock_lanes(&self) -> P::Output<Option<U32List<'a>>> {
+        P::to_output(self.u32_list("clock-lanes"))
+    }
+
+    /// The `bus-width` property of a parallel video interface endpoint, which
+    /// gives the number of data lines.
+    #[track_caller]
+    pub fn bus_width(&self) -> P::Output<Option<u32>> {
+        P::to_output(crate::tryblock!({
+            match self.node.properties()?.find("bus-width")? {
+                Some(width) => Ok(Some(width.as_value()?)),
+                None => Ok(None),
+            }
+        }))
+    }
+
+    fn u32_list(&self, name: &str) -> Result<Option<U32List<'a>>, FdtError> {
+        match self.node.properties()?.find(name)? {
+            Some(list) => Ok(Some(list.as_value()?)),
+            None => Ok(None),
+        }
+    }
+
+    fn fallible_remote_endpoint(&self) -> Result<Option<Endpoint<'a, FallibleParser<'a, P>>>, FdtError> {
+        let Some(remote) = self.node.properties()?.find("remote-endpoint")? else { return Ok(None) };
+        let phandle = remote.as_value::<PHandle>()?;
+
+        let root: FallibleRoot<'a, P> = self.node.make_root::<P::Parser>()?;
+        match root.resolve_phandle(phandle)? {
+            Some(node) => Ok(Some(Endpoint { node })),
+            None => Err(FdtError::MissingPHandleNode(phandle.as_u32())),
+        }
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> AsNode<'a, P> for Endpoint<'a, P> {
+    fn as_node(&self) -> Node<'a, P> {
+        self.node.alt()
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for Endpoint<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for Endpoint<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+/// See [`Node::ports`].
+pub struct PortsIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    pub(crate) children: Option<NodeChildrenIter<'a, (P::Parser, NoPanic)>>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for PortsIter<'a, P> {
+    type Item = P::Output<Port<'a, P>>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = next_named::<P>(&mut self.children, "port")?;
+        Some(P::to_output(res.map(|node| Port { node })))
+    }
+}
+
+/// See [`Port::endpoints`].
+pub struct EndpointsIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    children: Option<NodeChildrenIter<'a, (P::Parser, NoPanic)>>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for EndpointsIter<'a, P> {
+    type Item = P::Output<Endpoint<'a, P>>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = next_named::<P>(&mut self.children, "endpoint")?;
+        Some(P::to_output(res.map(|node| Endpoint { node })))
+    }
+}
+
+/// Returns the next child named `name`, stopping after any error.
+fn next_named<'a, P: ParserWithMode<'a>>(
+    children: &mut Option<NodeChildrenIter<'a, (P::Parser, NoPanic)>>,
+    name: &str,
+) -> Option<Result<FallibleNode<'a, P>, FdtError>> {
+    let res = children.as_mut()?.find_map(|child| match child.and_then(|child| child.name().map(|n| (child, n))) {
+        Ok((child, node_name)) => (node_name.name == name).then_some(Ok(child)),
+        Err(e) => Some(Err(e)),
+    });
+
+    if !matches!(res, Some(Ok(_))) {
+        *children = None;
+    }
+
+    res
+}
+
+/// The port or endpoint number given by the `reg` property of `node`.
+pub(crate) fn graph_id<'a, P: ParserWithMode<'a>>(node: FallibleNode<'a, P>) -> Result<u32, FdtError> {
+    match node.properties()?.find("reg")? {
+        Some(reg) => Ok(reg.as_value()?),
+        None => Ok(0),
+    }
+}
+
+/// Find the parent of `node`, searching from the root if it wasn't created
+/// with one, such as for a node returned by [`Node::parent`].
+fn parent_of<'a, P: ParserWithMode<'a>>(node: FallibleNode<'a, P>) -> Result<FallibleNode<'a, P>, FdtError> {
+    match node.parent() {
+        Some(parent) => Ok(parent),
+        None => node.full_parent()?.ok_or(FdtError::MissingParent),
+    }
+}
@@ -1,15 +1,16 @@
 use crate::{
     cell_collector::{BuildCellCollector, CellCollector, CollectCellsError},
-    parsing::{aligned::AlignedParser, NoPanic, Panic, ParserWithMode},
+    memory_map::MemoryRange,
+    parsing::{aligned::AlignedParser, NoPanic, Panic, ParseError, ParserWithMode},
     properties::{
         cells::{CellSizes, SizeCells},
-        reg::Reg,
+        reg::{Reg, RegEntry, RegIter},
         Compatible,
     },
     FdtError,
 };
 
-use super::{AsNode, FallibleNode, NodeChildrenIter, NodeName};
+use super::{root::AllNodesIter, AsNode, FallibleNode, NodeChildrenIter, NodeName};
 
 /// [Devicetree 3.4. `/memory`
 /// node](https://devicetree-specification.readthedocs.io/en/latest/chapter3-devicenodes.html#memory-node)
@@ -100,6 +101,21 @@ impl<'a, P: ParserWithMode<'a>> Memory<'a, P> {
     pub fn hotpluggable(&self) -> P::Output<bool> {
         P::to_output(crate::tryblock!({ Ok(self.node.properties()?.find("hotpluggable")?.is_some()) }))
     }
+
+    /// [Devicetree NUMA
+    /// binding](https://www.kernel.org/doc/Documentation/devicetree/bindings/numa.txt)
+    ///
+    /// **Optional**
+    ///
+    /// The NUMA node the memory ranges of this node belong to, which can be
+    /// matched against the `numa-node-id` of CPU nodes and the entries of a
+    /// `distance-map`.
+    #[track_caller]
+    pub fn numa_node_id(&self) -> P::Output<Option<u32>> {
+        P::to_output(crate::tryblock!({
+            self.node.properties()?.find("numa-node-id")?.map(|p| p.as_value::<u32>().map_err(Into::into)).transpose()
+        }))
+    }
 }
 
 impl<'a, P: ParserWithMode<'a>> AsNode<'a, P> for Memory<'a, P> {
@@ -108,6 +124,120 @@ impl<'a, P: ParserWithMode<'a>> AsNode<'a, P> for Memory<'a, P> {
     }
 }
 
+/// Iterator over every memory node in the devicetree. See
+/// [`Root::all_memory`](super::root::Root::all_memory).
+pub struct AllMemoryIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    pub(crate) iter: AllNodesIter<'a, (P::Parser, NoPanic)>,
+}
+
+impl<'a, P: ParserWithMode<'a>> AllMemoryIter<'a, P> {
+    /// Create an iterator over the `reg` entries of every remaining memory
+    /// node, in the order the nodes appear in the devicetree. The entries are
+    /// only concatenated, so they may be unsorted, adjacent or overlapping;
+    /// use [`AllMemoryIter::merged_regions`] to combine them.
+    pub fn reg_entries<Addr: CellCollector, Len: CellCollector>(self) -> MemoryRegEntriesIter<'a, Addr, Len, P> {
+        MemoryRegEntriesIter { nodes: AllMemoryIter { iter: self.iter }, reg: None }
+    }
+
+    /// Write the `reg` entries of every remaining memory node into `ranges`,
+    /// returning the number of ranges written. The ranges are sorted by
+    /// address, overlapping or adjacent ranges are merged, and empty entries
+    /// are skipped.
+    ///
+    /// Returns [`FdtError::SliceTooSmall`] if `ranges` isn't large enough to
+    /// hold every entry before merging, or [`FdtError::CollectCellsError`] if
+    /// an entry doesn't fit in a [`u64`].
+    #[track_caller]
+    pub fn merged_regions(self, ranges: &mut [MemoryRange]) -> P::Output<usize> {
+        P::to_output(crate::tryblock!({
+            let nodes: AllMemoryIter<'a, (P::Parser, NoPanic)> = AllMemoryIter { iter: self.iter };
+            let mut len = 0;
+            for entry in nodes.reg_entries::<u64, u64>() {
+                let entry = entry?.map_err(|_| FdtError::CollectCellsError)?;
+                if entry.len == 0 {
+                    continue;
+                }
+
+                *ranges.get_mut(len).ok_or(FdtError::SliceTooSmall)? =
+                    MemoryRange { address: entry.address, size: entry.len };
+                len += 1;
+            }
+
+            ranges[..len].sort_unstable();
+
+            let mut merged: usize = 0;
+            for i in 0..len {
+                let range = ranges[i];
+                match merged.checked_sub(1).map(|last| &mut ranges[last]) {
+                    Some(last) if range.address <= last.end() => {
+                        last.size = range.end().max(last.end()) - last.address;
+                    }
+                    _ => {
+                        ranges[merged] = range;
+                        merged += 1;
+                    }
+                }
+            }
+
+            Ok(merged)
+        }))
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for AllMemoryIter<'a, P> {
+    type Item = P::Output<Memory<'a, P>>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        for next in self.iter.by_ref() {
+            let res = next.and_then(|(_, node)| match node.properties()?.find("device_type")? {
+                Some(device_type) => Ok((device_type.as_value::<&str>()? == "memory").then_some(node)),
+                None => Ok(None),
+            });
+
+            match res {
+                Ok(Some(node)) => return Some(P::to_output(Ok(Memory { node }))),
+                Ok(None) => continue,
+                Err(e) => return Some(P::to_output(Err(e))),
+            }
+        }
+
+        None
+    }
+}
+
+/// Iterator over the `reg` entries of every memory node. See
+/// [`AllMemoryIter::reg_entries`].
+pub struct MemoryRegEntriesIter<'a, Addr: CellCollector, Len: CellCollector, P: ParserWithMode<'a>> {
+    nodes: AllMemoryIter<'a, (P::Parser, NoPanic)>,
+    reg: Option<RegIter<'a, Addr, Len>>,
+}
+
+impl<'a, Addr: CellCollector, Len: CellCollector, P: ParserWithMode<'a>> Iterator
+    for MemoryRegEntriesIter<'a, Addr, Len, P>
+{
+    type Item = P::Output<Result<RegEntry<Addr::Output, Len::Output>, CollectCellsError>>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.reg.as_mut().and_then(Iterator::next) {
+                return Some(P::to_output(Ok(entry)));
+            }
+
+            let reg = match self.nodes.next()? {
+                Ok(memory) => memory.reg(),
+                Err(e) => Err(e),
+            };
+
+            match reg {
+                Ok(reg) => self.reg = Some(reg.iter()),
+                Err(e) => return Some(P::to_output(Err(e))),
+            }
+        }
+    }
+}
+
 /// Describes the initial mapped area of the `/memory` node. See
 /// [`Memory::initial_mapped_area`].
 #[allow(missing_docs)]
@@ -175,7 +305,7 @@ impl<'a, P: ParserWithMode<'a>> Iterator for ReservedMemoryChildrenIter<'a, P> {
 
 #[allow(missing_docs)]
 pub struct ReservedMemoryChild<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
-    node: FallibleNode<'a, P>,
+    pub(crate) node: FallibleNode<'a, P>,
 }
 
 impl<'a, P: ParserWithMode<'a>> ReservedMemoryChild<'a, P> {
@@ -261,6 +391,18 @@ impl<'a, P: ParserWithMode<'a>> ReservedMemoryChild<'a, P> {
         }))
     }
 
+    /// [Devicetree 3.5.2. `/reserved-memory` child
+    /// nodes](https://devicetree-specification.readthedocs.io/en/latest/chapter3-devicenodes.html#table-5)
+    ///
+    /// **Optional**
+    ///
+    /// Address and length pairs, in the same format as `reg`, specifying the
+    /// acceptable regions of memory to dynamically allocate the region from.
+    /// If not present, the region can be allocated from anywhere in memory.
+    pub fn alloc_ranges(&self) -> P::Output<Option<Reg<'a>>> {
+        P::to_output(Reg::parse_named::<P>(self.node, "alloc-ranges"))
+    }
+
     /// [Devicetree 3.5.2. `/reserved-memory` child
     /// nodes](https://devicetree-specification.readthedocs.io/en/latest/chapter3-devicenodes.html#table-5)
     ///
@@ -302,7 +444,7 @@ impl<'a, P: ParserWithMode<'a>> ReservedMemoryChild<'a, P> {
     /// use that region to store volatile or cached data that can be otherwise
     /// regenerated or migrated elsewhere.
     pub fn reusable(&self) -> P::Output<bool> {
-        P::to_output(self.node.properties().and_then(|p| p.find("no-map").map(|p| p.is_some())))
+        P::to_output(self.node.properties().and_then(|p| p.find("reusable").map(|p| p.is_some())))
     }
 
     /// [Devicetree 3.5.2. `/reserved-memory` child
@@ -340,3 +482,59 @@ pub struct MemoryRegion {
     #[allow(missing_docs)]
     pub size: Option<usize>,
 }
+
+/// [Devicetree 5.3. Memory Reservation
+/// Block](https://devicetree-specification.readthedocs.io/en/latest/chapter5-flattened-format.html#memory-reservation-block)
+///
+/// A single entry of the memory reservation block, describing a range of
+/// physical memory which is reserved and must not be used by the client program
+/// for general memory allocations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MemoryReservation {
+    /// Physical address of the reserved region
+    pub address: u64,
+    /// Size in bytes of the reserved region
+    pub size: u64,
+}
+
+/// Iterator over the entries of the memory reservation block. See
+/// [`Fdt::memory_reservations`](crate::Fdt::memory_reservations).
+pub struct MemoryReservationsIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    pub(crate) block: &'a [u8],
+    pub(crate) finished: bool,
+    pub(crate) _mode: core::marker::PhantomData<*mut P>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Clone for MemoryReservationsIter<'a, P> {
+    fn clone(&self) -> Self {
+        Self { block: self.block, finished: self.finished, _mode: core::marker::PhantomData }
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for MemoryReservationsIter<'a, P> {
+    type Item = P::Output<MemoryReservation>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        const ENTRY_SIZE: usize = 16;
+        let Some((entry, rest)) = self.block.split_at_checked(ENTRY_SIZE) else {
+            self.finished = true;
+            return Some(P::to_output(Err(FdtError::ParseError(ParseError::UnexpectedEndOfData))));
+        };
+
+        let address = u64::from_be_bytes(entry[..8].try_into().unwrap());
+        let size = u64::from_be_bytes(entry[8..].try_into().unwrap());
+
+        if address == 0 && size == 0 {
+            self.finished = true;
+            return None;
+        }
+
+        self.block = rest;
+        Some(P::to_output(Ok(MemoryReservation { address, size })))
+    }
+}
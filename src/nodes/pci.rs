@@ -0,0 +1,219 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use super::{AsNode, FallibleNode, Node};
+use crate::{
+    parsing::{aligned::AlignedParser, Panic, ParserWithMode},
+    properties::{
+        id_map::{IdMap, MappedId},
+        pci::{PciAddress, PciAddressSpace},
+        ranges::{Ranges, RangesIter},
+        reg::Reg,
+    },
+    FdtError,
+};
+
+/// [PCI Bus Binding to Open Firmware](https://www.openfirmware.info/data/docs/bus.pci.pdf)
+/// host bridge node
+///
+/// A PCI or PCIe host bridge, such as a `pci-host-ecam-generic` node, which
+/// has a `device_type` of `"pci"`. See [`Node::pci_host_bridge`].
+pub struct PciHostBridge<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    pub(crate) node: FallibleNode<'a, P>,
+}
+
+impl<'a, P: ParserWithMode<'a>> PciHostBridge<'a, P> {
+    /// The range of bus numbers below the host bridge, from `bus-range`,
+    /// which defaults to all 256 buses.
+    #[track_caller]
+    pub fn bus_range(&self) -> P::Output<BusRange> {
+        P::to_output(self.fallible_bus_range())
+    }
+
+    /// The PCI domain (segment) number assigned to the host bridge, from
+    /// `linux,pci-domain`.
+    #[track_caller]
+    pub fn domain(&self) -> P::Output<Option<u32>> {
+        P::to_output(crate::tryblock!({
+            match self.node.properties()?.find("linux,pci-domain")? {
+                Some(domain) => Ok(Some(domain.as_value()?)),
+                None => Ok(None),
+            }
+        }))
+    }
+
+    /// The ECAM (enhanced configuration access mechanism) window of the host
+    /// bridge, which is the first entry of its `reg` property, covering the
+    /// buses in [`PciHostBridge::bus_range`].
+    #[track_caller]
+    pub fn ecam(&self) -> P::Output<Option<EcamWindow>> {
+        P::to_output(crate::tryblock!({
+            let Some(reg) = self.node.property::<Reg>()? else { return Ok(None) };
+            let Some(entry) = reg.iter::<u64, u64>().next() else { return Ok(None) };
+            let entry = entry.map_err(|_| FdtError::CollectCellsError)?;
+
+            Ok(Some(EcamWindow { address: entry.address, size: entry.len, bus_range: self.fallible_bus_range()? }))
+        }))
+    }
+
+    /// Returns an iterator over the address windows the host bridge forwards
+    /// to PCI, from its `ranges` property.
+    #[track_caller]
+    pub fn windows(&self) -> P::Output<PciWindowsIter<'a, P>> {
+        P::to_output(crate::tryblock!({
+            let ranges = self.node.property::<Ranges>()?;
+            Ok(PciWindowsIter { ranges: ranges.map(Ranges::iter), _mode: core::marker::PhantomData })
+        }))
+    }
+
+    /// Translate the PCI requester ID `rid`, made up of the bus, device and
+    /// function numbers, through the `msi-map` property to the MSI controller
+    /// which receives MSIs from the device, along with the device ID it
+    /// receives them with. `msi-map-mask` is applied to `rid` first.
+    ///
+    /// Returns `None` if the host bridge has no `msi-map` or no entry of it
+    /// covers `rid`.
+    #[track_caller]
+    pub fn map_msi(&self, rid: u32) -> P::Output<Option<MappedId<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            match IdMap::<P>::parse(self.node, "msi-map", "msi-map-mask")? {
+                Some(map) => map.fallible_map(rid),
+                None => Ok(None),
+            }
+        }))
+    }
+
+    /// Translate the PCI requester ID `rid` through the `iommu-map` property
+    /// to the IOMMU which translates the device's DMA, along with the stream
+    /// ID it uses. `iommu-map-mask` is applied to `rid` first.
+    ///
+    /// Returns `None` if the host bridge has no `iommu-map` or no entry of it
+    /// covers `rid`.
+    #[track_caller]
+    pub fn map_iommu(&self, rid: u32) -> P::Output<Option<MappedId<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            match IdMap::<P>::parse(self.node, "iommu-map", "iommu-map-mask")? {
+                Some(map) => map.fallible_map(rid),
+                None => Ok(None),
+            }
+        }))
+    }
+
+    fn fallible_bus_range(&self) -> Result<BusRange, FdtError> {
+        let Some(range) = self.node.properties()?.find("bus-range")? else {
+            return Ok(BusRange { start: 0, end: 255 });
+        };
+
+        match range.value.len() {
+            8 => {
+                let start = u32::from_be_bytes(range.value[..4].try_into().unwrap());
+                let end = u32::from_be_bytes(range.value[4..].try_into().unwrap());
+                match start <= end && end <= 255 {
+                    true => Ok(BusRange { start: start as u8, end: end as u8 }),
+                    false => Err(FdtError::InvalidPropertyValue),
+                }
+            }
+            _ => Err(FdtError::InvalidPropertyValue),
+        }
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for PciHostBridge<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for PciHostBridge<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> AsNode<'a, P> for PciHostBridge<'a, P> {
+    fn as_node(&self) -> Node<'a, P> {
+        self.node.alt()
+    }
+}
+
+/// An inclusive range of PCI bus numbers, from a `bus-range` property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BusRange {
+    /// The first bus number.
+    pub start: u8,
+    /// The last bus number.
+    pub end: u8,
+}
+
+/// The ECAM window of a [`PciHostBridge`], see [`PciHostBridge::ecam`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EcamWindow {
+    /// Address of the window on the host bridge's parent bus.
+    pub address: u64,
+    /// Size of the window in bytes.
+    pub size: u64,
+    /// The buses covered by the window, of which the first is at `address`.
+    pub bus_range: BusRange,
+}
+
+impl EcamWindow {
+    /// The address of the configuration space register `register` of the
+    /// function `function` of device `device` on bus `bus`, or `None` if the
+    /// bus isn't covered by the window or the location is out of range.
+    pub fn config_address(&self, bus: u8, device: u8, function: u8, register: u16) -> Option<u64> {
+        if !(self.bus_range.start..=self.bus_range.end).contains(&bus) || device >= 32 || function >= 8 {
+            return None;
+        }
+
+        let offset = u64::from(bus - self.bus_range.start) << 20
+            | u64::from(device) << 15
+            | u64::from(function) << 12
+            | u64::from(register & 0xfff);
+
+        self.address.checked_add(offset).filter(|_| offset < self.size)
+    }
+}
+
+/// See [`PciHostBridge::windows`].
+pub struct PciWindowsIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    ranges: Option<RangesIter<'a, PciAddress, u64, u64>>,
+    _mode: core::marker::PhantomData<*mut P>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for PciWindowsIter<'a, P> {
+    type Item = P::Output<PciWindow>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        let range = match self.ranges.as_mut()?.next()? {
+            Ok(range) => range,
+            Err(_) => {
+                self.ranges = None;
+                return Some(P::to_output(Err(FdtError::CollectCellsError)));
+            }
+        };
+
+        let hi = range.child_bus_address.hi;
+        Some(P::to_output(Ok(PciWindow {
+            space: hi.address_space(),
+            prefetchable: hi.prefetchable(),
+            pci_address: u64::from(range.child_bus_address.mid) << 32 | u64::from(range.child_bus_address.lo),
+            parent_bus_address: range.parent_bus_address,
+            size: range.len,
+        })))
+    }
+}
+
+/// An address window of a [`PciHostBridge`], see
+/// [`PciHostBridge::windows`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PciWindow {
+    /// The PCI address space of the window, which is either
+    /// [`PciAddressSpace::Io`], [`PciAddressSpace::Memory32`], or
+    /// [`PciAddressSpace::Memory64`] for a valid host bridge.
+    pub space: PciAddressSpace,
+    /// The memory in the window is prefetchable.
+    pub prefetchable: bool,
+    /// Address of the window on the PCI bus.
+    pub pci_address: u64,
+    /// Address of the window on the host bridge's parent bus.
+    pub parent_bus_address: u64,
+    /// Size of the window in bytes.
+    pub size: u64,
+}
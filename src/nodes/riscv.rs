@@ -0,0 +1,429 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use super::{cpus::Cpu, AsNode, FallibleNode, Node};
+use crate::{
+    helpers::{FallibleParser, FallibleRoot},
+    nodes::root::Root,
+    parsing::{aligned::AlignedParser, Panic, ParserWithMode},
+    properties::{
+        interrupts::{ExtendedInterrupts, ExtendedInterruptsIter},
+        msi::MsiParent,
+        Compatible, PHandle,
+    },
+    FdtError,
+};
+
+/// Compatible strings of PLICs.
+pub(crate) const PLIC_COMPATIBLES: &[&str] = &["riscv,plic0", "sifive,plic-1.0.0"];
+
+/// [Devicetree RISC-V PLIC
+/// binding](https://www.kernel.org/doc/Documentation/devicetree/bindings/interrupt-controller/sifive%2Cplic-1.0.0.yaml)
+///
+/// A RISC-V platform-level interrupt controller, which routes the interrupts
+/// of devices to the external interrupt inputs of the harts. Each entry of its
+/// `interrupts-extended` property is a context, whose index is the context
+/// number used to program the PLIC. See [`Node::plic`].
+pub struct Plic<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    pub(crate) node: FallibleNode<'a, P>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Plic<'a, P> {
+    /// The number of interrupt sources, from `riscv,ndev`.
+    #[track_caller]
+    pub fn ndev(&self) -> P::Output<u32> {
+        P::to_output(crate::tryblock!({
+            match self.node.properties()?.find("riscv,ndev")? {
+                Some(ndev) => Ok(ndev.as_value()?),
+                None => Err(FdtError::MissingRequiredProperty("riscv,ndev")),
+            }
+        }))
+    }
+
+    /// Returns an iterator over the contexts of the PLIC, which are usually
+    /// the machine and supervisor external interrupts of each hart.
+    #[track_caller]
+    pub fn contexts(&self) -> P::Output<HartContextsIter<'a, P>> {
+        P::to_output(HartContextsIter::new(self.node))
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for Plic<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for Plic<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> AsNode<'a, P> for Plic<'a, P> {
+    fn as_node(&self) -> Node<'a, P> {
+        self.node.alt()
+    }
+}
+
+/// [Devicetree RISC-V ACLINT
+/// binding](https://www.kernel.org/doc/Documentation/devicetree/bindings/timer/sifive%2Cclint.yaml)
+///
+/// A RISC-V CLINT, or one of the devices of an ACLINT, which provide the
+/// software and timer interrupts of the harts. Each entry of its
+/// `interrupts-extended` property connects it to a hart. See [`Node::clint`].
+pub struct Clint<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    pub(crate) node: FallibleNode<'a, P>,
+    pub(crate) kind: ClintKind,
+}
+
+impl<'a, P: ParserWithMode<'a>> Clint<'a, P> {
+    /// The kind of device, determined by its compatible string.
+    pub fn kind(&self) -> ClintKind {
+        self.kind
+    }
+
+    /// Returns an iterator over the hart contexts of the device. A CLINT has
+    /// both a software and a timer interrupt context for each hart, while each
+    /// ACLINT device has a single context for each hart.
+    #[track_caller]
+    pub fn contexts(&self) -> P::Output<HartContextsIter<'a, P>> {
+        P::to_output(HartContextsIter::new(self.node))
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for Clint<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for Clint<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> AsNode<'a, P> for Clint<'a, P> {
+    fn as_node(&self) -> Node<'a, P> {
+        self.node.alt()
+    }
+}
+
+/// The kind of a [`Clint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ClintKind {
+    /// A combined software and timer interrupt device, `riscv,clint0` or
+    /// `sifive,clint0`.
+    Clint,
+    /// An ACLINT machine-level software interrupt device, `riscv,aclint-mswi`.
+    MachineSoftware,
+    /// An ACLINT machine-level timer device, `riscv,aclint-mtimer`.
+    MachineTimer,
+    /// An ACLINT supervisor-level software interrupt device,
+    /// `riscv,aclint-sswi`.
+    SupervisorSoftware,
+}
+
+impl ClintKind {
+    pub(crate) fn from_compatible(compatible: Compatible<'_>) -> Option<Self> {
+        compatible.all().find_map(|c| match c {
+            "riscv,clint0" | "sifive,clint0" => Some(Self::Clint),
+            "riscv,aclint-mswi" => Some(Self::MachineSoftware),
+            "riscv,aclint-mtimer" => Some(Self::MachineTimer),
+            "riscv,aclint-sswi" => Some(Self::SupervisorSoftware),
+            _ => None,
+        })
+    }
+}
+
+/// [Devicetree RISC-V APLIC
+/// binding](https://www.kernel.org/doc/Documentation/devicetree/bindings/interrupt-controller/riscv%2Caplic.yaml)
+///
+/// A RISC-V advanced platform-level interrupt controller domain, which either
+/// delivers interrupts directly to the harts listed in `interrupts-extended`,
+/// or forwards them as MSIs to the IMSIC given by `msi-parent`. See
+/// [`Node::aplic`].
+pub struct Aplic<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    pub(crate) node: FallibleNode<'a, P>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Aplic<'a, P> {
+    /// The number of interrupt sources, from `riscv,num-sources`.
+    #[track_caller]
+    pub fn num_sources(&self) -> P::Output<u32> {
+        P::to_output(crate::tryblock!({
+            match self.node.properties()?.find("riscv,num-sources")? {
+                Some(sources) => Ok(sources.as_value()?),
+                None => Err(FdtError::MissingRequiredProperty("riscv,num-sources")),
+            }
+        }))
+    }
+
+    /// Returns an iterator over the hart contexts the domain delivers
+    /// interrupts to directly, which is empty if it forwards them as MSIs.
+    #[track_caller]
+    pub fn contexts(&self) -> P::Output<HartContextsIter<'a, P>> {
+        P::to_output(HartContextsIter::new(self.node))
+    }
+
+    /// The IMSIC the domain forwards interrupts to as MSIs, if any.
+    #[track_caller]
+    pub fn msi_parent(&self) -> P::Output<Option<MsiParent<'a, P>>> {
+        self.node.alt::<P>().property()
+    }
+
+    /// Returns an iterator over the ranges of interrupt sources delegated to
+    /// child domains, from `riscv,delegation`. This is only present on the
+    /// root domain.
+    #[track_caller]
+    pub fn delegations(&self) -> P::Output<AplicDelegationsIter<'a, P>> {
+        P::to_output(crate::tryblock!({
+            let encoded_array = match self.node.properties()?.find("riscv,delegation")? {
+                Some(delegation) if delegation.value.len() % 12 == 0 => delegation.value,
+                Some(_) => return Err(FdtError::InvalidPropertyValue),
+                None => &[],
+            };
+
+            Ok(AplicDelegationsIter { root: Root { node: self.node.make_root::<P::Parser>()?.node }, encoded_array })
+        }))
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for Aplic<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for Aplic<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> AsNode<'a, P> for Aplic<'a, P> {
+    fn as_node(&self) -> Node<'a, P> {
+        self.node.alt()
+    }
+}
+
+/// See [`Aplic::delegations`].
+pub struct AplicDelegationsIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    root: Root<'a, FallibleParser<'a, P>>,
+    encoded_array: &'a [u8],
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for AplicDelegationsIter<'a, P> {
+    type Item = P::Output<AplicDelegation<'a, P>>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (entry, rest) = self.encoded_array.split_first_chunk::<12>()?;
+        self.encoded_array = rest;
+
+        let [phandle, first, last] =
+            core::array::from_fn(|i| u32::from_be_bytes(entry[i * 4..i * 4 + 4].try_into().unwrap()));
+
+        Some(P::to_output(crate::tryblock!({
+            let root: FallibleRoot<'a, P> = Root { node: self.root.node };
+            match root.resolve_phandle(PHandle::new(phandle))? {
+                Some(child) => Ok(AplicDelegation { child: child.alt(), first, last }),
+                None => Err(FdtError::MissingPHandleNode(phandle)),
+            }
+        })))
+    }
+}
+
+/// A range of interrupt sources an [`Aplic`] delegates to a child domain.
+pub struct AplicDelegation<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    /// The child domain.
+    pub child: Node<'a, P>,
+    /// The first delegated interrupt source.
+    pub first: u32,
+    /// The last delegated interrupt source, inclusive.
+    pub last: u32,
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for AplicDelegation<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for AplicDelegation<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+/// [Devicetree RISC-V IMSIC
+/// binding](https://www.kernel.org/doc/Documentation/devicetree/bindings/interrupt-controller/riscv%2Cimsics.yaml)
+///
+/// The RISC-V incoming MSI controllers of a group of harts at one privilege
+/// level, with one interrupt file for each hart listed in its
+/// `interrupts-extended` property. See [`Node::imsic`].
+pub struct Imsic<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    pub(crate) node: FallibleNode<'a, P>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Imsic<'a, P> {
+    /// The number of interrupt identities of each interrupt file, from
+    /// `riscv,num-ids`.
+    #[track_caller]
+    pub fn num_ids(&self) -> P::Output<u32> {
+        P::to_output(crate::tryblock!({
+            self.u32_property("riscv,num-ids")?.ok_or(FdtError::MissingRequiredProperty("riscv,num-ids"))
+        }))
+    }
+
+    /// The number of interrupt identities of each guest interrupt file, from
+    /// `riscv,num-guest-ids`, which defaults to [`Imsic::num_ids`].
+    #[track_caller]
+    pub fn num_guest_ids(&self) -> P::Output<u32> {
+        P::to_output(crate::tryblock!({
+            match self.u32_property("riscv,num-guest-ids")? {
+                Some(ids) => Ok(ids),
+                None => self.u32_property("riscv,num-ids")?.ok_or(FdtError::MissingRequiredProperty("riscv,num-ids")),
+            }
+        }))
+    }
+
+    /// The number of guest index bits in the MSI target address, from
+    /// `riscv,guest-index-bits`, which defaults to zero.
+    #[track_caller]
+    pub fn guest_index_bits(&self) -> P::Output<u32> {
+        P::to_output(crate::tryblock!({ Ok(self.u32_property("riscv,guest-index-bits")?.unwrap_or(0)) }))
+    }
+
+    /// The number of hart index bits in the MSI target address, from
+    /// `riscv,hart-index-bits`. If this is `None`, it's the number of bits
+    /// needed to index the harts in [`Imsic::contexts`].
+    #[track_caller]
+    pub fn hart_index_bits(&self) -> P::Output<Option<u32>> {
+        P::to_output(self.u32_property("riscv,hart-index-bits"))
+    }
+
+    /// Returns an iterator over the harts with an interrupt file, in the order
+    /// of their hart index.
+    #[track_caller]
+    pub fn contexts(&self) -> P::Output<HartContextsIter<'a, P>> {
+        P::to_output(HartContextsIter::new(self.node))
+    }
+
+    fn u32_property(&self, name: &str) -> Result<Option<u32>, FdtError> {
+        match self.node.properties()?.find(name)? {
+            Some(value) => Ok(Some(value.as_value()?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for Imsic<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for Imsic<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> AsNode<'a, P> for Imsic<'a, P> {
+    fn as_node(&self) -> Node<'a, P> {
+        self.node.alt()
+    }
+}
+
+/// See [`Plic::contexts`], [`Clint::contexts`], [`Aplic::contexts`], and
+/// [`Imsic::contexts`].
+pub struct HartContextsIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    interrupts: Option<ExtendedInterruptsIter<'a, FallibleParser<'a, P>>>,
+    index: usize,
+}
+
+impl<'a, P: ParserWithMode<'a>> HartContextsIter<'a, P> {
+    fn new(node: FallibleNode<'a, P>) -> Result<Self, FdtError> {
+        let interrupts = node.property::<ExtendedInterrupts<FallibleParser<'a, P>>>()?;
+        Ok(Self { interrupts: interrupts.map(|i| i.iter()), index: 0 })
+    }
+
+    fn next_context(&mut self) -> Result<Option<HartContext<'a, P>>, FdtError> {
+        let Some(interrupts) = self.interrupts.as_mut() else { return Ok(None) };
+        let Some(interrupt) = interrupts.next().transpose()? else { return Ok(None) };
+
+        let intc = *interrupt.interrupt_parent();
+        let cause = interrupt.interrupt_specifier().single().ok_or(FdtError::InvalidPropertyValue)?;
+        match intc.property::<Compatible>()? {
+            Some(compatible) if compatible.compatible_with("riscv,cpu-intc") => {}
+            _ => return Err(FdtError::InvalidPropertyValue),
+        }
+
+        let cpu: Cpu<'a, FallibleParser<'a, P>> = Cpu { node: intc.full_parent()?.ok_or(FdtError::MissingParent)? };
+        let hart_id = cpu.reg::<u64>()?.first().map_err(|_| FdtError::CollectCellsError)?;
+
+        let index = self.index;
+        self.index += 1;
+
+        Ok(Some(HartContext {
+            index,
+            cpu: Cpu { node: cpu.node },
+            hart_id,
+            cause: (cause != u32::MAX).then_some(cause),
+        }))
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for HartContextsIter<'a, P> {
+    type Item = P::Output<HartContext<'a, P>>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = self.next_context();
+        if !matches!(res, Ok(Some(_))) {
+            self.interrupts = None;
+        }
+
+        // This is a manual impl of `map` because we need the panic location to
+        // be the caller if `P::to_output` panics
+        #[allow(clippy::manual_map)]
+        match res.transpose() {
+            Some(output) => Some(P::to_output(output)),
+            None => None,
+        }
+    }
+}
+
+/// A connection from a RISC-V interrupt controller to the local interrupt
+/// controller (`riscv,cpu-intc`) of a hart, from an entry of the controller's
+/// `interrupts-extended` property.
+pub struct HartContext<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    /// The position of the entry in `interrupts-extended`, which for a PLIC
+    /// is the context number, and for an IMSIC is the hart index.
+    pub index: usize,
+    /// The CPU node of the hart.
+    pub cpu: Cpu<'a, P>,
+    /// The hart ID, from the first `reg` entry of the CPU node.
+    pub hart_id: u64,
+    /// The local interrupt raised on the hart, such as `9` for supervisor
+    /// external interrupts, or `None` if the context is unused, which is
+    /// specified as `0xffffffff`.
+    pub cause: Option<u32>,
+}
+
+impl<'a, P: ParserWithMode<'a>> HartContext<'a, P> {
+    /// The privilege level of the local interrupt, if it's a standard
+    /// software, timer, or external interrupt.
+    pub fn privilege(&self) -> Option<RiscvPrivilege> {
+        match self.cause? {
+            1 | 5 | 9 => Some(RiscvPrivilege::Supervisor),
+            3 | 7 | 11 => Some(RiscvPrivilege::Machine),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for HartContext<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for HartContext<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+/// The privilege level of a [`HartContext`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RiscvPrivilege {
+    /// Supervisor mode.
+    Supervisor,
+    /// Machine mode.
+    Machine,
+}
+
+/// Whether `node` is compatible with any of `compatibles`.
+pub(crate) fn compatible_with_any<'a, P: ParserWithMode<'a>>(
+    node: FallibleNode<'a, P>,
+    compatibles: &[&str],
+) -> Result<bool, FdtError> {
+    match node.property::<Compatible>()? {
+        Some(compatible) => Ok(compatibles.iter().any(|c| compatible.compatible_with(c))),
+        None => Ok(false),
+    }
+}
@@ -2,7 +2,8 @@ use super::{
     aliases::Aliases,
     chosen::Chosen,
     cpus::Cpus,
-    memory::{Memory, ReservedMemory},
+    memory::{AllMemoryIter, Memory, ReservedMemory},
+    symbols::Symbols,
     AsNode, IntoSearchableNodeName, Node, RawNode, SearchableNodeName,
 };
 use crate::{
@@ -138,6 +139,19 @@ impl<'a, P: ParserWithMode<'a>> Root<'a, P> {
         }))
     }
 
+    /// The `/__symbols__` node present in devicetrees compiled with symbols
+    /// enabled (`dtc -@`), which maps labels to the paths of the labeled nodes.
+    #[track_caller]
+    pub fn symbols(&self) -> P::Output<Option<Symbols<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            let this: FallibleRoot<'a, P> = Root { node: self.node };
+            match this.find_node("/__symbols__")? {
+                Some(node) => Ok(Some(Symbols { node })),
+                None => Ok(None),
+            }
+        }))
+    }
+
     /// [Devicetree 3.6. `/chosen`
     /// Node](https://devicetree-specification.readthedocs.io/en/latest/chapter3-devicenodes.html#chosen-node)
     ///
@@ -196,6 +210,25 @@ impl<'a, P: ParserWithMode<'a>> Root<'a, P> {
         }))
     }
 
+    /// [Devicetree 3.4. `/memory`
+    /// node](https://devicetree-specification.readthedocs.io/en/latest/chapter3-devicenodes.html#memory-node)
+    ///
+    /// Returns an iterator over every memory node, identified by a
+    /// `device_type` of `"memory"`, in depth-first order. Unlike
+    /// [`Root::memory`], this includes every node when the memory ranges are
+    /// split across several nodes, such as `memory@80000000` and
+    /// `memory@880000000` or one node per NUMA node.
+    ///
+    /// Use [`AllMemoryIter::merged_regions`] to collect the `reg` entries of
+    /// all of the memory nodes at once, sorted and merged.
+    #[track_caller]
+    pub fn all_memory(&self) -> P::Output<AllMemoryIter<'a, P>> {
+        P::to_output(crate::tryblock!({
+            let this: FallibleRoot<'a, P> = Root { node: self.node };
+            Ok(AllMemoryIter { iter: this.all_nodes()? })
+        }))
+    }
+
     /// [Devicetree 3.5. `/reserved-memory`
     /// node](https://devicetree-specification.readthedocs.io/en/latest/chapter3-devicenodes.html#reserved-memory-node)
     ///
@@ -252,6 +285,20 @@ impl<'a, P: ParserWithMode<'a>> Root<'a, P> {
         }))
     }
 
+    /// Attempt to find the node with the given label (e.g. `uart0` for
+    /// `uart0: serial@10000000 { ... };`) using the `/__symbols__` node, which
+    /// is only present in devicetrees compiled with symbols enabled (`dtc -@`)
+    #[track_caller]
+    pub fn find_node_by_label(self, label: &str) -> P::Output<Option<Node<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            let this: FallibleRoot<'a, P> = Root { node: self.node };
+            match this.symbols()? {
+                Some(symbols) => symbols.resolve(label).map(|n| n.map(|n| n.alt())),
+                None => Ok(None),
+            }
+        }))
+    }
+
     /// Attempt to find a node with the given path (with an optional unit
     /// address, defaulting to the first matching name if omitted). If you only
     /// have the node name but not the path, use [`Root::find_node_by_name`] instead.
@@ -0,0 +1,89 @@
+use super::{AsNode, Node, NodePropertiesIter};
+use crate::{
+    helpers::{FallibleNode, FallibleParser},
+    parsing::{NoPanic, ParserWithMode},
+};
+
+/// `/__symbols__` node
+///
+/// Devicetrees compiled with symbols enabled (`dtc -@`) contain a
+/// `/__symbols__` node which maps each label in the source to the full path of
+/// the node it was attached to. Overlays use it to resolve references to
+/// labels in the base devicetree.
+///
+/// Each property of the `/__symbols__` node defines a symbol. The property
+/// name is the label, and the property value is the full path to the labeled
+/// node, encoded as a string.
+///
+/// ### Example
+///
+/// ```norust
+/// __symbols__ {
+///     uart0 = "/soc/serial@10000000";
+///     intc = "/soc/interrupt-controller@c000000";
+/// };
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Symbols<'a, P: ParserWithMode<'a>> {
+    pub(crate) node: FallibleNode<'a, P>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Symbols<'a, P> {
+    /// Attempt to resolve a label to the path of the labeled node.
+    pub fn resolve_path(self, label: &str) -> P::Output<Option<&'a str>> {
+        P::to_output(crate::tryblock!({
+            self.node.properties()?.find(label)?.map(|p| p.as_value().map_err(Into::into)).transpose()
+        }))
+    }
+
+    /// Attempt to resolve a label to the labeled node.
+    pub fn resolve(self, label: &str) -> P::Output<Option<Node<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            let Some(path) = Symbols::<(_, NoPanic)> { node: self.node }.resolve_path(label)? else {
+                return Ok(None);
+            };
+
+            self.node.make_root::<P::Parser>()?.find_node(path).map(|r| r.map(|n| n.alt()))
+        }))
+    }
+
+    /// Create an iterator over all of the symbols, along with the labeled node
+    /// if its path exists in the devicetree.
+    pub fn iter(&self) -> P::Output<AllSymbolsIter<'a, P>> {
+        P::to_output(crate::tryblock!({
+            Ok(AllSymbolsIter { node: self.node, properties: self.node.properties()?.iter() })
+        }))
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> AsNode<'a, P> for Symbols<'a, P> {
+    fn as_node(&self) -> Node<'a, P> {
+        self.node.alt()
+    }
+}
+
+#[allow(missing_docs)]
+pub struct AllSymbolsIter<'a, P: ParserWithMode<'a>> {
+    node: FallibleNode<'a, P>,
+    properties: NodePropertiesIter<'a, FallibleParser<'a, P>>,
+}
+
+impl<'a, P> Iterator for AllSymbolsIter<'a, P>
+where
+    P: ParserWithMode<'a>,
+{
+    type Item = P::Output<(&'a str, Option<Node<'a, P>>)>;
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(P::to_output(match self.properties.next() {
+            Some(Ok(prop)) => crate::tryblock!({
+                let path = prop.as_value::<&'a str>()?;
+                let node = self.node.make_root::<P::Parser>()?.find_node(path)?;
+
+                Ok((prop.name, node.map(|n| n.alt())))
+            }),
+            Some(Err(e)) => Err(e),
+            None => return None,
+        }))
+    }
+}
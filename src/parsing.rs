@@ -469,3 +469,11 @@ impl<'a> StringsBlock<'a> {
 #[derive(Debug, Clone, Copy)]
 #[repr(transparent)]
 pub struct StructsBlock<'a, G>(pub(crate) &'a [G]);
+
+/// Newtype wrapper around a byte buffer representing the memory reservation
+/// block of the flattened devicetree. The buffer extends from the start of the
+/// block to the end of the devicetree, as the block itself is only terminated
+/// by an all-zero entry.
+#[derive(Debug, Clone, Copy)]
+#[repr(transparent)]
+pub struct MemoryReservationBlock<'a>(pub(crate) &'a [u8]);
@@ -3,15 +3,38 @@
 // obtain one at https://mozilla.org/MPL/2.0/.
 
 use crate::{
-    cell_collector::CollectCellsError,
-    nodes::{root::Root, Node, NodeName, NodeProperty},
-    parsing::{NoPanic, Parser},
-    properties::values::{InvalidPropertyValue, U32List},
-    FdtError,
+    nodes::{memory::MemoryReservationsIter, root::Root, Node, NodeProperty},
+    parsing::{NoPanic, Parser, ParserWithMode},
+    properties::{values::InvalidPropertyValue, PHandle},
+    Fdt, FdtError,
 };
 
+/// Properties which contain a list of phandles.
+const PHANDLE_PROPERTIES: &[&str] =
+    &["interrupt-parent", "cpu", "remote-endpoint", "phy-handle", "memory-region", "next-level-cache"];
+
+/// Properties which contain a list of phandles each followed by a number of
+/// arguments, given by a `#*-cells` property on the referenced node.
+const PHANDLE_ARGS_PROPERTIES: &[(&str, &str)] = &[
+    ("clocks", "#clock-cells"),
+    ("assigned-clocks", "#clock-cells"),
+    ("assigned-clock-parents", "#clock-cells"),
+    ("interrupts-extended", "#interrupt-cells"),
+    ("resets", "#reset-cells"),
+    ("power-domains", "#power-domain-cells"),
+    ("phys", "#phy-cells"),
+    ("dmas", "#dma-cells"),
+    ("iommus", "#iommu-cells"),
+    ("mboxes", "#mbox-cells"),
+    ("pwms", "#pwm-cells"),
+    ("msi-parent", "#msi-cells"),
+    ("io-channels", "#io-channel-cells"),
+    ("sound-dai", "#sound-dai-cells"),
+    ("thermal-sensors", "#thermal-sensor-cells"),
+];
+
 #[derive(Debug)]
-pub struct Error;
+pub(crate) struct Error;
 
 impl From<FdtError> for Error {
     #[track_caller]
@@ -20,13 +43,6 @@ impl From<FdtError> for Error {
     }
 }
 
-impl From<CollectCellsError> for Error {
-    #[track_caller]
-    fn from(_: CollectCellsError) -> Self {
-        Error
-    }
-}
-
 impl From<InvalidPropertyValue> for Error {
     #[track_caller]
     fn from(_: InvalidPropertyValue) -> Self {
@@ -46,179 +62,295 @@ impl From<Error> for core::fmt::Error {
     }
 }
 
-pub fn print_fdt<'a, P: Parser<'a>>(
-    f: &mut core::fmt::Formatter<'_>,
-    root: Root<'a, (P, NoPanic)>,
-) -> core::fmt::Result {
-    let res = crate::tryblock!(Error, {
-        let mut any_children = true;
-        let mut any_props;
-        let mut node_iter = root.all_nodes()?.peekable();
-        let (mut n_braces, mut final_depth) = (0, 0);
-
-        writeln!(f, "/ {{")?;
-        any_props = print_properties(f, &root.node, 0)?;
-        while let Some((depth, node)) = node_iter.next().transpose()? {
-            let next_depth = match node_iter.peek().cloned().transpose()? {
-                Some((next_depth, _)) => next_depth,
-                None => 0,
-            };
-            let next_is_child = next_depth > depth;
-            any_children = true;
-
-            if n_braces > 0 {
-                for _ in (0..n_braces).rev() {
-                    final_depth -= 1;
-                    writeln!(f, "{:width$}}};", ' ', width = (final_depth) * 4)?;
-                }
-
-                n_braces = 0;
-            }
-
-            if any_props {
-                writeln!(f)?;
-            }
+/// Prints a devicetree as devicetree source in the same format as
+/// `dtc -I dtb -O dts`, returned by [`Fdt::display_dts`]. Property values are
+/// printed as strings, cells, or bytes based on their contents, the same way
+/// `dtc` guesses them.
+///
+/// Compiling the output gives back an equivalent devicetree.
+pub struct DisplayDts<'f, 'a, P: ParserWithMode<'a>> {
+    pub(crate) fdt: &'f Fdt<'a, P>,
+    pub(crate) phandle_labels: bool,
+}
 
-            writeln!(
-                f,
-                "{:width$}{} {{",
-                ' ',
-                if node.name()?.name.is_empty() { NodeName { name: "/", unit_address: None } } else { node.name()? },
-                width = depth * 4,
-            )?;
+impl<'a, P: ParserWithMode<'a>> DisplayDts<'_, 'a, P> {
+    /// Synthesize a label for every node with a `phandle` property, and print
+    /// references to them in well-known properties (e.g. `interrupt-parent`
+    /// or `clocks`) as `&label` instead of the phandle value. Labels are the
+    /// node name followed by the phandle, e.g. `interrupt_controller_2`.
+    pub fn phandle_labels(mut self, enabled: bool) -> Self {
+        self.phandle_labels = enabled;
+        self
+    }
+}
 
-            any_props = print_properties(f, &node, depth)?;
+impl<'a, P: ParserWithMode<'a>> core::fmt::Display for DisplayDts<'_, 'a, P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let fdt = self.fdt;
+        let mut parser: (P::Parser, NoPanic) = <_>::new(fdt.structs.0, fdt.strings, fdt.structs);
 
-            if !any_props && !next_is_child {
-                writeln!(f)?;
-            }
+        let Ok(node) = parser.parse_root() else {
+            return Err(core::fmt::Error);
+        };
 
-            if next_depth <= depth {
-                writeln!(f, "{:width$}}};", ' ', width = depth * 4)?;
+        let reservations = MemoryReservationsIter::<'a, (P::Parser, NoPanic)> {
+            block: fdt.memory_reservations.0,
+            finished: false,
+            _mode: core::marker::PhantomData,
+        };
 
-                if !any_props && !next_is_child {
-                    writeln!(f)?;
-                }
-            }
+        print_fdt(f, Root { node }, reservations, self.phandle_labels)
+    }
+}
 
-            if depth > next_depth {
-                n_braces += depth - next_depth;
-                final_depth = depth;
-            } else {
-                n_braces = 0;
-                final_depth = 0;
-            }
-        }
+pub(crate) fn print_fdt<'a, P: Parser<'a>>(
+    f: &mut core::fmt::Formatter<'_>,
+    root: Root<'a, (P, NoPanic)>,
+    reservations: MemoryReservationsIter<'a, (P, NoPanic)>,
+    phandle_labels: bool,
+) -> core::fmt::Result {
+    let res = crate::tryblock!(Error, {
+        writeln!(f, "/dts-v1/;\n")?;
 
-        if any_children {
-            writeln!(f, "    }};")?;
+        for reservation in reservations {
+            let reservation = reservation?;
+            writeln!(f, "/memreserve/\t{:#018x} {:#018x};", reservation.address, reservation.size)?;
         }
 
-        write!(f, "}};")?;
-
-        Ok(())
+        print_node(f, &root.node, phandle_labels.then_some(root), 0)
     });
 
     Ok(res?)
 }
 
+/// Print `node` and its children, where `root` is used to resolve phandle
+/// references when labels are enabled.
 pub(crate) fn print_node<'a, P: Parser<'a>>(
     f: &mut core::fmt::Formatter<'_>,
     node: &Node<'a, (P, NoPanic)>,
+    root: Option<Root<'a, (P, NoPanic)>>,
     depth: usize,
 ) -> Result<(), Error> {
-    writeln!(
-        f,
-        "{:width$}{} {{",
-        if depth == 0 { "" } else { " " },
-        if node.name()?.name.is_empty() { NodeName { name: "/", unit_address: None } } else { node.name()? },
-        width = depth * 4
-    )?;
-
-    let children = node.children()?.iter();
-    let n_children = children.clone().count();
-
-    if print_properties(f, node, depth)? && n_children > 0 {
-        writeln!(f)?;
+    indent(f, depth)?;
+    if root.is_some() {
+        if let Some(phandle) = node.property::<PHandle>()? {
+            write_label(f, node, phandle)?;
+            write!(f, ": ")?;
+        }
     }
 
-    for child in children {
-        print_node(f, &child?, depth + 1)?;
+    writeln!(f, "{} {{", node.name()?)?;
+
+    for property in node.properties()? {
+        let property = property?;
+        indent(f, depth + 1)?;
+        write!(f, "{}", property.name)?;
+
+        match property.value.len() {
+            0 => writeln!(f, ";")?,
+            _ => {
+                write!(f, " = ")?;
+                print_value(f, property, root)?;
+                writeln!(f, ";")?;
+            }
+        }
     }
 
-    writeln!(f, "{:width$}}};", if depth == 0 { "" } else { " " }, width = depth * 4)?;
+    for child in node.children()? {
+        writeln!(f)?;
+        print_node(f, &child?, root, depth + 1)?;
+    }
+
+    indent(f, depth)?;
+    writeln!(f, "}};")?;
 
     Ok(())
 }
 
-fn print_properties<'a, P: Parser<'a>>(
+fn indent(f: &mut core::fmt::Formatter<'_>, depth: usize) -> core::fmt::Result {
+    (0..depth).try_for_each(|_| write!(f, "\t"))
+}
+
+/// Labels are only allowed to contain alphanumerics and underscores, so
+/// replace anything else in the node name with an underscore.
+fn write_label<'a, P: Parser<'a>>(
     f: &mut core::fmt::Formatter<'_>,
     node: &Node<'a, (P, NoPanic)>,
-    depth: usize,
-) -> Result<bool, Error> {
-    let mut any_props = false;
-    for prop in node.properties()? {
-        any_props = true;
-        let prop = prop?;
-
-        match prop.name {
-            "reg" => {
-                write!(f, "{:width$}reg = <", ' ', width = depth * 4 + 4)?;
-                for (i, reg) in node.reg()?.unwrap().iter::<u64, Option<u64>>().enumerate() {
-                    let reg = reg?;
-                    if i > 0 {
-                        write!(f, " ")?;
-                    }
-
-                    match reg.len {
-                        Some(size) => write!(f, "{:#04x} {:#04x}", reg.address as usize, size)?,
-                        None => write!(f, "{:#04x}", reg.address as usize)?,
-                    }
-                }
-                writeln!(f, ">;")?;
+    phandle: PHandle,
+) -> Result<(), Error> {
+    for c in node.name()?.name.chars() {
+        match c.is_ascii_alphanumeric() {
+            true => write!(f, "{c}")?,
+            false => write!(f, "_")?,
+        }
+    }
+
+    write!(f, "_{}", phandle.as_u32())?;
+
+    Ok(())
+}
+
+fn print_value<'a, P: Parser<'a>>(
+    f: &mut core::fmt::Formatter<'_>,
+    property: NodeProperty<'a>,
+    root: Option<Root<'a, (P, NoPanic)>>,
+) -> Result<(), Error> {
+    if let Some(root) = root {
+        if let Some(cells) = phandle_cells_property(property.name) {
+            // Validate the whole value before printing anything so that values
+            // which don't match the expected layout can be printed as plain
+            // cells instead
+            if write_references(None, property.value, cells, root).is_ok() {
+                return write_references(Some(f), property.value, cells, root);
             }
-            "compatible" => {
-                writeln!(f, "{:width$}compatible = {:?};", ' ', prop.as_value::<&str>()?, width = depth * 4 + 4)?
+        }
+    }
+
+    let value = property.value;
+    let not_string = value.iter().filter(|&&c| !is_string_char(c)).count();
+    let nuls = value.iter().filter(|&&c| c == 0).count();
+
+    if value.last() == Some(&0) && not_string == 0 && nuls <= value.len() - nuls {
+        for (i, string) in value[..value.len() - 1].split(|&c| c == 0).enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
             }
-            name if name.contains("-cells") => {
-                writeln!(f, "{:width$}{} = <{:#04x}>;", ' ', name, prop.as_value::<u32>()?, width = depth * 4 + 4)?;
+
+            write_string(f, string)?;
+        }
+    } else if value.len().is_multiple_of(4) {
+        write!(f, "<")?;
+        for (i, cell) in value.chunks_exact(4).enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
             }
-            _ => match prop.as_value::<&str>() {
-                Ok("") => writeln!(f, "{:width$}{};", ' ', prop.name, width = depth * 4 + 4)?,
-                Ok(value) => {
-                    let len = value.len();
-                    let printable_character_count = value.chars().filter(|c| c.is_ascii_graphic()).count();
-
-                    match printable_character_count * 100 / len {
-                        0..75 => print_prop_raw_value(f, prop, depth)?,
-                        _ => writeln!(f, "{:width$}{} = {:?};", ' ', prop.name, value, width = depth * 4 + 4)?,
-                    }
-                }
-                _ => print_prop_raw_value(f, prop, depth)?,
-            },
+
+            write!(f, "{:#04x}", u32::from_be_bytes(cell.try_into().unwrap()))?;
+        }
+        write!(f, ">")?;
+    } else {
+        write!(f, "[")?;
+        for (i, byte) in value.iter().enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
+            }
+
+            write!(f, "{byte:02x}")?;
         }
+        write!(f, "]")?;
     }
 
-    Ok(any_props)
+    Ok(())
 }
 
-fn print_prop_raw_value(f: &mut core::fmt::Formatter<'_>, prop: NodeProperty<'_>, depth: usize) -> Result<(), Error> {
-    match prop.value.len() {
-        0 => writeln!(f, "{:width$}{};", ' ', prop.name, width = depth * 4 + 4)?,
-        _ => {
-            write!(f, "{:width$}{} = <", ' ', prop.name, width = depth * 4 + 4)?;
+/// Returns the name of the `#*-cells` property giving the number of arguments
+/// after each phandle in the property `name`, with `Some(None)` for lists of
+/// phandles without arguments.
+fn phandle_cells_property(name: &str) -> Option<Option<&'static str>> {
+    if PHANDLE_PROPERTIES.contains(&name) || is_pinctrl_property(name) {
+        return Some(None);
+    }
+
+    if name == "gpios" || (name.ends_with("-gpios") && name != "nr-gpios") || name.ends_with("-gpio") {
+        return Some(Some("#gpio-cells"));
+    }
+
+    PHANDLE_ARGS_PROPERTIES.iter().find(|(property, _)| *property == name).map(|(_, cells)| Some(*cells))
+}
 
-            for (i, n) in prop.as_value::<U32List>()?.iter().enumerate() {
-                if i != 0 {
-                    write!(f, " ")?;
-                }
+fn is_pinctrl_property(name: &str) -> bool {
+    name.strip_prefix("pinctrl-").is_some_and(|n| !n.is_empty() && n.bytes().all(|c| c.is_ascii_digit()))
+}
 
-                write!(f, "{n:#04x}")?;
+/// Write a `<...>` cell array with phandles printed as `&label`, or check that
+/// the value can be printed that way if `f` is `None`.
+fn write_references<'a, P: Parser<'a>>(
+    mut f: Option<&mut core::fmt::Formatter<'_>>,
+    value: &'a [u8],
+    cells: Option<&str>,
+    root: Root<'a, (P, NoPanic)>,
+) -> Result<(), Error> {
+    if !value.len().is_multiple_of(4) {
+        return Err(Error);
+    }
+
+    let mut values = value.chunks_exact(4).map(|cell| u32::from_be_bytes(cell.try_into().unwrap()));
+    let mut first = true;
+    let mut write_separator = |f: &mut Option<&mut core::fmt::Formatter<'_>>| -> Result<(), Error> {
+        if let Some(f) = f {
+            write!(f, "{}", if first { "<" } else { " " })?;
+        }
+
+        first = false;
+        Ok(())
+    };
+
+    while let Some(phandle) = values.next() {
+        write_separator(&mut f)?;
+
+        // Placeholder entries, which are commonly used in lists of GPIOs
+        if phandle == 0 || phandle == u32::MAX {
+            if let Some(f) = &mut f {
+                write!(f, "{phandle:#04x}")?;
             }
 
-            writeln!(f, ">;")?;
+            continue;
         }
+
+        let node = root.resolve_phandle(PHandle::new(phandle))?.ok_or(Error)?;
+        let arguments = match cells {
+            Some(cells) => match node.raw_property(cells)? {
+                Some(property) => property.as_value::<u32>()?,
+                None if cells == "#msi-cells" => 0,
+                None => return Err(Error),
+            },
+            None => 0,
+        };
+
+        if let Some(f) = &mut f {
+            write!(f, "&")?;
+            write_label(f, &node, PHandle::new(phandle))?;
+        }
+
+        for _ in 0..arguments {
+            let argument = values.next().ok_or(Error)?;
+            write_separator(&mut f)?;
+
+            if let Some(f) = &mut f {
+                write!(f, "{argument:#04x}")?;
+            }
+        }
+    }
+
+    if let Some(f) = f {
+        write!(f, ">")?;
     }
 
     Ok(())
 }
+
+fn is_string_char(c: u8) -> bool {
+    c.is_ascii_graphic() || b" \0\x07\x08\t\n\x0b\x0c\r".contains(&c)
+}
+
+fn write_string(f: &mut core::fmt::Formatter<'_>, string: &[u8]) -> core::fmt::Result {
+    write!(f, "\"")?;
+
+    for &c in string {
+        match c {
+            0x07 => write!(f, "\\a")?,
+            0x08 => write!(f, "\\b")?,
+            b'\t' => write!(f, "\\t")?,
+            b'\n' => write!(f, "\\n")?,
+            0x0b => write!(f, "\\v")?,
+            0x0c => write!(f, "\\f")?,
+            b'\r' => write!(f, "\\r")?,
+            b'\\' => write!(f, "\\\\")?,
+            b'"' => write!(f, "\\\"")?,
+            c if c.is_ascii_graphic() || c == b' ' => write!(f, "{}", c as char)?,
+            c => write!(f, "\\x{c:02x}")?,
+        }
+    }
+
+    write!(f, "\"")
+}
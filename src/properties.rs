@@ -2,16 +2,84 @@
 // v. 2.0. If a copy of the MPL was not distributed with this file, You can
 // obtain one at https://mozilla.org/MPL/2.0/.
 
+/// Define a newtype `$name` around the parsed property type `$inner`, such as
+/// [`phandle_args::PhandleArgsList`], implementing `Copy` and `Clone`. When
+/// given a parser, which takes a [`FallibleNode`] and returns
+/// `Result<Option<$inner>, FdtError>`, the newtype also dereferences to
+/// `$inner` and implements [`Property`] using it.
+macro_rules! newtype_property {
+    ($(#[$attr:meta])* pub struct $name:ident($inner:ident);) => {
+        $(#[$attr])*
+        pub struct $name<'a, P: $crate::parsing::ParserWithMode<'a> = (
+            $crate::parsing::aligned::AlignedParser<'a>,
+            $crate::parsing::Panic,
+        )>($inner<'a, P>);
+
+        impl<'a, P: $crate::parsing::ParserWithMode<'a>> Copy for $name<'a, P> {}
+        impl<'a, P: $crate::parsing::ParserWithMode<'a>> Clone for $name<'a, P> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+    };
+    ($(#[$attr:meta])* pub struct $name:ident($inner:ident) = |$node:ident| $parse:expr;) => {
+        newtype_property! { $(#[$attr])* pub struct $name($inner); }
+
+        impl<'a, P: $crate::parsing::ParserWithMode<'a>> core::ops::Deref for $name<'a, P> {
+            type Target = $inner<'a, P>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<'a, P: $crate::parsing::ParserWithMode<'a>> $crate::properties::Property<'a, P> for $name<'a, P> {
+            fn parse(
+                $node: $crate::helpers::FallibleNode<'a, P>,
+                _: $crate::helpers::FallibleRoot<'a, P>,
+            ) -> Result<Option<Self>, $crate::FdtError> {
+                Ok($parse?.map(Self))
+            }
+        }
+    };
+}
+
 /// Types for various `*-cells` properties.
 pub mod cells;
+/// Types for working with the clock binding properties.
+pub mod clocks;
+/// Types for decoding ARM GIC interrupt specifiers.
+pub mod gic;
+/// Types for working with the GPIO binding properties.
+pub mod gpio;
+/// Types for working with tables mapping IDs between nodes, such as
+/// `msi-map` and `iommu-map`.
+pub mod id_map;
 /// Types for working with interrupt properties.
 pub mod interrupts;
+/// Types for working with the IOMMU binding properties.
+pub mod iommu;
+/// Types for working with the MSI binding properties.
+pub mod msi;
+/// Mapping of specifiers through nexus nodes, such as with `interrupt-map` and
+/// `gpio-map`.
+pub(crate) mod nexus;
 /// Types and helpers for the devicetree PCI bindings.
 pub mod pci;
+/// Types for working with lists of phandles followed by argument cells.
+pub mod phandle_args;
+/// Types for working with the pin control binding properties.
+pub mod pinctrl;
+/// Types for working with the power domain binding properties.
+pub mod power_domains;
 /// Types for working with the `ranges` property.
 pub mod ranges;
 /// Type for working with the `reg` property.
 pub mod reg;
+/// Types for working with the regulator binding properties.
+pub mod regulators;
+/// Types for working with the reset binding properties.
+pub mod resets;
 /// Abstractions for various devicetree value types.
 pub mod values;
 
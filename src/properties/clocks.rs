@@ -0,0 +1,217 @@
+use super::{
+    phandle_args::{PhandleArgs, PhandleArgsIter, PhandleArgsList},
+    values::{StringList, U32List, U32ListIter},
+    Compatible, Property,
+};
+use crate::{
+    helpers::{FallibleNode, FallibleParser, FallibleRoot},
+    parsing::{aligned::AlignedParser, Panic, ParserWithMode},
+    FdtError,
+};
+
+/// Maximum number of `fixed-factor-clock` providers followed while computing a
+/// clock rate, which guards against cycles.
+const MAX_CLOCK_DEPTH: usize = 32;
+
+newtype_property! {
+    /// [Devicetree clock
+    /// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/clock/clock.yaml)
+    /// `clocks` property
+    ///
+    /// The input clocks of a device, as a list of phandles to clock providers
+    /// each followed by `#clock-cells` cells of clock specifier. The entries are
+    /// named by the `clock-names` property, if present.
+    ///
+    /// This dereferences to a [`PhandleArgsList`] for iterating over the clocks or
+    /// looking them up by name.
+    pub struct Clocks(PhandleArgsList) = |node| {
+        PhandleArgsList::parse(node, "clocks", "#clock-cells", Some("clock-names"))
+    };
+}
+
+/// [Devicetree clock
+/// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/clock/clock.yaml)
+/// `clock-names` property
+///
+/// The names of the input clocks of a device, in the same order as the
+/// `clocks` property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockNames<'a>(&'a str);
+
+impl<'a> ClockNames<'a> {
+    /// Returns an iterator over the clock names.
+    pub fn iter(self) -> StringList<'a> {
+        StringList::from(self.0)
+    }
+
+    /// Returns the index of the clock with the name `name`.
+    pub fn position(self, name: &str) -> Option<usize> {
+        self.iter().position(|n| n == name)
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Property<'a, P> for ClockNames<'a> {
+    fn parse(node: FallibleNode<'a, P>, _: FallibleRoot<'a, P>) -> Result<Option<Self>, FdtError> {
+        match node.properties()?.find("clock-names")? {
+            Some(names) => Ok(Some(Self(names.as_value()?))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// [Devicetree clock
+/// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/clock/clock.yaml)
+/// `assigned-clocks` property
+///
+/// Clocks which should be configured when the device is initialized, given by
+/// the `assigned-clocks` property, with the rates from `assigned-clock-rates`
+/// and the parents from `assigned-clock-parents`, which are matched to the
+/// clocks by index.
+///
+/// Example:
+///
+/// ```norust
+/// assigned-clocks = <&clkcon 0>, <&pll 2>;
+/// assigned-clock-parents = <&pll 2>;
+/// assigned-clock-rates = <0>, <460800>;
+/// ```
+pub struct AssignedClocks<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    clocks: PhandleArgsList<'a, P>,
+    parents: Option<PhandleArgsList<'a, P>>,
+    rates: Option<U32List<'a>>,
+}
+
+impl<'a, P: ParserWithMode<'a>> AssignedClocks<'a, P> {
+    /// Create an iterator over the assigned clocks.
+    pub fn iter(&self) -> AssignedClocksIter<'a, P> {
+        AssignedClocksIter {
+            clocks: self.clocks.fallible_iter(),
+            parents: self.parents.map(|p| p.fallible_iter()),
+            rates: self.rates.map(|r| r.iter()),
+        }
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for AssignedClocks<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for AssignedClocks<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Property<'a, P> for AssignedClocks<'a, P> {
+    fn parse(node: FallibleNode<'a, P>, _: FallibleRoot<'a, P>) -> Result<Option<Self>, FdtError> {
+        let Some(clocks) = PhandleArgsList::parse(node, "assigned-clocks", "#clock-cells", None)? else {
+            return Ok(None);
+        };
+
+        let parents = PhandleArgsList::parse(node, "assigned-clock-parents", "#clock-cells", None)?;
+        let rates = match node.properties()?.find("assigned-clock-rates")? {
+            Some(rates) => Some(rates.as_value::<U32List<'a>>()?),
+            None => None,
+        };
+
+        Ok(Some(Self { clocks, parents, rates }))
+    }
+}
+
+/// See [`AssignedClocks::iter`].
+pub struct AssignedClocksIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    clocks: PhandleArgsIter<'a, FallibleParser<'a, P>>,
+    parents: Option<PhandleArgsIter<'a, FallibleParser<'a, P>>>,
+    rates: Option<U32ListIter<'a>>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for AssignedClocksIter<'a, P> {
+    type Item = P::Output<AssignedClock<'a, P>>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        let clock = self.clocks.next()?;
+        let parent = self.parents.as_mut().and_then(Iterator::next);
+        let rate = self.rates.as_mut().and_then(Iterator::next).filter(|rate| *rate != 0);
+
+        Some(P::to_output(crate::tryblock!({
+            let parent = match parent.transpose()? {
+                Some(parent) if parent.provider.is_some() => Some(parent.alt()),
+                _ => None,
+            };
+
+            Ok(AssignedClock { clock: clock?.alt(), parent, rate })
+        })))
+    }
+}
+
+/// A single entry of an [`AssignedClocks`] property.
+pub struct AssignedClock<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    /// The clock to configure.
+    pub clock: PhandleArgs<'a, P>,
+    /// The parent to reparent the clock to, if any.
+    pub parent: Option<PhandleArgs<'a, P>>,
+    /// The rate in Hz to set the clock to, if any. A rate of zero in
+    /// `assigned-clock-rates` leaves the rate unchanged, and is given as
+    /// `None`.
+    pub rate: Option<u32>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for AssignedClock<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for AssignedClock<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+/// Compute the rate of the clock output by `provider` if it's a `fixed-clock`
+/// or a `fixed-factor-clock` whose parents are ultimately a `fixed-clock`.
+pub(crate) fn fixed_rate<'a, P: ParserWithMode<'a>>(
+    mut provider: FallibleNode<'a, P>,
+) -> Result<Option<u64>, FdtError> {
+    let (mut mult, mut div) = (1u128, 1u128);
+
+    for _ in 0..MAX_CLOCK_DEPTH {
+        let Some(compatible) = provider.property::<Compatible>()? else { return Ok(None) };
+        let properties = provider.properties()?;
+
+        if compatible.compatible_with("fixed-clock") {
+            let Some(frequency) = properties.find("clock-frequency")? else {
+                return Err(FdtError::MissingRequiredProperty("clock-frequency"));
+            };
+
+            let rate = u128::from(frequency.as_value::<u64>()?).checked_mul(mult).map(|rate| rate / div);
+            return Ok(rate.and_then(|rate| u64::try_from(rate).ok()));
+        }
+
+        if !compatible.compatible_with("fixed-factor-clock") {
+            return Ok(None);
+        }
+
+        let factor = |name| match properties.find(name)? {
+            Some(factor) => Ok(u128::from(factor.as_value::<u32>()?)),
+            None => Err(FdtError::MissingRequiredProperty(name)),
+        };
+
+        let (Some(new_mult), Some(new_div)) =
+            (mult.checked_mul(factor("clock-mult")?), div.checked_mul(factor("clock-div")?))
+        else {
+            return Ok(None);
+        };
+
+        if new_div == 0 {
+            return Err(FdtError::InvalidPropertyValue);
+        }
+
+        (mult, div) = (new_mult, new_div);
+
+        let parent = match PhandleArgsList::<P>::parse(provider, "clocks", "#clock-cells", None)? {
+            Some(clocks) => clocks.fallible_iter().next().transpose()?.and_then(|parent| parent.provider),
+            None => None,
+        };
+
+        match parent {
+            Some(parent) => provider = parent,
+            None => return Ok(None),
+        }
+    }
+
+    Ok(None)
+}
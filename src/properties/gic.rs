@@ -0,0 +1,144 @@
+use super::PHandle;
+
+/// [Devicetree ARM GIC
+/// binding](https://www.kernel.org/doc/Documentation/devicetree/bindings/interrupt-controller/arm%2Cgic.yaml)
+/// interrupt specifier
+///
+/// An interrupt specifier of a GICv2 or GICv3 interrupt controller, decoded
+/// from its `<type number flags>` cells, with an optional fourth cell on GICv3
+/// referencing a PPI partition.
+///
+/// Example:
+///
+/// ```norust
+/// interrupts = <0 29 4>, <1 13 0xf08>;
+/// ```
+///
+/// Where the first interrupt is level triggered SPI 29 (INTID 61), and the
+/// second is level triggered PPI 13 (INTID 29) routed to CPUs 0 to 3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GicInterrupt {
+    /// The kind of interrupt, from the first cell.
+    pub kind: GicInterruptKind,
+    /// The interrupt number within its kind, from the second cell.
+    pub number: u32,
+    /// The trigger type, from bits `[3:0]` of the third cell, or `None` if
+    /// they're zero and the trigger type is left to the controller.
+    pub trigger: Option<GicTrigger>,
+    /// For GICv2 PPIs, the mask of CPUs the interrupt is wired to, from bits
+    /// `[15:8]` of the third cell.
+    pub cpu_mask: Option<u8>,
+    /// For GICv3 PPIs, the PPI partition node the interrupt is limited to,
+    /// from the optional fourth cell.
+    pub ppi_partition: Option<PHandle>,
+}
+
+impl GicInterrupt {
+    /// Decode a GICv2 interrupt specifier, which must have three cells.
+    /// Returns `None` if the specifier is malformed.
+    pub fn decode_v2(cells: &[u32]) -> Option<Self> {
+        let &[kind, number, flags] = cells else { return None };
+        let kind = match kind {
+            0 => GicInterruptKind::Spi,
+            1 => GicInterruptKind::Ppi,
+            _ => return None,
+        };
+
+        Some(Self {
+            cpu_mask: (kind == GicInterruptKind::Ppi).then_some((flags >> 8) as u8),
+            ..Self::decode(kind, number, flags, None)?
+        })
+    }
+
+    /// Decode a GICv3 interrupt specifier, which has three cells, or four if
+    /// the controller supports PPI partitions. Returns `None` if the specifier
+    /// is malformed.
+    pub fn decode_v3(cells: &[u32]) -> Option<Self> {
+        let (&[kind, number, flags], partition) = match cells {
+            [kind, number, flags] => (&[*kind, *number, *flags], None),
+            [kind, number, flags, partition] => (&[*kind, *number, *flags], Some(*partition)),
+            _ => return None,
+        };
+
+        let kind = match kind {
+            0 => GicInterruptKind::Spi,
+            1 => GicInterruptKind::Ppi,
+            2 => GicInterruptKind::ExtendedSpi,
+            3 => GicInterruptKind::ExtendedPpi,
+            _ => return None,
+        };
+
+        Self::decode(kind, number, flags, partition.filter(|&p| p != 0).map(PHandle::new))
+    }
+
+    /// The interrupt ID the GIC uses for the interrupt, which is the interrupt
+    /// number offset by the first ID of its kind.
+    pub fn intid(&self) -> u32 {
+        self.number + self.kind.first_intid()
+    }
+
+    fn decode(kind: GicInterruptKind, number: u32, flags: u32, ppi_partition: Option<PHandle>) -> Option<Self> {
+        if number >= kind.count() {
+            return None;
+        }
+
+        let trigger = match flags & 0xf {
+            0 => None,
+            1 => Some(GicTrigger::EdgeRising),
+            2 => Some(GicTrigger::EdgeFalling),
+            4 => Some(GicTrigger::LevelHigh),
+            8 => Some(GicTrigger::LevelLow),
+            _ => return None,
+        };
+
+        Some(Self { kind, number, trigger, cpu_mask: None, ppi_partition })
+    }
+}
+
+/// The kind of a [`GicInterrupt`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GicInterruptKind {
+    /// Shared peripheral interrupt, routable to any CPU.
+    Spi,
+    /// Private peripheral interrupt, local to each CPU.
+    Ppi,
+    /// GICv3.1 extended shared peripheral interrupt.
+    ExtendedSpi,
+    /// GICv3.1 extended private peripheral interrupt.
+    ExtendedPpi,
+}
+
+impl GicInterruptKind {
+    /// The interrupt ID of interrupt number `0` of this kind.
+    pub fn first_intid(self) -> u32 {
+        match self {
+            Self::Spi => 32,
+            Self::Ppi => 16,
+            Self::ExtendedSpi => 4096,
+            Self::ExtendedPpi => 1056,
+        }
+    }
+
+    /// The number of interrupt IDs reserved for this kind.
+    pub fn count(self) -> u32 {
+        match self {
+            Self::Spi => 988,
+            Self::Ppi => 16,
+            Self::ExtendedSpi => 1024,
+            Self::ExtendedPpi => 64,
+        }
+    }
+}
+
+/// The trigger type of a [`GicInterrupt`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GicTrigger {
+    /// Triggered on a low-to-high edge.
+    EdgeRising,
+    /// Triggered on a high-to-low edge.
+    EdgeFalling,
+    /// Active while the line is high.
+    LevelHigh,
+    /// Active while the line is low.
+    LevelLow,
+}
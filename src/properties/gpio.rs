@@ -0,0 +1,366 @@
+use super::{
+    nexus::{self, Nexus, MAX_NEXUS_DEPTH},
+    phandle_args::{PhandleArgs, PhandleArgsIter, PhandleArgsList, MAX_PHANDLE_ARGS},
+    values::StringList,
+    PHandle, Property,
+};
+use crate::{
+    cell_collector::CellArray,
+    helpers::{FallibleNode, FallibleParser, FallibleRoot},
+    nodes::{root::Root, Node},
+    parsing::{aligned::AlignedParser, Panic, ParserWithMode},
+    FdtError,
+};
+
+pub(crate) const GPIO_NEXUS: Nexus = Nexus {
+    cells: "#gpio-cells",
+    map: "gpio-map",
+    mask: "gpio-map-mask",
+    pass_thru: Some("gpio-map-pass-thru"),
+    unit_address: false,
+};
+
+newtype_property! {
+    /// [Devicetree GPIO
+    /// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/gpio/gpio.yaml)
+    /// `*-gpios` property
+    ///
+    /// A list of GPIOs used by a device for a single function, such as
+    /// `reset-gpios` or `cd-gpios`, as phandles to GPIO controllers each followed
+    /// by `#gpio-cells` cells of GPIO specifier. See [`Node::gpios`].
+    ///
+    /// Entries which refer to a nexus node are resolved through its `gpio-map`,
+    /// with `gpio-map-mask` and `gpio-map-pass-thru` applied, so the returned
+    /// [`Gpio`]s always refer to the GPIO controller itself.
+    ///
+    /// Example:
+    ///
+    /// ```norust
+    /// reset-gpios = <&gpio0 12 GPIO_ACTIVE_LOW>;
+    /// ```
+    ///
+    /// [`Node::gpios`]: crate::nodes::Node::gpios
+    pub struct Gpios(PhandleArgsList);
+}
+
+impl<'a, P: ParserWithMode<'a>> Gpios<'a, P> {
+    /// Create an iterator over the GPIOs. Empty entries, whose phandle is
+    /// zero, are given as `None`.
+    pub fn iter(&self) -> GpiosIter<'a, P> {
+        GpiosIter { entries: Some(self.0.fallible_iter()) }
+    }
+
+    /// Returns the GPIO at `index`, or `None` if there are fewer entries or
+    /// the entry is empty.
+    #[track_caller]
+    pub fn get(&self, index: usize) -> P::Output<Option<Gpio<'a, P>>> {
+        P::to_output(match self.0.fallible_iter().nth(index) {
+            Some(entry) => resolve::<P>(entry),
+            None => Ok(None),
+        })
+    }
+
+    /// Parse the property `property` of `node` as a list of GPIOs.
+    pub(crate) fn parse(node: FallibleNode<'a, P>, property: &str) -> Result<Option<Self>, FdtError> {
+        Ok(PhandleArgsList::parse(node, property, "#gpio-cells", None)?.map(Self))
+    }
+}
+
+fn resolve<'a, P: ParserWithMode<'a>>(
+    entry: Result<PhandleArgs<'a, FallibleParser<'a, P>>, FdtError>,
+) -> Result<Option<Gpio<'a, P>>, FdtError> {
+    let entry = entry?;
+    let Some(mut controller) = entry.provider else { return Ok(None) };
+    let mut specifier = entry.args;
+
+    for _ in 0..MAX_NEXUS_DEPTH {
+        let Some(map) = controller.properties()?.find(GPIO_NEXUS.map)? else {
+            return Ok(Some(Gpio { controller: controller.alt(), specifier }));
+        };
+
+        let key = CellArray::new(&specifier).ok_or(FdtError::CollectCellsError)?;
+        let Some((parent, mapped)) = nexus::map_specifier::<P>(controller, &GPIO_NEXUS, map.value, key)? else {
+            return Err(FdtError::UnresolvedSpecifier);
+        };
+
+        controller = parent;
+        specifier = CellArray::new(&mapped).ok_or(FdtError::CollectCellsError)?;
+    }
+
+    Err(FdtError::UnresolvedSpecifier)
+}
+
+/// See [`Gpios::iter`].
+pub struct GpiosIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    entries: Option<PhandleArgsIter<'a, FallibleParser<'a, P>>>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for GpiosIter<'a, P> {
+    type Item = P::Output<Option<Gpio<'a, P>>>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = resolve::<P>(self.entries.as_mut()?.next()?);
+        if res.is_err() {
+            self.entries = None;
+        }
+
+        Some(P::to_output(res))
+    }
+}
+
+/// A single GPIO of a [`Gpios`] property, resolved to its GPIO controller.
+pub struct Gpio<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    /// The GPIO controller node.
+    pub controller: Node<'a, P>,
+    /// The GPIO specifier in the domain of the controller, of which there are
+    /// as many cells as its `#gpio-cells` property specifies.
+    pub specifier: CellArray<MAX_PHANDLE_ARGS>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Gpio<'a, P> {
+    /// The GPIO line number, which is the first cell of the specifier for
+    /// controllers using the standard two cell format.
+    pub fn pin(&self) -> Option<u32> {
+        self.specifier.first().copied()
+    }
+
+    /// The flags of the GPIO, which are the last cell of the specifier if it
+    /// has at least two cells.
+    pub fn flags(&self) -> Option<GpioFlags> {
+        match *self.specifier {
+            [_, .., flags] => Some(GpioFlags(flags)),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for Gpio<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for Gpio<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> core::fmt::Debug for Gpio<'a, P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Gpio")
+            .field("controller", &self.controller.fallible().name())
+            .field("specifier", &self.specifier)
+            .finish()
+    }
+}
+
+/// The flags cell of a GPIO specifier, as defined by
+/// `include/dt-bindings/gpio/gpio.h`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct GpioFlags(pub u32);
+
+impl GpioFlags {
+    /// `GPIO_ACTIVE_LOW`
+    pub const ACTIVE_LOW: u32 = 1 << 0;
+    /// `GPIO_SINGLE_ENDED`
+    pub const SINGLE_ENDED: u32 = 1 << 1;
+    /// `GPIO_LINE_OPEN_DRAIN`, which is combined with `GPIO_SINGLE_ENDED`.
+    pub const LINE_OPEN_DRAIN: u32 = 1 << 2;
+    /// `GPIO_TRANSITORY`
+    pub const TRANSITORY: u32 = 1 << 3;
+    /// `GPIO_PULL_UP`
+    pub const PULL_UP: u32 = 1 << 4;
+    /// `GPIO_PULL_DOWN`
+    pub const PULL_DOWN: u32 = 1 << 5;
+    /// `GPIO_PULL_DISABLE`
+    pub const PULL_DISABLE: u32 = 1 << 6;
+
+    /// The line is asserted when driven low.
+    #[inline(always)]
+    pub fn active_low(self) -> bool {
+        self.0 & Self::ACTIVE_LOW != 0
+    }
+
+    /// The line is an open drain output, which is only driven low.
+    #[inline(always)]
+    pub fn open_drain(self) -> bool {
+        self.0 & (Self::SINGLE_ENDED | Self::LINE_OPEN_DRAIN) == Self::SINGLE_ENDED | Self::LINE_OPEN_DRAIN
+    }
+
+    /// The line is an open source output, which is only driven high.
+    #[inline(always)]
+    pub fn open_source(self) -> bool {
+        self.0 & (Self::SINGLE_ENDED | Self::LINE_OPEN_DRAIN) == Self::SINGLE_ENDED
+    }
+
+    /// The line state doesn't need to be maintained across suspend or reset.
+    #[inline(always)]
+    pub fn transitory(self) -> bool {
+        self.0 & Self::TRANSITORY != 0
+    }
+
+    /// The line should have its pull-up bias enabled.
+    #[inline(always)]
+    pub fn pull_up(self) -> bool {
+        self.0 & Self::PULL_UP != 0
+    }
+
+    /// The line should have its pull-down bias enabled.
+    #[inline(always)]
+    pub fn pull_down(self) -> bool {
+        self.0 & Self::PULL_DOWN != 0
+    }
+
+    /// The line should have its bias disabled.
+    #[inline(always)]
+    pub fn pull_disable(self) -> bool {
+        self.0 & Self::PULL_DISABLE != 0
+    }
+}
+
+/// [Devicetree GPIO
+/// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/gpio/gpio.yaml)
+/// `#gpio-cells` property
+///
+/// The number of cells in a GPIO specifier for the GPIO controller or nexus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GpioCells(pub usize);
+
+impl<'a, P: ParserWithMode<'a>> Property<'a, P> for GpioCells {
+    fn parse(node: FallibleNode<'a, P>, _: FallibleRoot<'a, P>) -> Result<Option<Self>, FdtError> {
+        match node.properties()?.find("#gpio-cells")? {
+            Some(cells) => Ok(Some(Self(cells.as_value()?))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// [Devicetree GPIO
+/// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/gpio/gpio.yaml)
+/// `ngpios` property
+///
+/// The number of GPIO lines usable on the controller, when fewer than the
+/// hardware supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ngpios(pub u32);
+
+impl<'a, P: ParserWithMode<'a>> Property<'a, P> for Ngpios {
+    fn parse(node: FallibleNode<'a, P>, _: FallibleRoot<'a, P>) -> Result<Option<Self>, FdtError> {
+        match node.properties()?.find("ngpios")? {
+            Some(ngpios) => Ok(Some(Self(ngpios.as_value()?))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// [Devicetree GPIO
+/// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/gpio/gpio.yaml)
+/// `gpio-line-names` property
+///
+/// The names of the GPIO lines of the controller, in order. Unused lines have
+/// an empty name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpioLineNames<'a>(&'a str);
+
+impl<'a> GpioLineNames<'a> {
+    /// Returns an iterator over the line names.
+    pub fn iter(self) -> StringList<'a> {
+        StringList::from(self.0)
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Property<'a, P> for GpioLineNames<'a> {
+    fn parse(node: FallibleNode<'a, P>, _: FallibleRoot<'a, P>) -> Result<Option<Self>, FdtError> {
+        match node.properties()?.find("gpio-line-names")? {
+            Some(names) => Ok(Some(Self(names.as_value()?))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// [Devicetree GPIO
+/// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/gpio/gpio.yaml)
+/// `gpio-ranges` property
+///
+/// Ranges of GPIO lines of the controller which are routed to pins of a pin
+/// controller, as a phandle to the pin controller followed by the first GPIO
+/// line, the first pin, and the number of lines in each range.
+///
+/// Example:
+///
+/// ```norust
+/// gpio-ranges = <&pinctrl 0 20 10>, <&pinctrl 10 50 20>;
+/// ```
+pub struct GpioRanges<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    root: Root<'a, P>,
+    encoded_array: &'a [u8],
+}
+
+impl<'a, P: ParserWithMode<'a>> GpioRanges<'a, P> {
+    /// Create an iterator over the GPIO ranges.
+    pub fn iter(&self) -> GpioRangesIter<'a, P> {
+        GpioRangesIter { root: self.root, encoded_array: self.encoded_array }
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for GpioRanges<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for GpioRanges<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Property<'a, P> for GpioRanges<'a, P> {
+    fn parse(node: FallibleNode<'a, P>, root: FallibleRoot<'a, P>) -> Result<Option<Self>, FdtError> {
+        match node.properties()?.find("gpio-ranges")? {
+            Some(ranges) if ranges.value.len() % 16 == 0 => {
+                Ok(Some(Self { root: Root { node: root.node.alt() }, encoded_array: ranges.value }))
+            }
+            Some(_) => Err(FdtError::InvalidPropertyValue),
+            None => Ok(None),
+        }
+    }
+}
+
+/// See [`GpioRanges::iter`].
+pub struct GpioRangesIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    root: Root<'a, P>,
+    encoded_array: &'a [u8],
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for GpioRangesIter<'a, P> {
+    type Item = P::Output<GpioRange<'a, P>>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (entry, rest) = self.encoded_array.split_first_chunk::<16>()?;
+        self.encoded_array = rest;
+
+        let [phandle, gpio_offset, pin_offset, count] =
+            core::array::from_fn(|i| u32::from_be_bytes(entry[i * 4..i * 4 + 4].try_into().unwrap()));
+
+        Some(P::to_output(crate::tryblock!({
+            let root: FallibleRoot<'a, P> = Root { node: self.root.node.fallible() };
+            match root.resolve_phandle(PHandle::new(phandle))? {
+                Some(pinctrl) => Ok(GpioRange { pinctrl: pinctrl.alt(), gpio_offset, pin_offset, count }),
+                None => Err(FdtError::MissingPHandleNode(phandle)),
+            }
+        })))
+    }
+}
+
+/// A single entry of a [`GpioRanges`] property.
+pub struct GpioRange<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    /// The pin controller node.
+    pub pinctrl: Node<'a, P>,
+    /// The first GPIO line of the range.
+    pub gpio_offset: u32,
+    /// The first pin of the pin controller in the range.
+    pub pin_offset: u32,
+    /// The number of GPIO lines in the range.
+    pub count: u32,
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for GpioRange<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for GpioRange<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
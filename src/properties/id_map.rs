@@ -0,0 +1,160 @@
+use super::PHandle;
+use crate::{
+    helpers::{FallibleNode, FallibleParser, FallibleRoot},
+    nodes::{root::Root, Node},
+    parsing::{aligned::AlignedParser, Panic, ParserWithMode},
+    FdtError,
+};
+
+/// A table mapping ranges of input IDs to output IDs of another node, such as
+/// the `msi-map` and `iommu-map` properties which map PCI requester IDs to MSI
+/// device IDs and IOMMU stream IDs. Each entry is a tuple of
+/// `(id-base, phandle, output-base, length)`, and input IDs are `AND`ed with
+/// the matching `*-map-mask` property, if any, before being looked up.
+///
+/// Example:
+///
+/// ```norust
+/// iommu-map = <0x0 &smmu 0x0 0x100>, <0x100 &smmu 0x800 0x100>;
+/// iommu-map-mask = <0xffff>;
+/// ```
+pub struct IdMap<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    root: Root<'a, P>,
+    encoded_array: &'a [u8],
+    mask: u32,
+}
+
+impl<'a, P: ParserWithMode<'a>> IdMap<'a, P> {
+    /// The mask applied to input IDs, which is all ones if there is no
+    /// `*-map-mask` property.
+    pub fn mask(&self) -> u32 {
+        self.mask
+    }
+
+    /// Create an iterator over the entries of the map.
+    pub fn iter(&self) -> IdMapIter<'a, P> {
+        IdMapIter { root: self.root, encoded_array: self.encoded_array }
+    }
+
+    /// Translate the input ID `id` through the first entry which covers it
+    /// after applying the mask, returning `None` if there is none.
+    #[track_caller]
+    pub fn map(&self, id: u32) -> P::Output<Option<MappedId<'a, P>>> {
+        P::to_output(self.fallible_map(id))
+    }
+
+    /// Parse the map property `map` of `node` along with the mask property
+    /// `mask`.
+    pub(crate) fn parse(node: FallibleNode<'a, P>, map: &str, mask: &str) -> Result<Option<Self>, FdtError> {
+        let properties = node.properties()?;
+        let Some(map) = properties.find(map)? else { return Ok(None) };
+        if map.value.len() % 16 != 0 {
+            return Err(FdtError::InvalidPropertyValue);
+        }
+
+        let mask = match properties.find(mask)? {
+            Some(mask) => mask.as_value::<u32>()?,
+            None => u32::MAX,
+        };
+
+        Ok(Some(Self {
+            root: Root { node: node.make_root::<P::Parser>()?.node.alt() },
+            encoded_array: map.value,
+            mask,
+        }))
+    }
+
+    pub(crate) fn fallible_map(&self, id: u32) -> Result<Option<MappedId<'a, P>>, FdtError> {
+        let id = id & self.mask;
+        let entries = IdMapIter::<FallibleParser<'a, P>> {
+            root: Root { node: self.root.node.fallible() },
+            encoded_array: self.encoded_array,
+        };
+
+        for entry in entries {
+            let entry = entry?;
+            if let Some(output) = entry.map(id) {
+                return Ok(Some(MappedId { target: entry.target.alt(), id: output }));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for IdMap<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for IdMap<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+/// See [`IdMap::iter`].
+pub struct IdMapIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    root: Root<'a, P>,
+    encoded_array: &'a [u8],
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for IdMapIter<'a, P> {
+    type Item = P::Output<IdMapEntry<'a, P>>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (entry, rest) = self.encoded_array.split_first_chunk::<16>()?;
+        self.encoded_array = rest;
+
+        let [id_base, phandle, output_base, length] =
+            core::array::from_fn(|i| u32::from_be_bytes(entry[i * 4..i * 4 + 4].try_into().unwrap()));
+
+        Some(P::to_output(crate::tryblock!({
+            let root: FallibleRoot<'a, P> = Root { node: self.root.node.fallible() };
+            match root.resolve_phandle(PHandle::new(phandle))? {
+                Some(target) => Ok(IdMapEntry { id_base, target: target.alt(), output_base, length }),
+                None => Err(FdtError::MissingPHandleNode(phandle)),
+            }
+        })))
+    }
+}
+
+/// A single entry of an [`IdMap`].
+pub struct IdMapEntry<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    /// The first input ID covered by the entry.
+    pub id_base: u32,
+    /// The node receiving the output IDs, such as an MSI controller or IOMMU.
+    pub target: Node<'a, P>,
+    /// The output ID of `id_base`.
+    pub output_base: u32,
+    /// The number of IDs covered by the entry.
+    pub length: u32,
+}
+
+impl<'a, P: ParserWithMode<'a>> IdMapEntry<'a, P> {
+    /// Translate the already masked input ID `id`, returning `None` if the
+    /// entry doesn't cover it.
+    pub fn map(&self, id: u32) -> Option<u32> {
+        let offset = id.checked_sub(self.id_base)?;
+        (offset < self.length).then(|| self.output_base.wrapping_add(offset))
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for IdMapEntry<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for IdMapEntry<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+/// An input ID translated by an [`IdMap`].
+pub struct MappedId<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    /// The node receiving the output ID, such as an MSI controller or IOMMU.
+    pub target: Node<'a, P>,
+    /// The translated ID, such as an MSI device ID or IOMMU stream ID.
+    pub id: u32,
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for MappedId<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for MappedId<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
@@ -1,7 +1,12 @@
-use super::{cells::AddressCells, PHandle, Property};
+use super::{
+    cells::AddressCells,
+    gic::GicInterrupt,
+    nexus::{self, Nexus, NexusMapEntry, NexusMapIter, MAX_NEXUS_CELLS},
+    PHandle, Property,
+};
 use crate::{
-    cell_collector::{BuildCellCollector, CellCollector, CollectCellsError},
-    helpers::{FallibleNode, FallibleRoot},
+    cell_collector::{BuildCellCollector, CellArray, CellCollector, CollectCellsError},
+    helpers::{FallibleNode, FallibleParser, FallibleRoot},
     nodes::{root::Root, Node},
     parsing::{aligned::AlignedParser, BigEndianU32, NoPanic, Panic, ParserWithMode},
     FdtError,
@@ -217,6 +222,13 @@ pub struct ExtendedInterrupt<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Pan
     encoded_array: &'a [u8],
 }
 
+impl<'a, P: ParserWithMode<'a>> Copy for ExtendedInterrupt<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for ExtendedInterrupt<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
 #[allow(missing_docs)]
 impl<'a, P: ParserWithMode<'a>> ExtendedInterrupt<'a, P> {
     pub fn interrupt_parent(self) -> InterruptParent<'a, P> {
@@ -275,6 +287,19 @@ impl<'a> InterruptSpecifier<'a> {
         let mut iter = self.into_iter();
         Some((iter.next()?, iter.next()?))
     }
+
+    /// Decode the interrupt specifier as a GICv2 specifier, if it's valid for
+    /// an `arm,gic-400` or similar interrupt parent. See
+    /// [`GicInterrupt::decode_v2`].
+    pub fn gic_v2(self) -> Option<GicInterrupt> {
+        GicInterrupt::decode_v2(self.collect_to::<CellArray<4>>().ok()?.as_slice())
+    }
+
+    /// Decode the interrupt specifier as a GICv3 specifier, if it's valid for
+    /// an `arm,gic-v3` interrupt parent. See [`GicInterrupt::decode_v3`].
+    pub fn gic_v3(self) -> Option<GicInterrupt> {
+        GicInterrupt::decode_v3(self.collect_to::<CellArray<4>>().ok()?.as_slice())
+    }
 }
 
 impl<'a> IntoIterator for InterruptSpecifier<'a> {
@@ -409,39 +434,15 @@ impl<'a, AddrMask: CellCollector, IntMask: CellCollector, P: ParserWithMode<'a>>
     for InterruptMapMask<AddrMask, IntMask>
 {
     fn parse(node: FallibleNode<'a, P>, _: FallibleRoot<'a, P>) -> Result<Option<Self>, FdtError> {
-        let address_cells =
-            node.property::<AddressCells>()?.ok_or(FdtError::MissingRequiredProperty("#address-cells"))?;
-        let interrupt_cells =
-            node.property::<InterruptCells>()?.ok_or(FdtError::MissingRequiredProperty("#interrupt-cells"))?;
-        match node.properties()?.find("interrupt-map-mask")? {
-            Some(prop) => {
-                if prop.value.len() % 4 != 0 {
-                    return Err(FdtError::InvalidPropertyValue);
-                }
-
-                let mut address_collector = AddrMask::Builder::default();
-                let mut specifier_collector = IntMask::Builder::default();
-                let mut cells = prop.value.chunks_exact(4);
-
-                // TODO: replace this stuff with `array_chunks` when its stabilized
-                //
-                // These unwraps can't panic because `chunks_exact` guarantees that
-                // we'll always get slices of 4 bytes
-                for chunk in cells.by_ref().take(address_cells.0) {
-                    address_collector.push(u32::from_be_bytes(chunk.try_into().unwrap()))?;
-                }
-
-                for chunk in cells.take(interrupt_cells.0) {
-                    specifier_collector.push(u32::from_be_bytes(chunk.try_into().unwrap()))?;
-                }
-
-                Ok(Some(Self {
-                    address_mask: AddrMask::map(address_collector.finish()),
-                    interrupt_specifier_mask: IntMask::map(specifier_collector.finish()),
-                }))
-            }
-            None => Ok(None),
-        }
+        let domain_cells = nexus::domain_cells::<P>(node, &INTERRUPT_NEXUS)?;
+        let Some(mask) = nexus::map_mask::<P>(node, INTERRUPT_NEXUS.mask, domain_cells)? else { return Ok(None) };
+        let (address_mask, interrupt_specifier_mask) = mask.split_at(domain_cells.0 * 4);
+
+        Ok(Some(Self {
+            address_mask: InterruptSpecifier { encoded_array: address_mask }.collect_to::<AddrMask>()?,
+            interrupt_specifier_mask: InterruptSpecifier { encoded_array: interrupt_specifier_mask }
+                .collect_to::<IntMask>()?,
+        }))
     }
 }
 
@@ -499,8 +500,7 @@ pub struct InterruptMap<
     ParentInterruptSpecifier: CellCollector = u32,
     P: ParserWithMode<'a> = (AlignedParser<'a>, Panic),
 > {
-    address_cells: AddressCells,
-    interrupt_cells: InterruptCells,
+    domain_cells: (usize, usize),
     node: FallibleNode<'a, P>,
     encoded_map: &'a [u8],
     _collectors: core::marker::PhantomData<*mut (
@@ -522,13 +522,7 @@ impl<
 {
     /// Create an iterator over each individual [`InterruptMapEntry`].
     pub fn iter(&self) -> InterruptMapIter<'a, CAddr, CInt, PAddr, PInt, P> {
-        InterruptMapIter {
-            address_cells: self.address_cells,
-            interrupt_cells: self.interrupt_cells,
-            node: self.node,
-            encoded_map: self.encoded_map,
-            _collectors: core::marker::PhantomData,
-        }
+        InterruptMapIter { entries: self.entries(), _collectors: core::marker::PhantomData }
     }
 
     /// Create an iterator over each individual [`InterruptMapEntry`] which uses the provided mask to modify the .
@@ -542,14 +536,7 @@ impl<
         <CInt as CellCollector>::Output:
             Clone + core::ops::BitAnd<<CInt as CellCollector>::Output, Output = <CInt as CellCollector>::Output>,
     {
-        MaskedInterruptMapIter {
-            address_cells: self.address_cells,
-            interrupt_cells: self.interrupt_cells,
-            node: self.node,
-            encoded_map: self.encoded_map,
-            mask,
-            _collectors: core::marker::PhantomData,
-        }
+        MaskedInterruptMapIter { entries: self.entries(), mask, _collectors: core::marker::PhantomData }
     }
 
     /// Attempt to find a specific child unit address with a specific child
@@ -565,8 +552,7 @@ impl<
         CInt::Output: PartialEq,
     {
         let this: InterruptMap<_, _, _, _, (P::Parser, NoPanic)> = InterruptMap {
-            address_cells: self.address_cells,
-            interrupt_cells: self.interrupt_cells,
+            domain_cells: self.domain_cells,
             node: self.node,
             encoded_map: self.encoded_map,
             _collectors: self._collectors,
@@ -593,6 +579,10 @@ impl<
                 }),
         )
     }
+
+    fn entries(&self) -> NexusMapIter<'a, P> {
+        NexusMapIter::new(self.node, &INTERRUPT_NEXUS, self.encoded_map, self.domain_cells)
+    }
 }
 
 impl<
@@ -605,16 +595,10 @@ impl<
     > Property<'a, P> for InterruptMap<'a, CAddr, CInt, PAddr, PInt, P>
 {
     fn parse(node: FallibleNode<'a, P>, _: FallibleRoot<'a, P>) -> Result<Option<Self>, FdtError> {
-        let Some(encoded_map) = node.properties()?.find("interrupt-map")? else { return Ok(None) };
-
-        let address_cells =
-            node.property::<AddressCells>()?.ok_or(FdtError::MissingRequiredProperty("#address-cells"))?;
-        let interrupt_cells =
-            node.property::<InterruptCells>()?.ok_or(FdtError::MissingRequiredProperty("#interrupt-cells"))?;
+        let Some(encoded_map) = node.properties()?.find(INTERRUPT_NEXUS.map)? else { return Ok(None) };
 
         Ok(Some(InterruptMap {
-            address_cells,
-            interrupt_cells,
+            domain_cells: nexus::domain_cells::<P>(node, &INTERRUPT_NEXUS)?,
             node: node.alt(),
             encoded_map: encoded_map.value,
             _collectors: core::marker::PhantomData,
@@ -631,10 +615,7 @@ pub struct InterruptMapIter<
     PInt: CellCollector,
     P: ParserWithMode<'a> = (AlignedParser<'a>, Panic),
 > {
-    address_cells: AddressCells,
-    interrupt_cells: InterruptCells,
-    node: FallibleNode<'a, P>,
-    encoded_map: &'a [u8],
+    entries: NexusMapIter<'a, P>,
     _collectors: core::marker::PhantomData<*mut (CAddr, CInt, PAddr, PInt)>,
 }
 
@@ -651,76 +632,12 @@ impl<
 
     #[track_caller]
     fn next(&mut self) -> Option<Self::Item> {
-        let res = crate::tryblock!({
-            let child_addr_size = self.address_cells.0 * 4;
-            let child_intsp_size = self.interrupt_cells.as_byte_count();
-
-            let Some((child_address_iter, rest)) = self.encoded_map.split_at_checked(child_addr_size) else {
-                return Ok(None);
-            };
-            let Some((child_specifier_iter, rest)) = rest.split_at_checked(child_intsp_size) else {
-                return Ok(None);
-            };
-            let Some((interrupt_parent, rest)) = rest.split_at_checked(4) else {
-                return Ok(None);
-            };
-
-            let root = self.node.make_root::<(P::Parser, NoPanic)>()?;
-            let phandle = u32::from_ne_bytes(interrupt_parent.try_into().unwrap());
-            let interrupt_parent = root
-                .resolve_phandle(PHandle(BigEndianU32::from_be(phandle)))?
-                .ok_or(FdtError::MissingPHandleNode(phandle.swap_bytes()))?;
-
-            let parent_address_cells = interrupt_parent
-                .property::<AddressCells>()?
-                .ok_or(FdtError::MissingRequiredProperty("#address-cells"))?;
-            let parent_interrupt_cells = interrupt_parent
-                .property::<InterruptCells>()?
-                .ok_or(FdtError::MissingRequiredProperty("#interrupt-cells"))?;
-
-            let parent_addr_size = parent_address_cells.0 * 4;
-            let parent_intsp_size = parent_interrupt_cells.as_byte_count();
-
-            let Some((parent_address_iter, rest)) = rest.split_at_checked(parent_addr_size) else {
-                return Ok(None);
-            };
-
-            let Some((parent_specifier_iter, rest)) = rest.split_at_checked(parent_intsp_size) else {
-                return Ok(None);
-            };
-            self.encoded_map = rest;
-
-            let mut child_address_collector = CAddr::Builder::default();
-            for chunk in child_address_iter.chunks_exact(4) {
-                child_address_collector.push(u32::from_be_bytes(chunk.try_into().unwrap()))?;
-            }
-
-            let mut child_specifier_collector = CInt::Builder::default();
-            for chunk in child_specifier_iter.chunks_exact(4) {
-                child_specifier_collector.push(u32::from_be_bytes(chunk.try_into().unwrap()))?;
-            }
-
-            let mut parent_address_collector = PAddr::Builder::default();
-            for chunk in parent_address_iter.chunks_exact(4) {
-                parent_address_collector.push(u32::from_be_bytes(chunk.try_into().unwrap()))?;
-            }
-
-            let mut parent_specifier_collector = PInt::Builder::default();
-            for chunk in parent_specifier_iter.chunks_exact(4) {
-                parent_specifier_collector.push(u32::from_be_bytes(chunk.try_into().unwrap()))?;
-            }
-
-            Ok(Some(InterruptMapEntry {
-                interrupt_parent: interrupt_parent.alt(),
-                child_unit_address: CAddr::map(child_address_collector.finish()),
-                child_interrupt_specifier: CInt::map(child_specifier_collector.finish()),
-                parent_unit_address: PAddr::map(parent_address_collector.finish()),
-                parent_interrupt_specifier: PInt::map(parent_specifier_collector.finish()),
-            }))
-        });
+        let res = self.entries.next().map(|entry| entry.and_then(InterruptMapEntry::collect));
 
+        // This is a manual impl of `map` because we need the panic location to
+        // be the caller if `P::to_output` panics
         #[allow(clippy::manual_map)]
-        match res.transpose() {
+        match res {
             Some(output) => Some(P::to_output(output)),
             None => None,
         }
@@ -741,10 +658,7 @@ pub struct MaskedInterruptMapIter<
     <CInt as CellCollector>::Output:
         Clone + core::ops::BitAnd<<CInt as CellCollector>::Output, Output = <CInt as CellCollector>::Output>,
 {
-    address_cells: AddressCells,
-    interrupt_cells: InterruptCells,
-    node: FallibleNode<'a, P>,
-    encoded_map: &'a [u8],
+    entries: NexusMapIter<'a, P>,
     mask: InterruptMapMask<CAddr, CInt>,
     _collectors: core::marker::PhantomData<*mut (CAddr, CInt, PAddr, PInt)>,
 }
@@ -767,81 +681,18 @@ where
 
     #[track_caller]
     fn next(&mut self) -> Option<Self::Item> {
-        let res = crate::tryblock!({
-            let child_addr_size = self.address_cells.0 * 4;
-            let child_intsp_size = self.interrupt_cells.as_byte_count();
-
-            let Some((child_address_iter, rest)) = self.encoded_map.split_at_checked(child_addr_size) else {
-                return Ok(None);
-            };
-            let Some((child_specifier_iter, rest)) = rest.split_at_checked(child_intsp_size) else {
-                return Ok(None);
-            };
-            let Some((interrupt_parent, rest)) = rest.split_at_checked(4) else {
-                return Ok(None);
-            };
-
-            let root = self.node.make_root::<(P::Parser, NoPanic)>()?;
-            let phandle = u32::from_ne_bytes(interrupt_parent.try_into().unwrap());
-            let interrupt_parent = root
-                .resolve_phandle(PHandle(BigEndianU32::from_be(phandle)))?
-                .ok_or(FdtError::MissingPHandleNode(phandle.swap_bytes()))?;
-
-            let parent_address_cells = interrupt_parent
-                .property::<AddressCells>()?
-                .ok_or(FdtError::MissingRequiredProperty("#address-cells"))?;
-            let parent_interrupt_cells = interrupt_parent
-                .property::<InterruptCells>()?
-                .ok_or(FdtError::MissingRequiredProperty("#interrupt-cells"))?;
-
-            let parent_addr_size = parent_address_cells.0 * 4;
-            let parent_intsp_size = parent_interrupt_cells.as_byte_count();
-
-            let Some((parent_address_iter, rest)) = rest.split_at_checked(parent_addr_size) else {
-                return Ok(None);
-            };
-
-            let Some((parent_specifier_iter, rest)) = rest.split_at_checked(parent_intsp_size) else {
-                return Ok(None);
-            };
-            self.encoded_map = rest;
-
-            let mut child_address_collector = CAddr::Builder::default();
-            for chunk in child_address_iter.chunks_exact(4) {
-                child_address_collector.push(u32::from_be_bytes(chunk.try_into().unwrap()))?;
-            }
-
-            let mut child_specifier_collector = CInt::Builder::default();
-            for chunk in child_specifier_iter.chunks_exact(4) {
-                child_specifier_collector.push(u32::from_be_bytes(chunk.try_into().unwrap()))?;
-            }
-
-            let mut parent_address_collector = PAddr::Builder::default();
-            for chunk in parent_address_iter.chunks_exact(4) {
-                parent_address_collector.push(u32::from_be_bytes(chunk.try_into().unwrap()))?;
-            }
-
-            let mut parent_specifier_collector = PInt::Builder::default();
-            for chunk in parent_specifier_iter.chunks_exact(4) {
-                parent_specifier_collector.push(u32::from_be_bytes(chunk.try_into().unwrap()))?;
-            }
-
-            let child_unit_address = CAddr::map(child_address_collector.finish());
-            let child_interrupt_specifier = CInt::map(child_specifier_collector.finish());
+        let res = self.entries.next().map(|entry| {
+            let entry = InterruptMapEntry::collect(entry?)?;
             let (child_unit_address, child_interrupt_specifier) =
-                self.mask.mask(child_unit_address, child_interrupt_specifier);
-
-            Ok(Some(InterruptMapEntry {
-                interrupt_parent: interrupt_parent.alt(),
-                child_unit_address,
-                child_interrupt_specifier,
-                parent_unit_address: PAddr::map(parent_address_collector.finish()),
-                parent_interrupt_specifier: PInt::map(parent_specifier_collector.finish()),
-            }))
+                self.mask.mask(entry.child_unit_address, entry.child_interrupt_specifier);
+
+            Ok(InterruptMapEntry { child_unit_address, child_interrupt_specifier, ..entry })
         });
 
+        // This is a manual impl of `map` because we need the panic location to
+        // be the caller if `P::to_output` panics
         #[allow(clippy::manual_map)]
-        match res.transpose() {
+        match res {
             Some(output) => Some(P::to_output(output)),
             None => None,
         }
@@ -873,6 +724,31 @@ pub struct InterruptMapEntry<
     pub parent_interrupt_specifier: PInt::Output,
 }
 
+impl<
+        'a,
+        P: ParserWithMode<'a>,
+        CAddr: CellCollector,
+        CInt: CellCollector,
+        PAddr: CellCollector,
+        PInt: CellCollector,
+    > InterruptMapEntry<'a, CAddr, CInt, PAddr, PInt, P>
+{
+    fn collect(entry: NexusMapEntry<'a, P>) -> Result<Self, FdtError> {
+        let (child_unit_address, child_interrupt_specifier) = entry.child.split_at(entry.child_address_cells * 4);
+        let (parent_unit_address, parent_interrupt_specifier) =
+            entry.parent_specifier.split_at(entry.parent_address_cells * 4);
+        let collect = |encoded_array| InterruptSpecifier { encoded_array };
+
+        Ok(Self {
+            interrupt_parent: entry.parent.alt(),
+            child_unit_address: collect(child_unit_address).collect_to::<CAddr>()?,
+            child_interrupt_specifier: collect(child_interrupt_specifier).collect_to::<CInt>()?,
+            parent_unit_address: collect(parent_unit_address).collect_to::<PAddr>()?,
+            parent_interrupt_specifier: collect(parent_interrupt_specifier).collect_to::<PInt>()?,
+        })
+    }
+}
+
 impl<
         'a,
         P: ParserWithMode<'a>,
@@ -913,3 +789,150 @@ where
     PInt::Output: Copy,
 {
 }
+
+const INTERRUPT_NEXUS: Nexus = Nexus {
+    cells: "#interrupt-cells",
+    map: "interrupt-map",
+    mask: "interrupt-map-mask",
+    pass_thru: None,
+    unit_address: true,
+};
+
+/// Maximum number of nodes followed up the interrupt tree while resolving an
+/// interrupt, which guards against cycles.
+const MAX_INTERRUPT_TREE_DEPTH: usize = 32;
+
+/// An interrupt followed through the interrupt tree to the interrupt
+/// controller which receives it. See [`Node::resolve_interrupt`] and
+/// [`Node::map_interrupt`].
+pub struct ResolvedInterrupt<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    /// The interrupt controller node.
+    pub controller: Node<'a, P>,
+    /// The interrupt specifier in the interrupt domain of the controller,
+    /// which has `#interrupt-cells` cells.
+    pub specifier: CellArray<16>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for ResolvedInterrupt<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for ResolvedInterrupt<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+/// The next node up the interrupt tree from `node`: the node referenced by its
+/// `interrupt-parent` property, or otherwise its devicetree parent.
+pub(crate) fn linked_parent<'a, P: ParserWithMode<'a>>(
+    node: FallibleNode<'a, P>,
+) -> Result<Option<FallibleNode<'a, P>>, FdtError> {
+    match node.properties()?.find("interrupt-parent")? {
+        Some(phandle) => {
+            let phandle = phandle.as_value::<u32>()?;
+            match node.make_root::<P::Parser>()?.resolve_phandle(PHandle::new(phandle))? {
+                Some(parent) => Ok(Some(parent)),
+                None => Err(FdtError::MissingPHandleNode(phandle)),
+            }
+        }
+        None => node.full_parent(),
+    }
+}
+
+/// Find the interrupt parent of `node`, following [`linked_parent`] until
+/// reaching a node with an `#interrupt-cells` property.
+fn interrupt_domain<'a, P: ParserWithMode<'a>>(
+    mut node: FallibleNode<'a, P>,
+) -> Result<Option<FallibleNode<'a, P>>, FdtError> {
+    for _ in 0..MAX_INTERRUPT_TREE_DEPTH {
+        match linked_parent::<P>(node)? {
+            Some(parent) if parent.property::<InterruptCells>()?.is_some() => return Ok(Some(parent)),
+            Some(parent) => node = parent,
+            None => return Ok(None),
+        }
+    }
+
+    Err(FdtError::UnresolvedInterrupt)
+}
+
+/// Resolve the interrupt at `index` in the `interrupts-extended` or
+/// `interrupts` property of `node`.
+pub(crate) fn resolve_interrupt<'a, P: ParserWithMode<'a>>(
+    node: FallibleNode<'a, P>,
+    index: usize,
+) -> Result<Option<ResolvedInterrupt<'a, FallibleParser<'a, P>>>, FdtError> {
+    let (parent, specifier) = match node.property::<ExtendedInterrupts<'a, FallibleParser<'a, P>>>()? {
+        Some(extended) => match extended.iter().nth(index).transpose()? {
+            Some(interrupt) => (interrupt.interrupt_parent.0, interrupt.interrupt_specifier()),
+            None => return Ok(None),
+        },
+        None => {
+            let Some(interrupts) = node.properties()?.find("interrupts")? else { return Ok(None) };
+            let parent = interrupt_domain::<P>(node)?.ok_or(FdtError::MissingRequiredProperty("interrupt-parent"))?;
+            let interrupt_cells = parent.property::<InterruptCells>()?.unwrap_or(InterruptCells(0));
+            if interrupt_cells.0 == 0 {
+                return Err(FdtError::InvalidPropertyValue);
+            }
+
+            match interrupts.value.chunks_exact(interrupt_cells.as_byte_count()).nth(index) {
+                Some(encoded_array) => (parent, InterruptSpecifier { encoded_array }),
+                None => return Ok(None),
+            }
+        }
+    };
+
+    // Nexus nodes match on the unit address of the device in addition to the
+    // interrupt specifier, which is the beginning of its `reg` property
+    let mut unit_address = CellArray::<16>::default();
+    if let Some(reg) = node.properties()?.find("reg")? {
+        for cell in reg.value.chunks_exact(4).take(16) {
+            unit_address.push(u32::from_be_bytes(cell.try_into().unwrap()))?;
+        }
+    }
+
+    map_interrupt::<P>(parent, unit_address, specifier.collect_to::<CellArray<16>>()?).map(Some)
+}
+
+/// Follow an interrupt in the interrupt domain of `parent` through any nexus
+/// nodes to the interrupt controller which receives it.
+pub(crate) fn map_interrupt<'a, P: ParserWithMode<'a>>(
+    mut parent: FallibleNode<'a, P>,
+    mut unit_address: CellArray<16>,
+    mut specifier: CellArray<16>,
+) -> Result<ResolvedInterrupt<'a, FallibleParser<'a, P>>, FdtError> {
+    for _ in 0..MAX_INTERRUPT_TREE_DEPTH {
+        if parent.property::<InterruptController>()?.is_some() {
+            return Ok(ResolvedInterrupt { controller: parent, specifier });
+        }
+
+        let Some(map) = parent.properties()?.find(INTERRUPT_NEXUS.map)? else {
+            // Nodes with `#interrupt-cells` which are neither an interrupt
+            // controller nor a nexus pass interrupts on to their own interrupt
+            // parent unchanged
+            parent = interrupt_domain::<P>(parent)?.ok_or(FdtError::UnresolvedInterrupt)?;
+            continue;
+        };
+
+        // Entries of an `interrupt-map` match on the unit address followed by
+        // the interrupt specifier
+        let address_cells =
+            parent.property::<AddressCells>()?.ok_or(FdtError::MissingRequiredProperty("#address-cells"))?;
+        let mut key = CellArray::<MAX_NEXUS_CELLS>::new(&unit_address.resize(address_cells.0)?)
+            .ok_or(FdtError::CollectCellsError)?;
+        for &cell in specifier.iter() {
+            key.push(cell)?;
+        }
+
+        let Some((next, mapped)) = nexus::map_specifier::<P>(parent, &INTERRUPT_NEXUS, map.value, key)? else {
+            return Err(FdtError::UnresolvedInterrupt);
+        };
+
+        let address_cells =
+            next.property::<AddressCells>()?.ok_or(FdtError::MissingRequiredProperty("#address-cells"))?;
+        let (address, rest) = mapped.split_at_checked(address_cells.0).ok_or(FdtError::InvalidPropertyValue)?;
+
+        parent = next;
+        unit_address = CellArray::new(address).ok_or(FdtError::CollectCellsError)?;
+        specifier = CellArray::new(rest).ok_or(FdtError::CollectCellsError)?;
+    }
+
+    Err(FdtError::UnresolvedInterrupt)
+}
@@ -0,0 +1,26 @@
+use super::{id_map::IdMap, phandle_args::PhandleArgsList};
+
+newtype_property! {
+    /// [Devicetree IOMMU
+    /// binding](https://www.kernel.org/doc/Documentation/devicetree/bindings/iommu/iommu.txt)
+    /// `iommus` property
+    ///
+    /// The IOMMUs which translate the DMA of a device, as a list of phandles to
+    /// IOMMU nodes each followed by `#iommu-cells` cells of IOMMU specifier, which
+    /// usually contain the stream ID of the device.
+    ///
+    /// This dereferences to a [`PhandleArgsList`] for iterating over the IOMMUs.
+    pub struct Iommus(PhandleArgsList) = |node| PhandleArgsList::parse(node, "iommus", "#iommu-cells", None);
+}
+
+newtype_property! {
+    /// [Devicetree PCI IOMMU
+    /// binding](https://www.kernel.org/doc/Documentation/devicetree/bindings/pci/pci-iommu.txt)
+    /// `iommu-map` and `iommu-map-mask` properties
+    ///
+    /// Maps the requester IDs of devices below a bus, such as a PCI host bridge, to
+    /// IOMMUs and the stream IDs they use.
+    ///
+    /// This dereferences to an [`IdMap`] for translating requester IDs.
+    pub struct IommuMap(IdMap) = |node| IdMap::parse(node, "iommu-map", "iommu-map-mask");
+}
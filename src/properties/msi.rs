@@ -0,0 +1,142 @@
+use super::{id_map::IdMap, phandle_args::PhandleArgsList, Property};
+use crate::{
+    cell_collector::CellArray,
+    helpers::{FallibleNode, FallibleParser, FallibleRoot},
+    nodes::Node,
+    parsing::{aligned::AlignedParser, Panic, ParserWithMode},
+    FdtError,
+};
+
+/// [Devicetree MSI
+/// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/msi-controller.yaml)
+///
+/// The presence of an `msi-controller` property defines a node as an MSI
+/// controller, which receives message signaled interrupts from devices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MsiController;
+
+impl<'a, P: ParserWithMode<'a>> Property<'a, P> for MsiController {
+    fn parse(node: FallibleNode<'a, P>, _: FallibleRoot<'a, P>) -> Result<Option<Self>, FdtError> {
+        match node.properties()?.find("msi-controller")? {
+            Some(_) => Ok(Some(Self)),
+            None => Ok(None),
+        }
+    }
+}
+
+newtype_property! {
+    /// [Devicetree MSI
+    /// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/msi-consumer.yaml)
+    /// `msi-parent` property
+    ///
+    /// The MSI controllers a device may send MSIs to. This is either a single
+    /// phandle, for controllers without a `#msi-cells` property, or a list of
+    /// phandles each followed by `#msi-cells` cells of MSI specifier, which
+    /// usually contain the device ID the controller identifies the device by.
+    ///
+    /// This dereferences to a [`PhandleArgsList`] for iterating over the
+    /// controllers.
+    pub struct MsiParent(PhandleArgsList) = |node| {
+        PhandleArgsList::parse(node, "msi-parent", "#msi-cells", None)
+            .map(|list| list.map(PhandleArgsList::with_optional_cells))
+    };
+}
+
+newtype_property! {
+    /// [Devicetree PCI MSI
+    /// binding](https://www.kernel.org/doc/Documentation/devicetree/bindings/pci/pci-msi.txt)
+    /// `msi-map` and `msi-map-mask` properties
+    ///
+    /// Maps the requester IDs of devices below a bus, such as a PCI host bridge, to
+    /// MSI controllers and the device IDs they identify the devices by.
+    ///
+    /// This dereferences to an [`IdMap`] for translating requester IDs.
+    pub struct MsiMap(IdMap) = |node| IdMap::parse(node, "msi-map", "msi-map-mask");
+}
+
+/// The MSI controller a device sends MSIs to. See [`Node::msi_controller`].
+///
+/// [`Node::msi_controller`]: crate::nodes::Node::msi_controller
+pub struct ResolvedMsi<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    /// The MSI controller node.
+    pub controller: Node<'a, P>,
+    /// The MSI specifier, which is either the arguments of the `msi-parent`
+    /// entry, or the single device ID produced by an `msi-map`.
+    pub specifier: CellArray<16>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for ResolvedMsi<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for ResolvedMsi<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+/// Find the MSI controller of `node` from its own `msi-parent`, or otherwise
+/// from the `msi-map` or `msi-parent` of the closest bus node above it.
+pub(crate) fn resolve_msi<'a, P: ParserWithMode<'a>>(
+    node: FallibleNode<'a, P>,
+) -> Result<Option<ResolvedMsi<'a, FallibleParser<'a, P>>>, FdtError> {
+    if let Some(resolved) = first_msi_parent::<P>(node)? {
+        return Ok(Some(resolved));
+    }
+
+    let mut child = node;
+    while let Some(bus) = child.full_parent()? {
+        if let Some(map) = IdMap::<P>::parse(bus, "msi-map", "msi-map-mask")? {
+            let Some(mapped) = map.fallible_map(requester_id::<P>(bus, child, node)?)? else { return Ok(None) };
+            let specifier = CellArray::new(&[mapped.id]).ok_or(FdtError::CollectCellsError)?;
+            return Ok(Some(ResolvedMsi { controller: mapped.target.fallible(), specifier }));
+        }
+
+        if let Some(resolved) = first_msi_parent::<P>(bus)? {
+            return Ok(Some(resolved));
+        }
+
+        child = bus;
+    }
+
+    Ok(None)
+}
+
+/// The first non-empty entry of the `msi-parent` property of `node`.
+fn first_msi_parent<'a, P: ParserWithMode<'a>>(
+    node: FallibleNode<'a, P>,
+) -> Result<Option<ResolvedMsi<'a, FallibleParser<'a, P>>>, FdtError> {
+    let Some(parent) = node.property::<MsiParent<FallibleParser<'a, P>>>()? else { return Ok(None) };
+    for entry in parent.iter() {
+        let entry = entry?;
+        if let Some(controller) = entry.provider {
+            return Ok(Some(ResolvedMsi { controller, specifier: entry.args }));
+        }
+    }
+
+    Ok(None)
+}
+
+/// The ID `device` is identified by on the bus `bus`, through its direct
+/// child `child`, for looking up in the bus's `msi-map`. For PCI buses this is
+/// the requester ID made up of the bus, device, and function numbers in the
+/// first cell of the device's own PCI address, since devices behind bridges
+/// use their own requester ID rather than the bridge's. Otherwise it's the
+/// first cell of the child's `reg` property.
+fn requester_id<'a, P: ParserWithMode<'a>>(
+    bus: FallibleNode<'a, P>,
+    child: FallibleNode<'a, P>,
+    device: FallibleNode<'a, P>,
+) -> Result<u32, FdtError> {
+    let pci = match bus.properties()?.find("device_type")? {
+        Some(device_type) => device_type.as_value::<&str>()? == "pci",
+        None => false,
+    };
+
+    let node = if pci { device } else { child };
+    let Some(reg) = node.properties()?.find("reg")? else { return Err(FdtError::MissingRequiredProperty("reg")) };
+    let Some(first) = reg.value.first_chunk::<4>() else { return Err(FdtError::InvalidPropertyValue) };
+    let first = u32::from_be_bytes(*first);
+
+    match pci {
+        true => Ok((first >> 8) & 0xffff),
+        false => Ok(first),
+    }
+}
@@ -0,0 +1,199 @@
+// This Source Code Form is subject to the terms of the Mozilla Public License,
+// v. 2.0. If a copy of the MPL was not distributed with this file, You can
+// obtain one at https://mozilla.org/MPL/2.0/.
+
+use super::{cells::AddressCells, PHandle};
+use crate::{
+    cell_collector::CellArray,
+    helpers::{FallibleNode, FallibleRoot},
+    nodes::root::Root,
+    parsing::ParserWithMode,
+    FdtError,
+};
+
+/// Maximum number of nexus nodes followed while mapping a specifier, which
+/// guards against cycles.
+pub(crate) const MAX_NEXUS_DEPTH: usize = 16;
+
+/// Maximum number of cells in a specifier being mapped through a nexus node,
+/// including the unit address which prefixes it in an `interrupt-map`.
+pub(crate) const MAX_NEXUS_CELLS: usize = 32;
+
+/// The property names used by a nexus node to map specifiers of one kind, such
+/// as `gpio-map`, `gpio-map-mask`, and `gpio-map-pass-thru` for GPIOs.
+pub(crate) struct Nexus {
+    pub(crate) cells: &'static str,
+    pub(crate) map: &'static str,
+    pub(crate) mask: &'static str,
+    /// The `*-map-pass-thru` property, for maps which support one.
+    pub(crate) pass_thru: Option<&'static str>,
+    /// Whether the specifiers in the map are prefixed by a unit address of
+    /// `#address-cells` cells, as they are in an `interrupt-map`.
+    pub(crate) unit_address: bool,
+}
+
+/// The number of cells in a unit address and in a specifier in the domain of
+/// `node`. The unit address is empty for maps which don't have one.
+pub(crate) fn domain_cells<'a, P: ParserWithMode<'a>>(
+    node: FallibleNode<'a, P>,
+    nexus: &Nexus,
+) -> Result<(usize, usize), FdtError> {
+    let cells = match node.raw_property(nexus.cells)? {
+        Some(value) => value.as_value::<u32>()? as usize,
+        None => return Err(FdtError::MissingRequiredProperty(nexus.cells)),
+    };
+
+    match nexus.unit_address {
+        true => match node.property::<AddressCells>()? {
+            Some(address_cells) => Ok((address_cells.0, cells)),
+            None => Err(FdtError::MissingRequiredProperty("#address-cells")),
+        },
+        false => Ok((0, cells)),
+    }
+}
+
+/// The value of the mask property `name` of `node`, such as `*-map-mask`, which
+/// must have as many cells as a unit address and specifier in its domain.
+pub(crate) fn map_mask<'a, P: ParserWithMode<'a>>(
+    node: FallibleNode<'a, P>,
+    name: &str,
+    (address_cells, cells): (usize, usize),
+) -> Result<Option<&'a [u8]>, FdtError> {
+    let Some(mask) = node.properties()?.find(name)? else { return Ok(None) };
+    match address_cells.checked_add(cells).and_then(|cells| cells.checked_mul(4)) == Some(mask.value.len()) {
+        true => Ok(Some(mask.value)),
+        false => Err(FdtError::InvalidPropertyValue),
+    }
+}
+
+/// Look `specifier` up in the map of the nexus node `node`, whose `*-map`
+/// property value is `map`, returning the parent node and specifier of the
+/// matching entry, or `None` if no entry matches.
+///
+/// The specifier and the child specifier of each entry are `AND`ed with the
+/// `*-map-mask` before being compared. Cells set in the `*-map-pass-thru`
+/// mask are then copied from the specifier into the parent specifier of the
+/// matching entry.
+pub(crate) fn map_specifier<'a, P: ParserWithMode<'a>>(
+    node: FallibleNode<'a, P>,
+    nexus: &'static Nexus,
+    map: &'a [u8],
+    specifier: CellArray<MAX_NEXUS_CELLS>,
+) -> Result<Option<(FallibleNode<'a, P>, CellArray<MAX_NEXUS_CELLS>)>, FdtError> {
+    let domain_cells = domain_cells::<P>(node, nexus)?;
+    let cells = domain_cells.0.checked_add(domain_cells.1).ok_or(FdtError::CollectCellsError)?;
+
+    let read_mask = |name: Option<&str>, default| {
+        let mask = match name {
+            Some(name) => map_mask::<P>(node, name, domain_cells)?,
+            None => None,
+        };
+
+        match mask {
+            Some(mask) => Ok(CellArray::read_cells(mask, cells)?.0),
+            None => [default; MAX_NEXUS_CELLS].get(..cells).and_then(CellArray::new).ok_or(FdtError::CollectCellsError),
+        }
+    };
+
+    let mask = read_mask(Some(nexus.mask), u32::MAX)?;
+    let pass_thru = read_mask(nexus.pass_thru, 0)?;
+    let specifier = specifier.resize(cells)?;
+    let masked = specifier & mask;
+
+    for entry in NexusMapIter::<P>::new(node, nexus, map, domain_cells) {
+        let entry = entry?;
+        if CellArray::read_cells(entry.child, cells)?.0 & mask != masked {
+            continue;
+        }
+
+        let (mut mapped, _) = CellArray::read_cells(entry.parent_specifier, entry.parent_specifier.len() / 4)?;
+        for (i, cell) in mapped.cells_mut().iter_mut().enumerate() {
+            let pass_thru = pass_thru.get(i).copied().unwrap_or(0);
+            *cell = (*cell & !pass_thru) | (specifier.get(i).copied().unwrap_or(0) & pass_thru);
+        }
+
+        return Ok(Some((entry.parent, mapped)));
+    }
+
+    Ok(None)
+}
+
+/// Iterator over the entries of the `*-map` property of a nexus node.
+pub(crate) struct NexusMapIter<'a, P: ParserWithMode<'a>> {
+    node: FallibleNode<'a, P>,
+    nexus: &'static Nexus,
+    /// The [`domain_cells`] of the nexus node.
+    domain_cells: (usize, usize),
+    entries: &'a [u8],
+}
+
+impl<'a, P: ParserWithMode<'a>> NexusMapIter<'a, P> {
+    /// Iterate over `map`, the `*-map` property value of `node`, whose
+    /// [`domain_cells`] are `domain_cells`.
+    pub(crate) fn new(
+        node: FallibleNode<'a, P>,
+        nexus: &'static Nexus,
+        map: &'a [u8],
+        domain_cells: (usize, usize),
+    ) -> Self {
+        Self { node, nexus, domain_cells, entries: map }
+    }
+
+    fn next_entry(&mut self) -> Result<Option<NexusMapEntry<'a, P>>, FdtError> {
+        if self.entries.is_empty() {
+            return Ok(None);
+        }
+
+        let child_address_cells = self.domain_cells.0;
+        let (child, rest) = split_cells(self.entries, child_address_cells.checked_add(self.domain_cells.1))?;
+        let Some((phandle, rest)) = rest.split_first_chunk::<4>() else {
+            return Err(FdtError::InvalidPropertyValue);
+        };
+
+        let phandle = u32::from_be_bytes(*phandle);
+        let root: FallibleRoot<'a, P> = Root { node: self.node.make_root::<P::Parser>()?.node };
+        let Some(parent) = root.resolve_phandle(PHandle::new(phandle))? else {
+            return Err(FdtError::MissingPHandleNode(phandle));
+        };
+
+        let (parent_address_cells, parent_cells) = domain_cells::<P>(parent, self.nexus)?;
+        let (parent_specifier, rest) = split_cells(rest, parent_address_cells.checked_add(parent_cells))?;
+        self.entries = rest;
+
+        Ok(Some(NexusMapEntry { child, child_address_cells, parent, parent_specifier, parent_address_cells }))
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for NexusMapIter<'a, P> {
+    type Item = Result<NexusMapEntry<'a, P>, FdtError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = self.next_entry();
+        if res.is_err() {
+            self.entries = &[];
+        }
+
+        res.transpose()
+    }
+}
+
+/// A single entry of a `*-map` property, with its specifiers as raw cells.
+pub(crate) struct NexusMapEntry<'a, P: ParserWithMode<'a>> {
+    /// The child unit address, if the map has one, followed by the child
+    /// specifier.
+    pub(crate) child: &'a [u8],
+    pub(crate) child_address_cells: usize,
+    pub(crate) parent: FallibleNode<'a, P>,
+    /// The parent unit address, if the map has one, followed by the parent
+    /// specifier.
+    pub(crate) parent_specifier: &'a [u8],
+    pub(crate) parent_address_cells: usize,
+}
+
+/// Split `cells` cells off the front of `bytes`.
+fn split_cells(bytes: &[u8], cells: Option<usize>) -> Result<(&[u8], &[u8]), FdtError> {
+    cells
+        .and_then(|cells| cells.checked_mul(4))
+        .and_then(|len| bytes.split_at_checked(len))
+        .ok_or(FdtError::InvalidPropertyValue)
+}
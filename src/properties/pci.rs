@@ -121,6 +121,7 @@ impl core::ops::BitAnd for PciAddress {
 
 /// Type of PCI address space.
 #[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(u8)]
 pub enum PciAddressSpace {
     Configuration = 0b00,
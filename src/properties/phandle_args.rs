@@ -0,0 +1,235 @@
+use super::{values::StringList, PHandle};
+use crate::{
+    cell_collector::CellArray,
+    helpers::{FallibleNode, FallibleParser, FallibleRoot},
+    nodes::{root::Root, Node},
+    parsing::{aligned::AlignedParser, Panic, ParserWithMode},
+    FdtError,
+};
+
+/// Maximum number of argument cells supported in a single entry of a
+/// [`PhandleArgsList`].
+pub const MAX_PHANDLE_ARGS: usize = 16;
+
+/// A list of phandles to provider nodes, each followed by a number of argument
+/// cells given by a `#*-cells` property of the provider, such as the `clocks`,
+/// `resets`, `dmas`, and `*-gpios` properties. See
+/// [`Node::phandle_args_list`].
+///
+/// Entries can optionally be named by a matching `*-names` property, such as
+/// `clock-names` for `clocks`.
+///
+/// Example:
+///
+/// ```norust
+/// clocks = <&osc>, <&pll 1>, <&pll 2>;
+/// clock-names = "ref", "core", "bus";
+/// ```
+///
+/// Where `osc` has a `#clock-cells` value of `0`, and `pll` has a
+/// `#clock-cells` value of `1`.
+///
+/// [`Node::phandle_args_list`]: crate::nodes::Node::phandle_args_list
+pub struct PhandleArgsList<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    root: Root<'a, P>,
+    cells: &'static str,
+    optional_cells: bool,
+    encoded_array: &'a [u8],
+    names: Option<&'a str>,
+}
+
+impl<'a, P: ParserWithMode<'a>> PhandleArgsList<'a, P> {
+    /// Name of the `#*-cells` property giving the number of argument cells
+    /// for each provider.
+    pub fn cells_property(&self) -> &'static str {
+        self.cells
+    }
+
+    /// The names of the entries, if the node has a matching `*-names`
+    /// property.
+    pub fn names(&self) -> Option<StringList<'a>> {
+        self.names.map(StringList::from)
+    }
+
+    /// Create an iterator over the entries in the list.
+    pub fn iter(&self) -> PhandleArgsIter<'a, P> {
+        PhandleArgsIter {
+            root: self.root,
+            cells: self.cells,
+            optional_cells: self.optional_cells,
+            encoded_array: self.encoded_array,
+            names: self.names(),
+        }
+    }
+
+    /// Returns the entry at `index`, if there are enough entries.
+    #[track_caller]
+    pub fn get(&self, index: usize) -> P::Output<Option<PhandleArgs<'a, P>>> {
+        P::to_output(self.fallible_iter().nth(index).transpose().map(|e| e.map(PhandleArgs::alt)))
+    }
+
+    /// Returns the entry with the name `name` in the `*-names` property.
+    #[track_caller]
+    pub fn find(&self, name: &str) -> P::Output<Option<PhandleArgs<'a, P>>> {
+        P::to_output(crate::tryblock!({
+            let Some(index) = self.names().and_then(|mut names| names.position(|n| n == name)) else {
+                return Ok(None);
+            };
+
+            Ok(self.fallible_iter().nth(index).transpose()?.map(PhandleArgs::alt))
+        }))
+    }
+
+    /// Parse the property `property` of `node` as a list of phandles with
+    /// arguments. See [`Node::phandle_args_list`].
+    ///
+    /// [`Node::phandle_args_list`]: crate::nodes::Node::phandle_args_list
+    pub(crate) fn parse(
+        node: FallibleNode<'a, P>,
+        property: &str,
+        cells: &'static str,
+        names: Option<&str>,
+    ) -> Result<Option<Self>, FdtError> {
+        let Some(list) = node.properties()?.find(property)? else { return Ok(None) };
+        let names = match names {
+            Some(names) => match node.properties()?.find(names)? {
+                Some(names) => Some(names.as_value::<&'a str>()?),
+                None => None,
+            },
+            None => None,
+        };
+
+        Ok(Some(Self {
+            root: Root { node: node.make_root::<P::Parser>()?.node },
+            cells,
+            optional_cells: false,
+            encoded_array: list.value,
+            names,
+        }))
+    }
+
+    /// Treat providers without the `#*-cells` property as having no argument
+    /// cells, as bindings such as `msi-parent` allow, rather than returning
+    /// an error.
+    pub(crate) fn with_optional_cells(self) -> Self {
+        Self { optional_cells: true, ..self }
+    }
+
+    pub(crate) fn fallible_iter(&self) -> PhandleArgsIter<'a, FallibleParser<'a, P>> {
+        PhandleArgsIter {
+            root: Root { node: self.root.node },
+            cells: self.cells,
+            optional_cells: self.optional_cells,
+            encoded_array: self.encoded_array,
+            names: self.names(),
+        }
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for PhandleArgsList<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for PhandleArgsList<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+/// See [`PhandleArgsList::iter`].
+pub struct PhandleArgsIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    root: Root<'a, P>,
+    cells: &'static str,
+    optional_cells: bool,
+    encoded_array: &'a [u8],
+    names: Option<StringList<'a>>,
+}
+
+impl<'a, P: ParserWithMode<'a>> PhandleArgsIter<'a, P> {
+    fn next_entry(&mut self) -> Result<Option<PhandleArgs<'a, P>>, FdtError> {
+        let Some((phandle, rest)) = self.encoded_array.split_first_chunk::<4>() else {
+            return match self.encoded_array.is_empty() {
+                true => Ok(None),
+                false => Err(FdtError::InvalidPropertyValue),
+            };
+        };
+
+        self.encoded_array = rest;
+        let name = self.names.as_mut().and_then(Iterator::next);
+
+        // A phandle of zero is an empty entry, used by some bindings as a
+        // placeholder so that the following entries keep their index
+        let phandle = u32::from_be_bytes(*phandle);
+        if phandle == 0 {
+            return Ok(Some(PhandleArgs { provider: None, args: CellArray::default(), name }));
+        }
+
+        let root: FallibleRoot<'a, P> = Root { node: self.root.node };
+        let Some(provider) = root.resolve_phandle(PHandle::new(phandle))? else {
+            return Err(FdtError::MissingPHandleNode(phandle));
+        };
+
+        let cells = match provider.raw_property(self.cells)? {
+            Some(cells) => cells.as_value::<u32>()? as usize,
+            None if self.optional_cells => 0,
+            None => return Err(FdtError::MissingRequiredProperty(self.cells)),
+        };
+
+        let (args, rest) = CellArray::read_cells(self.encoded_array, cells)?;
+        self.encoded_array = rest;
+
+        Ok(Some(PhandleArgs { provider: Some(provider.alt()), args, name }))
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for PhandleArgsIter<'a, P> {
+    type Item = P::Output<PhandleArgs<'a, P>>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = self.next_entry();
+        if res.is_err() {
+            self.encoded_array = &[];
+        }
+
+        // This is a manual impl of `map` because we need the panic location to
+        // be the caller if `P::to_output` panics
+        #[allow(clippy::manual_map)]
+        match res.transpose() {
+            Some(output) => Some(P::to_output(output)),
+            None => None,
+        }
+    }
+}
+
+/// A single entry of a [`PhandleArgsList`].
+pub struct PhandleArgs<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    /// The provider node referenced by the phandle, or `None` if the entry is
+    /// empty because its phandle is zero.
+    pub provider: Option<Node<'a, P>>,
+    /// The argument cells following the phandle, of which there are as many
+    /// as the provider's `#*-cells` property specifies.
+    pub args: CellArray<MAX_PHANDLE_ARGS>,
+    /// The name of the entry in the `*-names` property, if any.
+    pub name: Option<&'a str>,
+}
+
+impl<'a, P: ParserWithMode<'a>> PhandleArgs<'a, P> {
+    pub(crate) fn alt<P2: ParserWithMode<'a, Granularity = P::Granularity>>(self) -> PhandleArgs<'a, P2> {
+        PhandleArgs { provider: self.provider.map(|n| n.alt()), args: self.args, name: self.name }
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for PhandleArgs<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for PhandleArgs<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> core::fmt::Debug for PhandleArgs<'a, P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PhandleArgs")
+            .field("provider", &self.provider.map(|n| n.fallible().name()))
+            .field("args", &self.args)
+            .field("name", &self.name)
+            .finish()
+    }
+}
@@ -0,0 +1,342 @@
+use super::{values::StringList, PHandle, Property};
+use crate::{
+    helpers::{FallibleNode, FallibleRoot},
+    nodes::{root::Root, Node},
+    parsing::{aligned::AlignedParser, Panic, ParserWithMode},
+    FdtError,
+};
+
+/// [Devicetree pin control
+/// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/pinctrl/pinctrl-consumer.yaml)
+/// `pinctrl-N` and `pinctrl-names` properties
+///
+/// The pin configuration states of a device, where the state with index `N`
+/// is given by the `pinctrl-N` property as a list of phandles to pin
+/// configuration nodes, and is named by the `N`th entry of `pinctrl-names`.
+/// The states are numbered sequentially from zero.
+///
+/// Example:
+///
+/// ```norust
+/// pinctrl-names = "default", "sleep";
+/// pinctrl-0 = <&uart0_pins &uart0_bias>;
+/// pinctrl-1 = <&uart0_sleep_pins>;
+/// ```
+pub struct PinctrlStates<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    node: Node<'a, P>,
+    names: Option<&'a str>,
+}
+
+impl<'a, P: ParserWithMode<'a>> PinctrlStates<'a, P> {
+    /// The names of the states, if the node has a `pinctrl-names` property.
+    pub fn names(&self) -> Option<StringList<'a>> {
+        self.names.map(StringList::from)
+    }
+
+    /// Create an iterator over the states, in order of their index.
+    pub fn iter(&self) -> PinctrlStatesIter<'a, P> {
+        PinctrlStatesIter { node: Some(self.node), names: self.names(), index: 0 }
+    }
+
+    /// Returns the state with index `index`.
+    #[track_caller]
+    pub fn get(&self, index: usize) -> P::Output<Option<PinctrlState<'a, P>>> {
+        P::to_output(self.fallible_state(index, self.names().and_then(|mut names| names.nth(index))))
+    }
+
+    /// Returns the state with the name `name` in `pinctrl-names`, such as
+    /// `"default"`.
+    #[track_caller]
+    pub fn find(&self, name: &str) -> P::Output<Option<PinctrlState<'a, P>>> {
+        P::to_output(match self.names().and_then(|mut names| names.position(|n| n == name)) {
+            Some(index) => self.fallible_state(index, self.names().and_then(|mut names| names.nth(index))),
+            None => Ok(None),
+        })
+    }
+
+    fn fallible_state(&self, index: usize, name: Option<&'a str>) -> Result<Option<PinctrlState<'a, P>>, FdtError> {
+        let mut buffer = [0; PROPERTY_NAME_LEN];
+        let Some(configs) = self.node.fallible().properties()?.find(property_name(index, &mut buffer))? else {
+            return Ok(None);
+        };
+
+        if configs.value.len() % 4 != 0 {
+            return Err(FdtError::InvalidPropertyValue);
+        }
+
+        let root = Root { node: self.node.fallible().make_root::<P::Parser>()?.node.alt() };
+        Ok(Some(PinctrlState { index, name, root, encoded_array: configs.value }))
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for PinctrlStates<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for PinctrlStates<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Property<'a, P> for PinctrlStates<'a, P> {
+    fn parse(node: FallibleNode<'a, P>, _: FallibleRoot<'a, P>) -> Result<Option<Self>, FdtError> {
+        let properties = node.properties()?;
+        if properties.find("pinctrl-0")?.is_none() {
+            return Ok(None);
+        }
+
+        let names = match properties.find("pinctrl-names")? {
+            Some(names) => Some(names.as_value::<&'a str>()?),
+            None => None,
+        };
+
+        Ok(Some(Self { node: node.alt(), names }))
+    }
+}
+
+/// Length of the longest `pinctrl-N` property name.
+const PROPERTY_NAME_LEN: usize = "pinctrl-".len() + 20;
+
+/// Write the name of the `pinctrl-N` property for the state `index` into
+/// `buffer`.
+fn property_name(mut index: usize, buffer: &mut [u8; PROPERTY_NAME_LEN]) -> &str {
+    let mut digits = [0; 20];
+    let mut len = 0;
+    loop {
+        digits[len] = b'0' + (index % 10) as u8;
+        len += 1;
+        index /= 10;
+
+        if index == 0 {
+            break;
+        }
+    }
+
+    buffer[..8].copy_from_slice(b"pinctrl-");
+    for (byte, digit) in buffer[8..].iter_mut().zip(digits[..len].iter().rev()) {
+        *byte = *digit;
+    }
+
+    core::str::from_utf8(&buffer[..8 + len]).unwrap()
+}
+
+/// See [`PinctrlStates::iter`].
+pub struct PinctrlStatesIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    node: Option<Node<'a, P>>,
+    names: Option<StringList<'a>>,
+    index: usize,
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for PinctrlStatesIter<'a, P> {
+    type Item = P::Output<PinctrlState<'a, P>>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        let states = PinctrlStates { node: self.node?, names: None };
+        let name = self.names.as_mut().and_then(Iterator::next);
+
+        let res = states.fallible_state(self.index, name).transpose();
+        self.index += 1;
+        if !matches!(res, Some(Ok(_))) {
+            self.node = None;
+        }
+
+        // This is a manual impl of `map` because we need the panic location to
+        // be the caller if `P::to_output` panics
+        #[allow(clippy::manual_map)]
+        match res {
+            Some(output) => Some(P::to_output(output)),
+            None => None,
+        }
+    }
+}
+
+/// A single pin configuration state of a device, given by a `pinctrl-N`
+/// property.
+pub struct PinctrlState<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    /// The index `N` of the state.
+    pub index: usize,
+    /// The name of the state in `pinctrl-names`, if any.
+    pub name: Option<&'a str>,
+    root: Root<'a, P>,
+    encoded_array: &'a [u8],
+}
+
+impl<'a, P: ParserWithMode<'a>> PinctrlState<'a, P> {
+    /// Create an iterator over the pin configuration nodes of the state. A
+    /// state may have no configuration nodes, in which case the device's
+    /// pins are left as they are.
+    pub fn configs(&self) -> PinctrlConfigsIter<'a, P> {
+        PinctrlConfigsIter { root: self.root, encoded_array: self.encoded_array }
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for PinctrlState<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for PinctrlState<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+/// See [`PinctrlState::configs`].
+pub struct PinctrlConfigsIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    root: Root<'a, P>,
+    encoded_array: &'a [u8],
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for PinctrlConfigsIter<'a, P> {
+    type Item = P::Output<Node<'a, P>>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (phandle, rest) = self.encoded_array.split_first_chunk::<4>()?;
+        self.encoded_array = rest;
+
+        let phandle = u32::from_be_bytes(*phandle);
+        Some(P::to_output(crate::tryblock!({
+            let root: FallibleRoot<'a, P> = Root { node: self.root.node.fallible() };
+            match root.resolve_phandle(PHandle::new(phandle))? {
+                Some(node) => Ok(node.alt()),
+                None => Err(FdtError::MissingPHandleNode(phandle)),
+            }
+        })))
+    }
+}
+
+/// [Devicetree generic pin configuration
+/// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/pinctrl/pincfg-node.yaml)
+///
+/// The generic pin multiplexing and configuration properties of a pin
+/// configuration node, see [`Node::pin_config`]. Pin controllers with their
+/// own bindings may use other properties as well, which aren't included.
+///
+/// [`Node::pin_config`]: crate::nodes::Node::pin_config
+#[derive(Debug, Clone)]
+pub struct PinConfig<'a> {
+    /// The names of the pins the configuration applies to, from `pins`.
+    pub pins: Option<StringList<'a>>,
+    /// The names of the pin groups the configuration applies to, from
+    /// `groups`.
+    pub groups: Option<StringList<'a>>,
+    /// The function to multiplex onto the pins, from `function`.
+    pub function: Option<&'a str>,
+    /// The bias to apply to the pins.
+    pub bias: Option<PinBias>,
+    /// How the pins are driven as outputs.
+    pub drive: Option<PinDrive>,
+    /// The output drive strength in mA, from `drive-strength`.
+    pub drive_strength: Option<u32>,
+    /// The output drive strength in µA, from `drive-strength-microamp`.
+    pub drive_strength_microamp: Option<u32>,
+    /// Whether the input buffer is enabled, from `input-enable` (`true`) or
+    /// `input-disable` (`false`).
+    pub input_enable: Option<bool>,
+    /// Whether the Schmitt trigger is enabled, from `input-schmitt-enable`
+    /// (`true`) or `input-schmitt-disable` (`false`).
+    pub input_schmitt: Option<bool>,
+    /// Whether the output is enabled, from `output-enable` (`true`) or
+    /// `output-disable` (`false`).
+    pub output_enable: Option<bool>,
+    /// The level to drive the output to, from `output-high` (`true`) or
+    /// `output-low` (`false`).
+    pub output_high: Option<bool>,
+    /// The controller specific slew rate, from `slew-rate`.
+    pub slew_rate: Option<u32>,
+}
+
+impl<'a> PinConfig<'a> {
+    pub(crate) fn parse<P: ParserWithMode<'a>>(node: FallibleNode<'a, P>) -> Result<Self, FdtError> {
+        let properties = node.properties()?;
+        let has = |name| Ok::<_, FdtError>(properties.find(name)?.is_some());
+        let flag = |enable, disable| match (has(enable)?, has(disable)?) {
+            (true, _) => Ok::<_, FdtError>(Some(true)),
+            (false, true) => Ok(Some(false)),
+            (false, false) => Ok(None),
+        };
+        let u32_value = |name| match properties.find(name)? {
+            Some(value) => Ok::<_, FdtError>(Some(value.as_value::<u32>()?)),
+            None => Ok(None),
+        };
+        // Resistances are optional on the bias properties
+        let bias_value = |name| match properties.find(name)? {
+            Some(value) if value.value.is_empty() => Ok::<_, FdtError>(Some(None)),
+            Some(value) => Ok(Some(Some(value.as_value::<u32>()?))),
+            None => Ok(None),
+        };
+
+        let bias = if has("bias-disable")? {
+            Some(PinBias::Disable)
+        } else if has("bias-high-impedance")? {
+            Some(PinBias::HighImpedance)
+        } else if has("bias-bus-hold")? {
+            Some(PinBias::BusHold)
+        } else if let Some(ohms) = bias_value("bias-pull-up")? {
+            Some(PinBias::PullUp(ohms))
+        } else if let Some(ohms) = bias_value("bias-pull-down")? {
+            Some(PinBias::PullDown(ohms))
+        } else if has("bias-pull-pin-default")? {
+            Some(PinBias::PullPinDefault)
+        } else {
+            None
+        };
+
+        let drive = if has("drive-push-pull")? {
+            Some(PinDrive::PushPull)
+        } else if has("drive-open-drain")? {
+            Some(PinDrive::OpenDrain)
+        } else if has("drive-open-source")? {
+            Some(PinDrive::OpenSource)
+        } else {
+            None
+        };
+
+        let string_list = |name| match properties.find(name)? {
+            Some(value) => Ok::<_, FdtError>(Some(value.as_value::<StringList<'a>>()?)),
+            None => Ok(None),
+        };
+
+        Ok(Self {
+            pins: string_list("pins")?,
+            groups: string_list("groups")?,
+            function: match properties.find("function")? {
+                Some(function) => Some(function.as_value()?),
+                None => None,
+            },
+            bias,
+            drive,
+            drive_strength: u32_value("drive-strength")?,
+            drive_strength_microamp: u32_value("drive-strength-microamp")?,
+            input_enable: flag("input-enable", "input-disable")?,
+            input_schmitt: flag("input-schmitt-enable", "input-schmitt-disable")?,
+            output_enable: flag("output-enable", "output-disable")?,
+            output_high: flag("output-high", "output-low")?,
+            slew_rate: u32_value("slew-rate")?,
+        })
+    }
+}
+
+/// The bias of a pin, see [`PinConfig::bias`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PinBias {
+    /// `bias-disable`
+    Disable,
+    /// `bias-high-impedance`
+    HighImpedance,
+    /// `bias-bus-hold`
+    BusHold,
+    /// `bias-pull-up`, with the pull-up resistance in Ohms if given.
+    PullUp(Option<u32>),
+    /// `bias-pull-down`, with the pull-down resistance in Ohms if given.
+    PullDown(Option<u32>),
+    /// `bias-pull-pin-default`
+    PullPinDefault,
+}
+
+/// How a pin is driven as an output, see [`PinConfig::drive`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PinDrive {
+    /// `drive-push-pull`
+    PushPull,
+    /// `drive-open-drain`
+    OpenDrain,
+    /// `drive-open-source`
+    OpenSource,
+}
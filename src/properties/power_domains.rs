@@ -0,0 +1,18 @@
+use super::phandle_args::PhandleArgsList;
+
+newtype_property! {
+    /// [Devicetree power domain
+    /// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/power-domain/power-domain-consumer.yaml)
+    /// `power-domains` property
+    ///
+    /// The power domains a device is a member of, as a list of phandles to power
+    /// domain providers each followed by `#power-domain-cells` cells of power
+    /// domain specifier. The entries are named by the `power-domain-names`
+    /// property, if present.
+    ///
+    /// This dereferences to a [`PhandleArgsList`] for iterating over the power
+    /// domains or looking them up by name.
+    pub struct PowerDomains(PhandleArgsList) = |node| {
+        PhandleArgsList::parse(node, "power-domains", "#power-domain-cells", Some("power-domain-names"))
+    };
+}
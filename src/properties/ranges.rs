@@ -37,9 +37,9 @@ impl<'a> Ranges<'a> {
     }
 }
 
-impl<'a, P: ParserWithMode<'a>> Property<'a, P> for Ranges<'a> {
-    fn parse(node: FallibleNode<'a, P>, _: FallibleRoot<'a, P>) -> Result<Option<Self>, FdtError> {
-        let Some(ranges) = node.properties()?.find("ranges")? else {
+impl<'a> Ranges<'a> {
+    fn parse_named<P: ParserWithMode<'a>>(node: FallibleNode<'a, P>, name: &str) -> Result<Option<Self>, FdtError> {
+        let Some(ranges) = node.properties()?.find(name)? else {
             return Ok(None);
         };
 
@@ -51,6 +51,49 @@ impl<'a, P: ParserWithMode<'a>> Property<'a, P> for Ranges<'a> {
     }
 }
 
+impl<'a, P: ParserWithMode<'a>> Property<'a, P> for Ranges<'a> {
+    fn parse(node: FallibleNode<'a, P>, _: FallibleRoot<'a, P>) -> Result<Option<Self>, FdtError> {
+        Self::parse_named::<P>(node, "ranges")
+    }
+}
+
+/// See [`Node::dma_ranges`].
+///
+/// The entries have the same layout as [`Ranges`], which this dereferences
+/// to, but describe how the bus node's children see the parent's address
+/// space when accessing memory, rather than how the parent sees the children.
+///
+/// [`Node::dma_ranges`]: crate::nodes::Node::dma_ranges
+#[derive(Debug, Clone, Copy)]
+pub struct DmaRanges<'a>(Ranges<'a>);
+
+impl<'a> DmaRanges<'a> {
+    /// Create an iterator over the entries in the property value and attempt to
+    /// collect the constituent parts into the specified [`CellCollector`]s.
+    pub fn iter<CAddr, PAddr, Len>(self) -> RangesIter<'a, CAddr, PAddr, Len>
+    where
+        CAddr: CellCollector,
+        PAddr: CellCollector,
+        Len: CellCollector,
+    {
+        self.0.iter()
+    }
+}
+
+impl<'a> core::ops::Deref for DmaRanges<'a> {
+    type Target = Ranges<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Property<'a, P> for DmaRanges<'a> {
+    fn parse(node: FallibleNode<'a, P>, _: FallibleRoot<'a, P>) -> Result<Option<Self>, FdtError> {
+        Ok(Ranges::parse_named::<P>(node, "dma-ranges")?.map(Self))
+    }
+}
+
 #[allow(missing_docs)]
 pub struct RangesIter<'a, CAddr: CellCollector = u64, PAddr: CellCollector = u64, Len: CellCollector = u64> {
     parent_address_cells: AddressCells,
@@ -127,3 +170,247 @@ pub struct Range<CAddr, PAddr, Len> {
     #[allow(missing_docs)]
     pub len: Len,
 }
+
+/// Maximum number of bus nodes an address can be translated through.
+const MAX_TRANSLATION_DEPTH: usize = 16;
+
+/// An error encountered while translating an address into the CPU address
+/// space, such as with [`Node::reg_translated`] or a [`DmaTranslator`].
+///
+/// [`Node::reg_translated`]: crate::nodes::Node::reg_translated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranslationError {
+    /// An ancestor bus node has no `ranges` property, so addresses on the bus
+    /// have no mapping into the address space of its parent.
+    Untranslatable,
+    /// The address, or the buffer starting at it, isn't contained by any of
+    /// the entries in the `ranges` or `dma-ranges` property of an ancestor bus
+    /// node.
+    NotMapped,
+    /// The address passes through more bus nodes than are supported.
+    TooDeep,
+    /// An address or length was too large for the type it was collected into,
+    /// or overflowed while being translated.
+    CollectCellsError,
+}
+
+impl From<CollectCellsError> for TranslationError {
+    fn from(_: CollectCellsError) -> Self {
+        Self::CollectCellsError
+    }
+}
+
+impl core::fmt::Display for TranslationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TranslationError::Untranslatable => write!(f, "a parent bus has no `ranges` property"),
+            TranslationError::NotMapped => {
+                write!(f, "address is not within any `ranges` or `dma-ranges` entry of a parent bus")
+            }
+            TranslationError::TooDeep => write!(f, "address passes through too many parent buses"),
+            TranslationError::CollectCellsError => write!(f, "address or length overflowed while translating"),
+        }
+    }
+}
+
+/// Translates addresses on the bus a node is located on into the CPU address
+/// space, using the `ranges` or `dma-ranges` property of each bus node between
+/// the node and the root.
+#[derive(Clone, Copy)]
+pub(crate) struct AddressTranslator<'a> {
+    /// The ranges of each bus node from the innermost outwards, along with
+    /// whether the bus is a PCI bus.
+    buses: [Option<(Ranges<'a>, bool)>; MAX_TRANSLATION_DEPTH],
+    /// Number of buses in `buses` to translate through.
+    depth: usize,
+    /// Whether this is translating DMA addresses using `dma-ranges`.
+    dma: bool,
+    /// Number of cells in a translated address.
+    pub(crate) address_cells: usize,
+    error: Option<TranslationError>,
+}
+
+impl<'a> AddressTranslator<'a> {
+    /// Create a translator using the `ranges` property of each bus, where a
+    /// bus without `ranges` can't be translated through.
+    pub(crate) fn new<P: ParserWithMode<'a>>(node: FallibleNode<'a, P>) -> Result<Self, FdtError> {
+        Self::with_property::<P>(node, false)
+    }
+
+    /// Create a translator using the `dma-ranges` property of each bus, where
+    /// a bus without `dma-ranges` maps addresses unchanged.
+    pub(crate) fn new_dma<P: ParserWithMode<'a>>(node: FallibleNode<'a, P>) -> Result<Self, FdtError> {
+        Self::with_property::<P>(node, true)
+    }
+
+    fn with_property<P: ParserWithMode<'a>>(node: FallibleNode<'a, P>, dma: bool) -> Result<Self, FdtError> {
+        let mut this = Self { buses: [None; MAX_TRANSLATION_DEPTH], depth: 0, dma, address_cells: 2, error: None };
+        let Some(mut bus) = node.full_parent()? else { return Ok(this) };
+
+        let mut cell_sizes = bus.property::<CellSizes>()?.unwrap_or_default();
+        this.address_cells = cell_sizes.address_cells;
+
+        for depth in 0.. {
+            // The root node's children are already in the CPU address space
+            let Some(parent) = bus.full_parent()? else { break };
+
+            let ranges = match bus.properties()?.find(if dma { "dma-ranges" } else { "ranges" })? {
+                Some(ranges) => ranges.value,
+                None if dma => &[],
+                None => {
+                    this.error = Some(TranslationError::Untranslatable);
+                    break;
+                }
+            };
+
+            let Some(slot) = this.buses.get_mut(depth) else {
+                this.error = Some(TranslationError::TooDeep);
+                break;
+            };
+
+            let parent_address_cells = parent.property::<AddressCells>()?.unwrap_or_default();
+            let pci = matches!(bus.properties()?.find("device_type")?, Some(ty) if ty.as_value::<&str>()? == "pci");
+            *slot = Some((Ranges { parent_address_cells, cell_sizes, ranges }, pci));
+            this.depth = depth + 1;
+
+            this.address_cells = parent_address_cells.0;
+            cell_sizes = parent.property::<CellSizes>()?.unwrap_or_default();
+            bus = parent;
+        }
+
+        Ok(this)
+    }
+
+    /// Translate `address` on the node's bus into the CPU address space. Only
+    /// the address itself needs to be contained by an entry at each bus, as
+    /// with a `reg` region whose start is all that gets translated.
+    pub(crate) fn translate(&self, address: u128) -> Result<u128, TranslationError> {
+        self.translate_region(address, 1)
+    }
+
+    /// Translate the region of `len` bytes at `address` on the node's bus into
+    /// the CPU address space. Unlike [`AddressTranslator::translate`], the
+    /// whole region must be contained by a single entry at each bus.
+    pub(crate) fn translate_region(&self, address: u128, len: u128) -> Result<u128, TranslationError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        self.buses().try_fold(address, |address, &(ranges, pci)| self.translate_one(ranges, pci, address, len, false))
+    }
+
+    /// Translate the region of `len` bytes at `address` in the CPU address
+    /// space into an address on the node's bus, the reverse of
+    /// [`AddressTranslator::translate_region`].
+    pub(crate) fn translate_reverse(&self, address: u128, len: u128) -> Result<u128, TranslationError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        self.buses()
+            .rev()
+            .try_fold(address, |address, &(ranges, pci)| self.translate_one(ranges, pci, address, len, true))
+    }
+
+    /// The ranges of each bus to translate through, from the innermost
+    /// outwards.
+    fn buses(&self) -> impl DoubleEndedIterator<Item = &(Ranges<'a>, bool)> {
+        self.buses[..self.depth].iter().flatten()
+    }
+
+    fn translate_one(
+        &self,
+        ranges: Ranges<'_>,
+        pci: bool,
+        address: u128,
+        len: u128,
+        reverse: bool,
+    ) -> Result<u128, TranslationError> {
+        // An empty `ranges` maps the child address space directly onto the
+        // parent's
+        if ranges.ranges.is_empty() {
+            return Ok(address);
+        }
+
+        for range in ranges.iter::<u128, u128, u128>() {
+            let range = range?;
+
+            // PCI addresses only match on the address space code in the high
+            // cell, since the remaining bits of it describe the device and its
+            // properties. DMA addresses are plain bus addresses, so only the
+            // lower cells are used.
+            let (child_bus_address, child_address) = match pci {
+                true if !self.dma && !reverse && (range.child_bus_address >> 88) & 0b11 != (address >> 88) & 0b11 => {
+                    continue
+                }
+                true => (range.child_bus_address as u64 as u128, address as u64 as u128),
+                false => (range.child_bus_address, address),
+            };
+
+            let (from, to, address) = match reverse {
+                false => (child_bus_address, range.parent_bus_address, child_address),
+                true => (range.parent_bus_address, child_bus_address, address),
+            };
+
+            let Some(offset) = address.checked_sub(from) else { continue };
+            if offset < range.len && range.len - offset >= len {
+                return to.checked_add(offset).ok_or(CollectCellsError.into());
+            }
+        }
+
+        Err(TranslationError::NotMapped)
+    }
+}
+
+/// Translates between CPU physical addresses and the addresses a device uses
+/// for DMA, created with [`Node::dma_translator`].
+///
+/// The `dma-ranges` property of every bus node between the device and the
+/// root is applied in turn, and bus nodes without a `dma-ranges` property are
+/// treated as mapping addresses unchanged. An empty `dma-ranges` property
+/// likewise maps the bus's address space directly onto its parent's.
+///
+/// [`Node::dma_translator`]: crate::nodes::Node::dma_translator
+#[derive(Clone, Copy)]
+pub struct DmaTranslator<'a> {
+    translator: AddressTranslator<'a>,
+}
+
+impl<'a> DmaTranslator<'a> {
+    pub(crate) fn new<P: ParserWithMode<'a>>(node: FallibleNode<'a, P>) -> Result<Self, FdtError> {
+        Ok(Self { translator: AddressTranslator::new_dma::<P>(node)? })
+    }
+
+    /// Translate a buffer of `len` bytes at the DMA address `dma_address`, as
+    /// seen by the device, into a CPU physical address. Returns
+    /// [`TranslationError::NotMapped`] if the buffer isn't entirely within a
+    /// DMA window of each bus.
+    pub fn to_cpu(&self, dma_address: u64, len: u64) -> Result<u64, TranslationError> {
+        let address = self.translator.translate_region(dma_address.into(), len.into())?;
+        u64::try_from(address).map_err(|_| TranslationError::CollectCellsError)
+    }
+
+    /// Translate a buffer of `len` bytes at the CPU physical address
+    /// `cpu_address` into the DMA address the device must use to access it.
+    /// Returns [`TranslationError::NotMapped`] if the buffer isn't entirely
+    /// within a DMA window of each bus.
+    pub fn to_dma(&self, cpu_address: u64, len: u64) -> Result<u64, TranslationError> {
+        let address = self.translator.translate_reverse(cpu_address.into(), len.into())?;
+        u64::try_from(address).map_err(|_| TranslationError::CollectCellsError)
+    }
+}
+
+/// Collect an address which was translated into a [`u128`] into the given
+/// [`CellCollector`] as `cells` cells.
+pub(crate) fn collect_address<C: CellCollector>(address: u128, cells: usize) -> Result<C::Output, CollectCellsError> {
+    if cells < 4 && address >> (cells * 32) != 0 {
+        return Err(CollectCellsError);
+    }
+
+    let mut collector = C::Builder::default();
+    for cell in (0..cells).rev() {
+        collector.push(address.checked_shr(cell as u32 * 32).unwrap_or(0) as u32)?;
+    }
+
+    Ok(C::map(collector.finish()))
+}
@@ -1,4 +1,8 @@
-use super::{cells::CellSizes, Property};
+use super::{
+    cells::CellSizes,
+    ranges::{collect_address, AddressTranslator, TranslationError},
+    Property,
+};
 use crate::{
     cell_collector::{BuildCellCollector, CellCollector, CollectCellsError},
     helpers::{FallibleNode, FallibleRoot},
@@ -35,9 +39,14 @@ impl<'a> Reg<'a> {
     }
 }
 
-impl<'a, P: ParserWithMode<'a>> Property<'a, P> for Reg<'a> {
-    fn parse(node: FallibleNode<'a, P>, _: FallibleRoot<'a, P>) -> Result<Option<Self>, FdtError> {
-        let Some(prop) = node.raw_property("reg")? else {
+impl<'a> Reg<'a> {
+    /// Parse a property named `name` with the same layout as `reg`, such as
+    /// `alloc-ranges`.
+    pub(crate) fn parse_named<P: ParserWithMode<'a>>(
+        node: FallibleNode<'a, P>,
+        name: &str,
+    ) -> Result<Option<Self>, FdtError> {
+        let Some(prop) = node.raw_property(name)? else {
             return Ok(None);
         };
 
@@ -56,6 +65,12 @@ impl<'a, P: ParserWithMode<'a>> Property<'a, P> for Reg<'a> {
     }
 }
 
+impl<'a, P: ParserWithMode<'a>> Property<'a, P> for Reg<'a> {
+    fn parse(node: FallibleNode<'a, P>, _: FallibleRoot<'a, P>) -> Result<Option<Self>, FdtError> {
+        Self::parse_named::<P>(node, "reg")
+    }
+}
+
 /// An individual entry in a [`Reg`] property.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct RegEntry<Addr, Len> {
@@ -138,6 +153,40 @@ pub struct RawRegEntry<'a> {
     pub len: &'a [u8],
 }
 
+/// Iterator over the entries of a [`Reg`] property with their addresses
+/// translated into the CPU address space. See [`Node::reg_translated`].
+///
+/// [`Node::reg_translated`]: crate::nodes::Node::reg_translated
+pub struct TranslatedRegIter<'a, Addr: CellCollector, Len: CellCollector> {
+    translator: AddressTranslator<'a>,
+    entries: RegIter<'a, u128, Len>,
+    _collector: core::marker::PhantomData<*mut Addr>,
+}
+
+impl<'a, Addr: CellCollector, Len: CellCollector> TranslatedRegIter<'a, Addr, Len> {
+    pub(crate) fn new<P: ParserWithMode<'a>>(node: FallibleNode<'a, P>, reg: Reg<'a>) -> Result<Self, FdtError> {
+        Ok(Self {
+            translator: AddressTranslator::new::<P>(node)?,
+            entries: reg.iter(),
+            _collector: core::marker::PhantomData,
+        })
+    }
+}
+
+impl<'a, Addr: CellCollector, Len: CellCollector> Iterator for TranslatedRegIter<'a, Addr, Len> {
+    type Item = Result<RegEntry<Addr::Output, Len::Output>, TranslationError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.entries.next()?;
+
+        Some(crate::tryblock!(TranslationError, {
+            let entry = entry?;
+            let address = self.translator.translate(entry.address)?;
+
+            Ok(RegEntry { address: collect_address::<Addr>(address, self.translator.address_cells)?, len: entry.len })
+        }))
+    }
+}
+
 /// [Devicetree 2.3.7.
 /// `virtual-reg`](https://devicetree-specification.readthedocs.io/en/latest/chapter2-devicetree-basics.html#virtual-reg)
 ///
@@ -0,0 +1,124 @@
+use super::PHandle;
+use crate::{
+    helpers::{FallibleNode, FallibleParser, FallibleRoot},
+    nodes::{root::Root, Node, NodePropertiesIter},
+    parsing::{aligned::AlignedParser, Panic, ParserWithMode},
+    FdtError,
+};
+
+/// [Devicetree regulator
+/// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/regulator/regulator.yaml)
+///
+/// The constraints of a regulator, decoded from the `regulator-*` properties
+/// of the regulator node. See [`Node::regulator_constraints`].
+///
+/// [`Node::regulator_constraints`]: crate::nodes::Node::regulator_constraints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegulatorConstraints<'a> {
+    /// The name of the regulator output, from `regulator-name`.
+    pub name: Option<&'a str>,
+    /// The smallest voltage consumers may set, from
+    /// `regulator-min-microvolt`.
+    pub min_microvolt: Option<u32>,
+    /// The largest voltage consumers may set, from `regulator-max-microvolt`.
+    pub max_microvolt: Option<u32>,
+    /// The regulator should never be disabled, from `regulator-always-on`.
+    pub always_on: bool,
+    /// The regulator was enabled by the bootloader and may already be
+    /// powering devices, from `regulator-boot-on`.
+    pub boot_on: bool,
+}
+
+impl<'a> RegulatorConstraints<'a> {
+    pub(crate) fn parse<P: ParserWithMode<'a>>(node: FallibleNode<'a, P>) -> Result<Self, FdtError> {
+        let properties = node.properties()?;
+        let microvolt = |name| match properties.find(name)? {
+            Some(value) => Ok::<_, FdtError>(Some(value.as_value::<u32>()?)),
+            None => Ok(None),
+        };
+
+        Ok(Self {
+            name: match properties.find("regulator-name")? {
+                Some(name) => Some(name.as_value()?),
+                None => None,
+            },
+            min_microvolt: microvolt("regulator-min-microvolt")?,
+            max_microvolt: microvolt("regulator-max-microvolt")?,
+            always_on: properties.find("regulator-always-on")?.is_some(),
+            boot_on: properties.find("regulator-boot-on")?.is_some(),
+        })
+    }
+}
+
+/// Resolve the phandle of a `*-supply` property to the regulator node.
+pub(crate) fn resolve_supply<'a, P: ParserWithMode<'a>>(
+    root: FallibleRoot<'a, P>,
+    value: &'a [u8],
+) -> Result<FallibleNode<'a, P>, FdtError> {
+    let phandle = u32::from_be_bytes(value.try_into().map_err(|_| FdtError::InvalidPropertyValue)?);
+    match root.resolve_phandle(PHandle::new(phandle))? {
+        Some(regulator) => Ok(regulator),
+        None => Err(FdtError::MissingPHandleNode(phandle)),
+    }
+}
+
+/// See [`Node::supplies`].
+///
+/// [`Node::supplies`]: crate::nodes::Node::supplies
+pub struct SuppliesIter<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    pub(crate) root: Root<'a, P>,
+    pub(crate) properties: Option<NodePropertiesIter<'a, FallibleParser<'a, P>>>,
+}
+
+impl<'a, P: ParserWithMode<'a>> SuppliesIter<'a, P> {
+    fn next_supply(&mut self) -> Result<Option<Supply<'a, P>>, FdtError> {
+        let Some(properties) = self.properties.as_mut() else { return Ok(None) };
+        for property in properties {
+            let property = property?;
+            let Some(name) = property.name.strip_suffix("-supply") else { continue };
+
+            let root: FallibleRoot<'a, P> = Root { node: self.root.node.fallible() };
+            let regulator = resolve_supply::<P>(root, property.value)?;
+            return Ok(Some(Supply { name, regulator: regulator.alt() }));
+        }
+
+        Ok(None)
+    }
+}
+
+impl<'a, P: ParserWithMode<'a>> Iterator for SuppliesIter<'a, P> {
+    type Item = P::Output<Supply<'a, P>>;
+
+    #[track_caller]
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = self.next_supply();
+        if !matches!(res, Ok(Some(_))) {
+            self.properties = None;
+        }
+
+        // This is a manual impl of `map` because we need the panic location to
+        // be the caller if `P::to_output` panics
+        #[allow(clippy::manual_map)]
+        match res.transpose() {
+            Some(output) => Some(P::to_output(output)),
+            None => None,
+        }
+    }
+}
+
+/// A `*-supply` property of a device, giving the regulator which powers one
+/// of its supply inputs.
+pub struct Supply<'a, P: ParserWithMode<'a> = (AlignedParser<'a>, Panic)> {
+    /// The name of the supply, which is the property name without the
+    /// `-supply` suffix, such as `vdd` for `vdd-supply`.
+    pub name: &'a str,
+    /// The regulator node.
+    pub regulator: Node<'a, P>,
+}
+
+impl<'a, P: ParserWithMode<'a>> Copy for Supply<'a, P> {}
+impl<'a, P: ParserWithMode<'a>> Clone for Supply<'a, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
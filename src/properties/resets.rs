@@ -0,0 +1,17 @@
+use super::phandle_args::PhandleArgsList;
+
+newtype_property! {
+    /// [Devicetree reset
+    /// binding](https://github.com/devicetree-org/dt-schema/blob/main/dtschema/schemas/reset/reset.yaml)
+    /// `resets` property
+    ///
+    /// The reset lines of a device, as a list of phandles to reset controllers
+    /// each followed by `#reset-cells` cells of reset specifier. The entries are
+    /// named by the `reset-names` property, if present.
+    ///
+    /// This dereferences to a [`PhandleArgsList`] for iterating over the resets
+    /// or looking them up by name.
+    pub struct Resets(PhandleArgsList) = |node| {
+        PhandleArgsList::parse(node, "resets", "#reset-cells", Some("reset-names"))
+    };
+}
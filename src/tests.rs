@@ -5,10 +5,10 @@
 extern crate std;
 
 use crate::*;
-use nodes::{AsNode, NodeName};
+use nodes::{AsNode, Node, NodeName};
 use properties::{
     cells::CellSizes,
-    interrupts::{InterruptCells, InterruptMap, Interrupts},
+    interrupts::{InterruptCells, InterruptMap, InterruptMapMask, Interrupts},
     pci::{PciAddress, PciAddressHighBits},
     ranges::Range,
     reg::{RawRegEntry, RegEntry},
@@ -308,6 +308,1393 @@ fn interrupt_map() {
         assert_eq!(entry.parent_unit_address, expected.2);
         assert_eq!(entry.parent_interrupt_specifier, expected.3);
     }
+
+    let pci = root.find_node("/soc/pci").unwrap();
+    let mask = pci.property::<InterruptMapMask<PciAddress, u64>>().unwrap();
+    let map = || pci.property::<InterruptMap<PciAddress, u64, Option<u64>, u64>>().unwrap();
+    let (address, specifier) = mask.mask(PciAddress { hi: PciAddressHighBits::new(0x1fff), mid: 1, lo: 1 }, 0xff);
+    assert_eq!((address, specifier), (PciAddress { hi: PciAddressHighBits::new(0x1800), mid: 0, lo: 0 }, 7));
+    assert_eq!(map().iter_masked(mask).count(), entries.len());
+
+    let entry = map().find(PciAddress { hi: PciAddressHighBits::new(4096), mid: 0, lo: 0 }, 3).unwrap();
+    assert_eq!(entry.interrupt_parent.name().name, "plic");
+    assert_eq!(entry.parent_interrupt_specifier, 32);
+}
+
+#[test]
+fn resolve_interrupt() {
+    let fdt = Fdt::new(TEST.as_slice()).unwrap();
+    let plic = fdt.find_node("/soc/plic@c000000").unwrap();
+
+    let uart = fdt.find_node("/soc/uart@10000000").unwrap().resolve_interrupt(0).unwrap();
+    assert_eq!(uart.controller.name(), plic.name());
+    assert_eq!(&*uart.specifier, &[0xa]);
+    assert!(fdt.find_node("/soc/uart@10000000").unwrap().resolve_interrupt(1).is_none());
+
+    let clint = fdt.find_node("/soc/clint@2000000").unwrap().resolve_interrupt(1).unwrap();
+    assert_eq!(clint.controller.name().name, "interrupt-controller");
+    assert_eq!(&*clint.specifier, &[7]);
+
+    let pci = fdt.find_node("/soc/pci@30000000").unwrap();
+    let intb = pci.map_interrupt(&[0x800, 0, 0], &[2]);
+    assert_eq!(intb.controller.name(), plic.name());
+    assert_eq!(&*intb.specifier, &[0x22]);
+    // The function number and register are masked out by `interrupt-map-mask`
+    assert_eq!(&*pci.map_interrupt(&[0x1900, 0, 0x10], &[1]).specifier, &[0x23]);
+
+    let fdt = Fdt::new_fallible(TEST.as_slice()).unwrap();
+    let pci = fdt.find_node("/soc/pci@30000000").unwrap().unwrap();
+    assert!(matches!(pci.map_interrupt(&[0, 0, 0], &[5]), Err(FdtError::UnresolvedInterrupt)));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn resolve_interrupt_nexus_chain() {
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            #address-cells = <1>;
+            #size-cells = <1>;
+
+            gic: interrupt-controller@1000 {
+                interrupt-controller;
+                #interrupt-cells = <3>;
+                #address-cells = <0>;
+            };
+
+            soc {
+                #address-cells = <1>;
+                #size-cells = <1>;
+                interrupt-parent = <&gic>;
+
+                timer@2000 {
+                    reg = <0x2000 0x100>;
+                    interrupts = <0 5 4>, <0 6 4>;
+                };
+
+                bridge: nexus {
+                    #address-cells = <0>;
+                    #interrupt-cells = <1>;
+                    interrupt-map-mask = <0xff>;
+                    interrupt-map = <1 &gic 0 40 4>, <2 &gic 0 41 4>;
+                };
+
+                pci@3000 {
+                    #address-cells = <3>;
+                    #size-cells = <2>;
+                    #interrupt-cells = <1>;
+                    reg = <0x3000 0x1000>;
+                    interrupt-map-mask = <0xf800 0 0 7>;
+                    interrupt-map = <0x0800 0 0 1 &bridge 2>, <0x1000 0 0 1 &bridge 0x101>;
+
+                    ethernet@1,0 {
+                        reg = <0x0800 0 0 0 0>;
+                        interrupts = <1>;
+                    };
+
+                    usb@2,0 {
+                        reg = <0x1000 0 0 0 0>;
+                        interrupts = <1>;
+                    };
+
+                    sound@3,0 {
+                        reg = <0x1800 0 0 0 0>;
+                        interrupts = <1>;
+                    };
+                };
+
+                mixed {
+                    interrupts-extended = <&gic 0 7 1>, <&bridge 1>;
+                };
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+
+    // `interrupt-parent` is inherited from the bus node
+    let timer = fdt.find_node("/soc/timer@2000").unwrap().resolve_interrupt(1).unwrap();
+    assert_eq!(timer.controller.name().unit_address, Some("1000"));
+    assert_eq!(&*timer.specifier, &[0, 6, 4]);
+
+    // PCI device -> PCI host bridge nexus -> second nexus -> controller
+    let ethernet = fdt.find_node("/soc/pci@3000/ethernet@1,0").unwrap().resolve_interrupt(0).unwrap();
+    assert_eq!(&*ethernet.specifier, &[0, 41, 4]);
+    let usb = fdt.find_node("/soc/pci@3000/usb@2,0").unwrap().resolve_interrupt(0).unwrap();
+    assert_eq!(&*usb.specifier, &[0, 40, 4]);
+
+    let mixed = fdt.find_node("/soc/mixed").unwrap();
+    assert_eq!(&*mixed.resolve_interrupt(0).unwrap().specifier, &[0, 7, 1]);
+    assert_eq!(&*mixed.resolve_interrupt(1).unwrap().specifier, &[0, 40, 4]);
+    assert!(mixed.resolve_interrupt(2).is_none());
+
+    let fdt = Fdt::new_fallible(&blob).unwrap();
+    let sound = fdt.find_node("/soc/pci@3000/sound@3,0").unwrap().unwrap();
+    assert!(matches!(sound.resolve_interrupt(0), Err(FdtError::UnresolvedInterrupt)));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn reg_translated() {
+    use properties::ranges::TranslationError;
+
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            #address-cells = <2>;
+            #size-cells = <2>;
+
+            soc {
+                #address-cells = <1>;
+                #size-cells = <1>;
+                ranges = <0x0 0x0 0xf0000000 0x100000>;
+
+                serial@4600 {
+                    reg = <0x4600 0x100>, <0x4800 0x10>;
+                };
+
+                outside@200000 {
+                    reg = <0x200000 0x10>;
+                };
+
+                bus@1000 {
+                    #address-cells = <1>;
+                    #size-cells = <1>;
+                    ranges = <0x100 0x1000 0x100>;
+
+                    device@110 {
+                        reg = <0x110 0x4>;
+                    };
+                };
+
+                identity {
+                    #address-cells = <1>;
+                    #size-cells = <1>;
+                    ranges;
+
+                    device@8000 {
+                        reg = <0x8000 0x4>;
+                    };
+                };
+
+                isolated {
+                    #address-cells = <1>;
+                    #size-cells = <1>;
+
+                    device@0 {
+                        reg = <0x0 0x4>;
+                    };
+                };
+            };
+
+            pci@40000000 {
+                device_type = "pci";
+                #address-cells = <3>;
+                #size-cells = <2>;
+                ranges = <0x02000000 0x0 0x40000000 0x0 0x40000000 0x0 0x10000000>;
+
+                ethernet@1,0 {
+                    reg = <0x02000810 0x0 0x40001000 0x0 0x1000>, <0x01000800 0x0 0x10 0x0 0x10>;
+                };
+            };
+
+            wide {
+                #address-cells = <2>;
+                #size-cells = <1>;
+                ranges = <0x0 0x0 0x1 0x0 0x1000>;
+
+                device@0,10 {
+                    reg = <0x0 0x10 0x10>;
+                };
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let translated =
+        |path: &str| fdt.find_node(path).unwrap().reg_translated::<u64, u64>().unwrap().collect::<std::vec::Vec<_>>();
+
+    assert_eq!(
+        translated("/soc/serial@4600"),
+        [Ok(RegEntry { address: 0xf000_4600, len: 0x100 }), Ok(RegEntry { address: 0xf000_4800, len: 0x10 })]
+    );
+    assert_eq!(translated("/soc/outside@200000"), [Err(TranslationError::NotMapped)]);
+    assert_eq!(translated("/soc/bus@1000/device@110"), [Ok(RegEntry { address: 0xf000_1010, len: 4 })]);
+    assert_eq!(translated("/soc/identity/device@8000"), [Ok(RegEntry { address: 0xf000_8000, len: 4 })]);
+    assert_eq!(translated("/soc/isolated/device@0"), [Err(TranslationError::Untranslatable)]);
+    assert_eq!(
+        translated("/pci@40000000/ethernet@1,0"),
+        [Ok(RegEntry { address: 0x4000_1000, len: 0x1000 }), Err(TranslationError::NotMapped)]
+    );
+    assert_eq!(translated("/wide/device@0,10"), [Ok(RegEntry { address: 0x1_0000_0010, len: 0x10 })]);
+
+    let wide = fdt.find_node("/wide/device@0,10").unwrap();
+    assert_eq!(wide.reg_translated::<u32, u32>().unwrap().next(), Some(Err(TranslationError::CollectCellsError)));
+    assert!(fdt.find_node("/soc").unwrap().reg_translated::<u64, u64>().is_none());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn translate_region_straddling_ranges() {
+    use properties::ranges::TranslationError;
+
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            #address-cells = <1>;
+            #size-cells = <1>;
+
+            bus {
+                #address-cells = <1>;
+                #size-cells = <1>;
+                ranges = <0x0 0x10000 0x1000>, <0x1000 0x80000 0x1000>;
+                dma-ranges = <0x0 0x10000 0x1000>, <0x1000 0x80000 0x1000>;
+
+                device@f00 {
+                    reg = <0xf00 0x200>;
+                };
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let device = fdt.find_node("/bus/device@f00").unwrap();
+
+    // Only the start of a `reg` region is translated, as it is by Linux, so a
+    // region running past the end of its `ranges` entry still translates
+    assert_eq!(
+        device.reg_translated::<u64, u64>().unwrap().collect::<std::vec::Vec<_>>(),
+        [Ok(RegEntry { address: 0x10f00, len: 0x200 })]
+    );
+
+    // A DMA buffer must fit entirely within a single `dma-ranges` entry
+    let dma = device.dma_translator();
+    assert_eq!(dma.to_cpu(0xf00, 0x100), Ok(0x10f00));
+    assert_eq!(dma.to_cpu(0xf00, 0x200), Err(TranslationError::NotMapped));
+    assert_eq!(dma.to_dma(0x10f00, 0x200), Err(TranslationError::NotMapped));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn dma_translator() {
+    use properties::ranges::TranslationError;
+
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            #address-cells = <2>;
+            #size-cells = <2>;
+
+            soc {
+                #address-cells = <1>;
+                #size-cells = <1>;
+                ranges;
+                dma-ranges = <0x0 0x0 0x80000000 0x40000000>, <0x40000000 0x1 0x0 0x10000000>;
+
+                dma@1000 {
+                    reg = <0x1000 0x100>;
+                };
+
+                bus {
+                    #address-cells = <1>;
+                    #size-cells = <1>;
+                    ranges;
+
+                    device@0 {
+                        reg = <0x0 0x4>;
+                    };
+                };
+            };
+
+            plain {
+                #address-cells = <1>;
+                #size-cells = <1>;
+                ranges;
+
+                device@0 {
+                    reg = <0x0 0x4>;
+                };
+            };
+
+            pci@40000000 {
+                device_type = "pci";
+                #address-cells = <3>;
+                #size-cells = <2>;
+                dma-ranges = <0x02000000 0x0 0x0 0x0 0x80000000 0x0 0x80000000>;
+
+                ethernet@1,0 {
+                    reg = <0x00000800 0x0 0x0 0x0 0x0>;
+                };
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+
+    let soc = fdt.find_node("/soc").unwrap().dma_ranges().unwrap();
+    assert_eq!(
+        soc.iter::<u32, u64, u32>().collect::<Result<std::vec::Vec<_>, _>>().unwrap(),
+        [
+            Range { child_bus_address: 0x0, parent_bus_address: 0x8000_0000, len: 0x4000_0000 },
+            Range { child_bus_address: 0x4000_0000, parent_bus_address: 0x1_0000_0000, len: 0x1000_0000 },
+        ]
+    );
+    assert!(fdt.find_node("/plain").unwrap().dma_ranges().is_none());
+
+    let dma = fdt.find_node("/soc/dma@1000").unwrap().dma_translator();
+    assert_eq!(dma.to_cpu(0x1000, 0x1000), Ok(0x8000_1000));
+    assert_eq!(dma.to_cpu(0x4000_0010, 0x10), Ok(0x1_0000_0010));
+    assert_eq!(dma.to_dma(0x8000_1000, 0x1000), Ok(0x1000));
+    assert_eq!(dma.to_dma(0x1_0000_0010, 0x10), Ok(0x4000_0010));
+    assert_eq!(dma.to_dma(0x1000, 0x10), Err(TranslationError::NotMapped));
+    assert_eq!(dma.to_dma(0xbfff_fff0, 0x20), Err(TranslationError::NotMapped));
+    assert_eq!(dma.to_cpu(0x5000_0000, 0x1), Err(TranslationError::NotMapped));
+
+    let nested = fdt.find_node("/soc/bus/device@0").unwrap().dma_translator();
+    assert_eq!(nested.to_dma(0x8000_0000, 0x4000_0000), Ok(0x0));
+    assert_eq!(nested.to_cpu(0x3fff_ffff, 0x1), Ok(0xbfff_ffff));
+
+    let plain = fdt.find_node("/plain/device@0").unwrap().dma_translator();
+    assert_eq!(plain.to_dma(0x1234_5678, 0x1000), Ok(0x1234_5678));
+    assert_eq!(plain.to_cpu(0x1234_5678, 0x1000), Ok(0x1234_5678));
+
+    let pci = fdt.find_node("/pci@40000000/ethernet@1,0").unwrap().dma_translator();
+    assert_eq!(pci.to_dma(0x8000_4000, 0x1000), Ok(0x4000));
+    assert_eq!(pci.to_cpu(0x4000, 0x1000), Ok(0x8000_4000));
+    assert_eq!(pci.to_dma(0x4000, 0x1000), Err(TranslationError::NotMapped));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn phandle_args_list() {
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            osc: oscillator {
+                #clock-cells = <0>;
+            };
+
+            pll: clock-controller {
+                #clock-cells = <1>;
+            };
+
+            gpio: gpio-controller {
+                #gpio-cells = <2>;
+            };
+
+            device {
+                clocks = <&osc>, <&pll 1>, <&pll 2>;
+                clock-names = "ref", "core", "bus";
+                cs-gpios = <&gpio 4 1>, <0>, <&gpio 6 0>;
+                resets = <&osc 1>;
+                dmas = <&pll 1>, <&pll>;
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let device = fdt.find_node("/device").unwrap();
+
+    let clocks = device.phandle_args_list("clocks", "#clock-cells", Some("clock-names")).unwrap();
+    let entries =
+        clocks.iter().map(|c| (c.provider.unwrap().name().name, c.args.to_vec(), c.name)).collect::<std::vec::Vec<_>>();
+    assert_eq!(
+        entries,
+        [
+            ("oscillator", std::vec![], Some("ref")),
+            ("clock-controller", std::vec![1], Some("core")),
+            ("clock-controller", std::vec![2], Some("bus")),
+        ]
+    );
+    assert_eq!(clocks.names().unwrap().collect::<std::vec::Vec<_>>(), ["ref", "core", "bus"]);
+    assert_eq!(clocks.find("bus").unwrap().args.as_slice(), [2]);
+    assert!(clocks.find("missing").is_none());
+    assert_eq!(clocks.get(1).unwrap().name, Some("core"));
+    assert!(clocks.get(3).is_none());
+
+    let gpios = device.phandle_args_list("cs-gpios", "#gpio-cells", None).unwrap();
+    let entries = gpios.iter().map(|g| (g.provider.is_some(), g.args)).collect::<std::vec::Vec<_>>();
+    assert_eq!(
+        entries,
+        [
+            (true, cell_collector::CellArray::new(&[4, 1]).unwrap()),
+            (false, cell_collector::CellArray::default()),
+            (true, cell_collector::CellArray::new(&[6, 0]).unwrap())
+        ]
+    );
+    assert!(gpios.names().is_none());
+    assert!(device.phandle_args_list("pwms", "#pwm-cells", None).is_none());
+
+    let fdt = Fdt::new_fallible(&blob).unwrap();
+    let device = fdt.find_node("/device").unwrap().unwrap();
+    let resets = device.phandle_args_list("resets", "#reset-cells", None).unwrap().unwrap();
+    assert!(matches!(resets.get(0), Err(FdtError::MissingRequiredProperty("#reset-cells"))));
+
+    let mut dmas = device.phandle_args_list("dmas", "#clock-cells", None).unwrap().unwrap().iter();
+    assert_eq!(dmas.next().unwrap().unwrap().args.as_slice(), [1]);
+    assert!(matches!(dmas.next(), Some(Err(FdtError::InvalidPropertyValue))));
+    assert!(dmas.next().is_none());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn clocks() {
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            osc: oscillator {
+                compatible = "fixed-clock";
+                #clock-cells = <0>;
+                clock-frequency = <24000000>;
+            };
+
+            pll: pll {
+                compatible = "fixed-factor-clock";
+                #clock-cells = <0>;
+                clocks = <&osc>;
+                clock-mult = <50>;
+                clock-div = <1>;
+            };
+
+            half: half {
+                compatible = "fixed-factor-clock";
+                #clock-cells = <0>;
+                clocks = <&pll>;
+                clock-mult = <1>;
+                clock-div = <2>;
+            };
+
+            ccu: clock-controller {
+                compatible = "vendor,ccu";
+                #clock-cells = <1>;
+            };
+
+            loop: loop {
+                compatible = "fixed-factor-clock";
+                #clock-cells = <0>;
+                clocks = <&loop>;
+                clock-mult = <1>;
+                clock-div = <1>;
+            };
+
+            device {
+                clocks = <&ccu 3>, <&half>;
+                clock-names = "bus", "core";
+                assigned-clocks = <&ccu 3>, <&ccu 4>;
+                assigned-clock-parents = <0>, <&pll>;
+                assigned-clock-rates = <100000000>, <0>;
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let device = fdt.find_node("/device").unwrap();
+
+    assert_eq!(device.clocks().unwrap().iter().count(), 2);
+    assert_eq!(device.clock_names().unwrap().iter().collect::<std::vec::Vec<_>>(), ["bus", "core"]);
+    assert_eq!(device.clock_names().unwrap().position("core"), Some(1));
+
+    let bus = device.clock("bus").unwrap();
+    assert_eq!(bus.provider.unwrap().name().name, "clock-controller");
+    assert_eq!(bus.args.as_slice(), [3]);
+    assert!(device.clock("missing").is_none());
+
+    let core = device.clock("core").unwrap().provider.unwrap();
+    assert_eq!(core.fixed_clock_rate(), Some(600_000_000));
+    assert_eq!(fdt.find_node("/oscillator").unwrap().fixed_clock_rate(), Some(24_000_000));
+    assert_eq!(bus.provider.unwrap().fixed_clock_rate(), None);
+    assert_eq!(fdt.find_node("/loop").unwrap().fixed_clock_rate(), None);
+
+    let assigned = device.assigned_clocks().unwrap().iter().collect::<std::vec::Vec<_>>();
+    assert_eq!(assigned.len(), 2);
+    assert_eq!(assigned[0].clock.args.as_slice(), [3]);
+    assert!(assigned[0].parent.is_none());
+    assert_eq!(assigned[0].rate, Some(100_000_000));
+    assert_eq!(assigned[1].clock.args.as_slice(), [4]);
+    assert_eq!(assigned[1].parent.unwrap().provider.unwrap().name().name, "pll");
+    assert_eq!(assigned[1].rate, None);
+    assert!(fdt.find_node("/oscillator").unwrap().assigned_clocks().is_none());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn gpios() {
+    use crate::properties::gpio::{GpioCells, GpioLineNames, GpioRanges, Ngpios};
+
+    let blob = dts::compile(
+        r#"
+    /dts-v1/;
+
+    / {
+        pinctrl: pinctrl {
+            #pinctrl-cells = <1>;
+        };
+
+        gpio0: gpio-controller {
+            gpio-controller;
+            #gpio-cells = <2>;
+            ngpios = <18>;
+            gpio-line-names = "reset", "", "led";
+            gpio-ranges = <&pinctrl 0 20 10>, <&pinctrl 10 50 8>;
+        };
+
+        connector: connector {
+            #gpio-cells = <2>;
+            gpio-map = <0 0 &gpio0 12 0>,
+                       <1 0 &gpio0 13 0x30>;
+            gpio-map-mask = <0xf 0x0>;
+            gpio-map-pass-thru = <0x0 0x1>;
+        };
+
+        device {
+            reset-gpios = <&gpio0 5 0x6>, <0>, <&connector 1 0x1>;
+            cd-gpios = <&connector 7 0>;
+        };
+
+        wide-connector {
+            #gpio-cells = <33>;
+            gpio-map = <>;
+        };
+    };
+"#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let device = fdt.find_node("/device").unwrap();
+
+    let reset = device.gpios("reset-gpios").unwrap().iter().collect::<std::vec::Vec<_>>();
+    assert_eq!(reset.len(), 3);
+
+    let direct = reset[0].unwrap();
+    assert_eq!(direct.controller.name().name, "gpio-controller");
+    assert_eq!(direct.pin(), Some(5));
+    assert!(direct.flags().unwrap().open_drain());
+    assert!(!direct.flags().unwrap().open_source());
+    assert!(!direct.flags().unwrap().active_low());
+    assert!(reset[1].is_none());
+
+    let mapped = reset[2].unwrap();
+    assert_eq!(mapped.controller.name().name, "gpio-controller");
+    assert_eq!(mapped.specifier.as_slice(), [13, 0x31]);
+    let flags = mapped.flags().unwrap();
+    assert!(flags.active_low() && flags.pull_up() && flags.pull_down() && !flags.pull_disable());
+    assert_eq!(device.gpios("reset-gpios").unwrap().get(2).unwrap().specifier.as_slice(), [13, 0x31]);
+
+    let cd = device.fallible().gpios("cd-gpios").unwrap().unwrap();
+    assert!(matches!(cd.get(0), Err(FdtError::UnresolvedSpecifier)));
+
+    // Nexus nodes with more cells than can be held are rejected rather than
+    // overrunning the default masks
+    let fallible = Fdt::new_fallible(&blob).unwrap();
+    let wide = fallible.find_node("/wide-connector").unwrap().unwrap();
+    let nexus = properties::nexus::map_specifier::<(parsing::aligned::AlignedParser<'_>, parsing::NoPanic)>(
+        wide,
+        &properties::gpio::GPIO_NEXUS,
+        &[],
+        cell_collector::CellArray::default(),
+    );
+    assert!(matches!(nexus, Err(FdtError::CollectCellsError)));
+    assert!(device.gpios("missing-gpios").is_none());
+
+    let controller = fdt.find_node("/gpio-controller").unwrap();
+    assert_eq!(controller.property::<GpioCells>(), Some(GpioCells(2)));
+    assert_eq!(controller.property::<Ngpios>(), Some(Ngpios(18)));
+    assert_eq!(
+        controller.property::<GpioLineNames>().unwrap().iter().collect::<std::vec::Vec<_>>(),
+        ["reset", "", "led"]
+    );
+
+    let ranges = controller.property::<GpioRanges>().unwrap().iter().collect::<std::vec::Vec<_>>();
+    assert_eq!(ranges.len(), 2);
+    assert_eq!(ranges[1].pinctrl.name().name, "pinctrl");
+    assert_eq!((ranges[1].gpio_offset, ranges[1].pin_offset, ranges[1].count), (10, 50, 8));
+
+    let gpio = controller.gpio_controller().unwrap();
+    assert_eq!(gpio.gpio_cells(), 2);
+    assert_eq!(gpio.ngpios(), Some(18));
+    assert_eq!(gpio.line_names().unwrap().iter().nth(2), Some("led"));
+    assert_eq!(gpio.ranges().unwrap().iter().next().unwrap().count, 10);
+    assert!(fdt.find_node("/connector").unwrap().gpio_controller().is_none());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn pinctrl() {
+    use crate::properties::pinctrl::{PinBias, PinDrive};
+
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            pinctrl {
+                uart0_pins: uart0-pins {
+                    pins = "PA0", "PA1";
+                    function = "uart0";
+                    bias-pull-up;
+                    drive-strength = <8>;
+                    drive-open-drain;
+                    input-enable;
+                };
+
+                uart0_bias: uart0-bias {
+                    groups = "uart0_grp";
+                    bias-pull-down = <50000>;
+                    output-low;
+                };
+
+                uart0_sleep: uart0-sleep {
+                    pins = "PA0", "PA1";
+                    bias-high-impedance;
+                };
+            };
+
+            serial {
+                pinctrl-names = "default", "sleep";
+                pinctrl-0 = <&uart0_pins &uart0_bias>;
+                pinctrl-1 = <&uart0_sleep>;
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let serial = fdt.find_node("/serial").unwrap();
+    let states = serial.pinctrl_states().unwrap();
+
+    let all = states.iter().collect::<std::vec::Vec<_>>();
+    assert_eq!(all.len(), 2);
+    assert_eq!((all[0].index, all[0].name), (0, Some("default")));
+    assert_eq!((all[1].index, all[1].name), (1, Some("sleep")));
+    assert!(states.get(2).is_none());
+
+    let default = states.find("default").unwrap();
+    let configs = default.configs().map(|n| n.name().name).collect::<std::vec::Vec<_>>();
+    assert_eq!(configs, ["uart0-pins", "uart0-bias"]);
+
+    let pins = default.configs().next().unwrap().pin_config();
+    assert_eq!(pins.pins.unwrap().collect::<std::vec::Vec<_>>(), ["PA0", "PA1"]);
+    assert_eq!(pins.function, Some("uart0"));
+    assert_eq!(pins.bias, Some(PinBias::PullUp(None)));
+    assert_eq!(pins.drive, Some(PinDrive::OpenDrain));
+    assert_eq!(pins.drive_strength, Some(8));
+    assert_eq!(pins.input_enable, Some(true));
+    assert_eq!(pins.output_high, None);
+
+    let bias = default.configs().nth(1).unwrap().pin_config();
+    assert_eq!(bias.groups.unwrap().collect::<std::vec::Vec<_>>(), ["uart0_grp"]);
+    assert_eq!(bias.bias, Some(PinBias::PullDown(Some(50000))));
+    assert_eq!(bias.output_high, Some(false));
+
+    let sleep = states.find("sleep").unwrap().configs().next().unwrap().pin_config();
+    assert_eq!(sleep.bias, Some(PinBias::HighImpedance));
+    assert!(states.find("idle").is_none());
+    assert!(fdt.find_node("/pinctrl").unwrap().pinctrl_states().is_none());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn power_resets_and_supplies() {
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            pd: power-controller {
+                #power-domain-cells = <1>;
+            };
+
+            rst: reset-controller {
+                #reset-cells = <1>;
+            };
+
+            vcc: regulator-vcc {
+                compatible = "regulator-fixed";
+                regulator-name = "vcc-3v3";
+                regulator-min-microvolt = <3300000>;
+                regulator-max-microvolt = <3300000>;
+                regulator-always-on;
+            };
+
+            vio: regulator-vio {
+                regulator-boot-on;
+            };
+
+            device {
+                power-domains = <&pd 4>;
+                power-domain-names = "core";
+                resets = <&rst 1>, <&rst 2>;
+                reset-names = "bus", "phy";
+                vdd-supply = <&vcc>;
+                vio-supply = <&vio>;
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let device = fdt.find_node("/device").unwrap();
+
+    let core = device.power_domains().unwrap().find("core").unwrap();
+    assert_eq!(core.provider.unwrap().name().name, "power-controller");
+    assert_eq!(core.args.as_slice(), [4]);
+
+    assert_eq!(device.resets().unwrap().iter().count(), 2);
+    let phy = device.reset("phy").unwrap();
+    assert_eq!(phy.provider.unwrap().name().name, "reset-controller");
+    assert_eq!(phy.args.as_slice(), [2]);
+    assert!(device.reset("missing").is_none());
+
+    let supplies = device.supplies().map(|s| (s.name, s.regulator.name().name)).collect::<std::vec::Vec<_>>();
+    assert_eq!(supplies, [("vdd", "regulator-vcc"), ("vio", "regulator-vio")]);
+    assert!(device.supply("vdda").is_none());
+
+    let vcc = device.supply("vdd").unwrap().regulator_constraints();
+    assert_eq!(vcc.name, Some("vcc-3v3"));
+    assert_eq!((vcc.min_microvolt, vcc.max_microvolt), (Some(3_300_000), Some(3_300_000)));
+    assert!(vcc.always_on && !vcc.boot_on);
+
+    let vio = device.supply("vio").unwrap().regulator_constraints();
+    assert_eq!((vio.name, vio.min_microvolt), (None, None));
+    assert!(!vio.always_on && vio.boot_on);
+    assert!(fdt.find_node("/regulator-vio").unwrap().power_domains().is_none());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn graph() {
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            sensor {
+                port {
+                    sensor_out: endpoint {
+                        remote-endpoint = <&csi_in>;
+                        data-lanes = <1 2>;
+                        clock-lanes = <0>;
+                    };
+                };
+            };
+
+            csi {
+                ports {
+                    #address-cells = <1>;
+                    #size-cells = <0>;
+
+                    port@0 {
+                        reg = <0>;
+                        #address-cells = <1>;
+                        #size-cells = <0>;
+
+                        csi_in: endpoint@1 {
+                            reg = <1>;
+                            remote-endpoint = <&sensor_out>;
+                            bus-width = <8>;
+                        };
+                    };
+
+                    port@1 {
+                        reg = <1>;
+                    };
+                };
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let csi = fdt.find_node("/csi").unwrap();
+    let sensor = fdt.find_node("/sensor").unwrap();
+
+    assert_eq!(csi.ports().map(|p| p.id()).collect::<std::vec::Vec<_>>(), [0, 1]);
+    assert!(csi.port(1).unwrap().endpoints().next().is_none());
+    assert!(csi.port(2).is_none());
+
+    let sensor_port = sensor.port(0).unwrap();
+    assert_eq!(sensor_port.device().name().name, "sensor");
+
+    let sensor_out = sensor_port.endpoints().next().unwrap();
+    assert_eq!(sensor_out.id(), 0);
+    assert_eq!(sensor_out.data_lanes().unwrap().iter().collect::<std::vec::Vec<_>>(), [1, 2]);
+    assert_eq!(sensor_out.clock_lanes().unwrap().iter().collect::<std::vec::Vec<_>>(), [0]);
+    assert_eq!(sensor_out.bus_width(), None);
+
+    let csi_in = sensor_out.remote_endpoint().unwrap();
+    assert_eq!(csi_in.id(), 1);
+    assert_eq!(csi_in.bus_width(), Some(8));
+    assert_eq!(sensor_out.remote_port().unwrap().id(), 0);
+    assert_eq!(sensor_out.remote_device().unwrap().name().name, "csi");
+    assert_eq!(csi_in.port().device().name().name, "csi");
+    assert_eq!(csi_in.remote_device().unwrap().name().name, "sensor");
+    assert_eq!(csi.port(0).unwrap().endpoint(1).unwrap().remote_endpoint().unwrap().id(), 0);
+    assert!(csi.port(0).unwrap().endpoint(0).is_none());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn pci_host_bridge() {
+    use crate::properties::pci::PciAddressSpace;
+
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            #address-cells = <2>;
+            #size-cells = <2>;
+
+            its: msi-controller@8080000 {
+                msi-controller;
+                #msi-cells = <1>;
+            };
+
+            smmu: iommu@9050000 {
+                #iommu-cells = <1>;
+            };
+
+            pcie@30000000 {
+                compatible = "pci-host-ecam-generic";
+                device_type = "pci";
+                #address-cells = <3>;
+                #size-cells = <2>;
+                reg = <0x0 0x30000000 0x0 0x1000000>;
+                bus-range = <0x10 0x1f>;
+                linux,pci-domain = <2>;
+                ranges = <0x01000000 0x0 0x00000000 0x0 0x3eff0000 0x0 0x10000>,
+                         <0x02000000 0x0 0x40000000 0x0 0x40000000 0x0 0x20000000>,
+                         <0x43000000 0x80 0x00000000 0x80 0x00000000 0x80 0x00000000>;
+                msi-map = <0x0 &its 0x10000 0x10000>;
+                msi-map-mask = <0xff00>;
+                iommu-map = <0x0 &smmu 0x0 0x100>, <0x100 &smmu 0x800 0x100>;
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let bridge = fdt.find_node("/pcie@30000000").unwrap().pci_host_bridge().unwrap();
+    assert!(fdt.find_node("/iommu@9050000").unwrap().pci_host_bridge().is_none());
+
+    assert_eq!(bridge.domain(), Some(2));
+    assert_eq!((bridge.bus_range().start, bridge.bus_range().end), (0x10, 0x1f));
+
+    let ecam = bridge.ecam().unwrap();
+    assert_eq!((ecam.address, ecam.size), (0x3000_0000, 0x100_0000));
+    assert_eq!(ecam.config_address(0x10, 0, 0, 0), Some(0x3000_0000));
+    assert_eq!(ecam.config_address(0x11, 2, 1, 0x10), Some(0x3000_0000 + (1 << 20) + (2 << 15) + (1 << 12) + 0x10));
+    assert_eq!(ecam.config_address(0x0f, 0, 0, 0), None);
+    assert_eq!(ecam.config_address(0x10, 32, 0, 0), None);
+
+    let malformed = nodes::pci::EcamWindow { address: u64::MAX - 0xfff, ..ecam };
+    assert_eq!(malformed.config_address(0x10, 0, 0, 0xffc), Some(u64::MAX - 3));
+    assert_eq!(malformed.config_address(0x10, 0, 1, 0), None);
+
+    let windows = bridge.windows().collect::<std::vec::Vec<_>>();
+    assert_eq!(windows.len(), 3);
+    assert_eq!((windows[0].space, windows[0].size), (PciAddressSpace::Io, 0x10000));
+    assert_eq!(windows[0].parent_bus_address, 0x3eff_0000);
+    assert_eq!((windows[1].space, windows[1].prefetchable), (PciAddressSpace::Memory32, false));
+    assert_eq!(windows[1].pci_address, 0x4000_0000);
+    assert_eq!((windows[2].space, windows[2].prefetchable), (PciAddressSpace::Memory64, true));
+    assert_eq!((windows[2].pci_address, windows[2].size), (0x80_0000_0000, 0x80_0000_0000));
+
+    let msi = bridge.map_msi(0x0108).unwrap();
+    assert_eq!((msi.target.name().name, msi.id), ("msi-controller", 0x10100));
+
+    let iommu = bridge.map_iommu(0x0108).unwrap();
+    assert_eq!((iommu.target.name().name, iommu.id), ("iommu", 0x808));
+    assert_eq!(bridge.map_iommu(0x0010).unwrap().id, 0x10);
+    assert!(bridge.map_iommu(0x0200).is_none());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn iommus() {
+    use crate::properties::iommu::IommuMap;
+
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            smmu: iommu@9050000 {
+                #iommu-cells = <1>;
+            };
+
+            smmu2: iommu@9060000 {
+                #iommu-cells = <2>;
+            };
+
+            dma-controller {
+                iommus = <&smmu 0x20>, <&smmu2 0x21 0x3>;
+            };
+
+            bus {
+                iommu-map = <0x0 &smmu 0x1000 0x100>, <0x100 &smmu2 0x0 0x80>;
+                iommu-map-mask = <0x1ff>;
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+
+    let iommus = fdt.find_node("/dma-controller").unwrap().iommus().unwrap();
+    let entries = iommus.iter().collect::<std::vec::Vec<_>>();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].provider.unwrap().name().unit_address, Some("9050000"));
+    assert_eq!(entries[0].args.as_slice(), [0x20]);
+    assert_eq!(entries[1].args.as_slice(), [0x21, 0x3]);
+
+    let map = fdt.find_node("/bus").unwrap().property::<IommuMap>().unwrap();
+    assert_eq!(map.mask(), 0x1ff);
+    assert_eq!(map.iter().count(), 2);
+    assert_eq!(map.iter().nth(1).unwrap().map(0x110), Some(0x10));
+
+    let mapped = map.map(0x0a05).unwrap();
+    assert_eq!((mapped.target.name().unit_address, mapped.id), (Some("9050000"), 0x1005));
+    let mapped = map.map(0x17f).unwrap();
+    assert_eq!((mapped.target.name().unit_address, mapped.id), (Some("9060000"), 0x7f));
+    assert!(map.map(0x180).is_none());
+    assert!(fdt.find_node("/dma-controller").unwrap().property::<IommuMap>().is_none());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn msi() {
+    use crate::properties::msi::MsiController;
+
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            #address-cells = <1>;
+            #size-cells = <1>;
+
+            its: msi-controller@8080000 {
+                msi-controller;
+                #msi-cells = <1>;
+            };
+
+            v2m: msi-controller@8020000 {
+                msi-controller;
+            };
+
+            uart@9000000 {
+                msi-parent = <&its 0x40>;
+            };
+
+            platform-bus {
+                #address-cells = <1>;
+                #size-cells = <1>;
+                msi-parent = <&v2m>;
+
+                dma@100 {
+                    reg = <0x100 0x100>;
+                };
+            };
+
+            pcie@10000000 {
+                device_type = "pci";
+                #address-cells = <3>;
+                #size-cells = <2>;
+                msi-map = <0x0 &its 0x10000 0x100>;
+                msi-map-mask = <0xff>;
+
+                ethernet@1,0 {
+                    reg = <0x10800 0x0 0x0 0x0 0x0>;
+                };
+            };
+
+            pcie@20000000 {
+                device_type = "pci";
+                #address-cells = <3>;
+                #size-cells = <2>;
+                msi-map = <0x0 &its 0x20000 0x10000>;
+
+                pcie@0,0 {
+                    device_type = "pci";
+                    #address-cells = <3>;
+                    #size-cells = <2>;
+                    reg = <0x0 0x0 0x0 0x0 0x0>;
+
+                    nvme@0,0 {
+                        reg = <0x10000 0x0 0x0 0x0 0x0>;
+                    };
+                };
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+
+    let its = fdt.find_node("/msi-controller@8080000").unwrap();
+    assert!(its.property::<MsiController>().is_some());
+    assert!(fdt.find_node("/uart@9000000").unwrap().property::<MsiController>().is_none());
+
+    let uart = fdt.find_node("/uart@9000000").unwrap();
+    let parent = uart.msi_parent().unwrap().iter().next().unwrap();
+    assert_eq!(parent.provider.unwrap().name().unit_address, Some("8080000"));
+    assert_eq!(parent.args.as_slice(), [0x40]);
+    let resolved = uart.msi_controller().unwrap();
+    assert_eq!(resolved.controller.name().unit_address, Some("8080000"));
+    assert_eq!(resolved.specifier.as_slice(), [0x40]);
+
+    let bus = fdt.find_node("/platform-bus").unwrap();
+    let parent = bus.msi_parent().unwrap().iter().next().unwrap();
+    assert_eq!(parent.provider.unwrap().name().unit_address, Some("8020000"));
+    assert!(parent.args.as_slice().is_empty());
+
+    let resolved = fdt.find_node("/platform-bus/dma@100").unwrap().msi_controller().unwrap();
+    assert_eq!(resolved.controller.name().unit_address, Some("8020000"));
+    assert!(resolved.specifier.as_slice().is_empty());
+
+    let resolved = fdt.find_node("/pcie@10000000/ethernet@1,0").unwrap().msi_controller().unwrap();
+    assert_eq!(resolved.controller.name().unit_address, Some("8080000"));
+    assert_eq!(resolved.specifier.as_slice(), [0x10008]);
+
+    // Devices behind a root port use their own requester ID, not the port's
+    let resolved = fdt.find_node("/pcie@20000000/pcie@0,0").unwrap().msi_controller().unwrap();
+    assert_eq!(resolved.specifier.as_slice(), [0x20000]);
+    let resolved = fdt.find_node("/pcie@20000000/pcie@0,0/nvme@0,0").unwrap().msi_controller().unwrap();
+    assert_eq!(resolved.specifier.as_slice(), [0x20100]);
+
+    assert!(fdt.find_node("/msi-controller@8020000").unwrap().msi_controller().is_none());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn gic() {
+    use crate::{
+        nodes::gic::{GicRegion, GicVersion},
+        properties::{
+            gic::{GicInterrupt, GicInterruptKind, GicTrigger},
+            interrupts::LegacyInterrupts,
+        },
+    };
+
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            #address-cells = <2>;
+            #size-cells = <2>;
+
+            gicv3: interrupt-controller@8000000 {
+                compatible = "arm,gic-v3";
+                interrupt-controller;
+                #interrupt-cells = <3>;
+                #redistributor-regions = <2>;
+                redistributor-stride = <0x0 0x40000>;
+                reg = <0x0 0x8000000 0x0 0x10000>,
+                      <0x0 0x80a0000 0x0 0xf60000>,
+                      <0x0 0x9000000 0x0 0x100000>,
+                      <0x0 0x8010000 0x0 0x2000>;
+            };
+
+            gic400: interrupt-controller@2c001000 {
+                compatible = "arm,gic-400";
+                interrupt-controller;
+                #interrupt-cells = <3>;
+                reg = <0x0 0x2c001000 0x0 0x1000>,
+                      <0x0 0x2c002000 0x0 0x2000>,
+                      <0x0 0x2c004000 0x0 0x2000>,
+                      <0x0 0x2c006000 0x0 0x2000>;
+            };
+
+            timer {
+                interrupt-parent = <&gic400>;
+                interrupts = <1 13 0xf08>, <0 29 4>;
+            };
+
+            uart@9000000 {
+                interrupt-parent = <&gicv3>;
+                interrupts = <2 5 1>;
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+
+    let v3 = fdt.find_node("/interrupt-controller@8000000").unwrap().gic().unwrap();
+    assert_eq!(v3.version(), GicVersion::V3);
+    assert_eq!(v3.distributor(), Some(GicRegion { address: 0x8000000, size: 0x10000 }));
+    assert_eq!(v3.redistributor_region_count(), 2);
+    let redistributors = v3.redistributor_regions().collect::<std::vec::Vec<_>>();
+    assert_eq!(
+        redistributors,
+        [GicRegion { address: 0x80a0000, size: 0xf60000 }, GicRegion { address: 0x9000000, size: 0x100000 }]
+    );
+    assert_eq!(v3.redistributor_stride(), Some(0x40000));
+    assert_eq!(v3.cpu_interface(), Some(GicRegion { address: 0x8010000, size: 0x2000 }));
+    assert_eq!(v3.hypervisor_interface(), None);
+
+    let v2 = fdt.find_node("/interrupt-controller@2c001000").unwrap().gic().unwrap();
+    assert_eq!(v2.version(), GicVersion::V2);
+    assert_eq!(v2.redistributor_region_count(), 0);
+    assert_eq!(v2.redistributor_regions().count(), 0);
+    assert_eq!(v2.cpu_interface(), Some(GicRegion { address: 0x2c002000, size: 0x2000 }));
+    assert_eq!(v2.virtual_cpu_interface(), Some(GicRegion { address: 0x2c006000, size: 0x2000 }));
+    assert!(fdt.find_node("/timer").unwrap().gic().is_none());
+
+    let timer = fdt.find_node("/timer").unwrap().property::<LegacyInterrupts>().unwrap();
+    let mut specifiers = timer.iter();
+    let ppi = specifiers.next().unwrap().gic_v2().unwrap();
+    assert_eq!((ppi.kind, ppi.number, ppi.intid()), (GicInterruptKind::Ppi, 13, 29));
+    assert_eq!((ppi.trigger, ppi.cpu_mask), (Some(GicTrigger::LevelLow), Some(0xf)));
+    let spi = specifiers.next().unwrap().gic_v2().unwrap();
+    assert_eq!(
+        (spi.kind, spi.intid(), spi.trigger, spi.cpu_mask),
+        (GicInterruptKind::Spi, 61, Some(GicTrigger::LevelHigh), None)
+    );
+
+    let uart = fdt.find_node("/uart@9000000").unwrap().resolve_interrupt(0).unwrap();
+    let espi = uart.controller.gic().unwrap().decode_interrupt(&uart.specifier).unwrap();
+    assert_eq!(
+        (espi.kind, espi.intid(), espi.trigger),
+        (GicInterruptKind::ExtendedSpi, 4101, Some(GicTrigger::EdgeRising))
+    );
+    assert!(GicInterrupt::decode_v2(&[2, 5, 1]).is_none());
+    assert!(GicInterrupt::decode_v3(&[1, 16, 4]).is_none());
+    assert!(GicInterrupt::decode_v3(&[1, 7, 4, 0]).unwrap().ppi_partition.is_none());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn riscv_interrupt_controllers() {
+    use crate::nodes::riscv::{ClintKind, RiscvPrivilege};
+
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            #address-cells = <2>;
+            #size-cells = <2>;
+
+            cpus {
+                #address-cells = <1>;
+                #size-cells = <0>;
+
+                cpu@0 {
+                    device_type = "cpu";
+                    reg = <0>;
+
+                    cpu0_intc: interrupt-controller {
+                        compatible = "riscv,cpu-intc";
+                        interrupt-controller;
+                        #interrupt-cells = <1>;
+                    };
+                };
+
+                cpu@3 {
+                    device_type = "cpu";
+                    reg = <3>;
+
+                    cpu3_intc: interrupt-controller {
+                        compatible = "riscv,cpu-intc";
+                        interrupt-controller;
+                        #interrupt-cells = <1>;
+                    };
+                };
+            };
+
+            plic@c000000 {
+                compatible = "sifive,fu540-c000-plic", "sifive,plic-1.0.0";
+                riscv,ndev = <53>;
+                interrupts-extended = <&cpu0_intc 0xffffffff>, <&cpu0_intc 9>, <&cpu3_intc 11>, <&cpu3_intc 9>;
+            };
+
+            timer@2004000 {
+                compatible = "riscv,aclint-mtimer";
+                interrupts-extended = <&cpu0_intc 7>, <&cpu3_intc 7>;
+            };
+
+            imsic: interrupt-controller@28000000 {
+                compatible = "riscv,imsics";
+                msi-controller;
+                riscv,num-ids = <255>;
+                riscv,guest-index-bits = <3>;
+                interrupts-extended = <&cpu0_intc 9>, <&cpu3_intc 9>;
+            };
+
+            aplic_s: interrupt-controller@d000000 {
+                compatible = "riscv,aplic";
+                riscv,num-sources = <96>;
+                msi-parent = <&imsic>;
+            };
+
+            interrupt-controller@c000000 {
+                compatible = "riscv,aplic";
+                riscv,num-sources = <96>;
+                riscv,children = <&aplic_s>;
+                riscv,delegation = <&aplic_s 1 63>;
+                interrupts-extended = <&cpu0_intc 11>, <&cpu3_intc 11>;
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+
+    let plic = fdt.find_node("/plic@c000000").unwrap().plic().unwrap();
+    assert_eq!(plic.ndev(), 53);
+    let contexts = plic.contexts().collect::<std::vec::Vec<_>>();
+    assert_eq!(contexts.len(), 4);
+    assert_eq!((contexts[0].index, contexts[0].hart_id, contexts[0].cause), (0, 0, None));
+    assert_eq!((contexts[1].hart_id, contexts[1].privilege()), (0, Some(RiscvPrivilege::Supervisor)));
+    assert_eq!(
+        (contexts[2].index, contexts[2].hart_id, contexts[2].privilege()),
+        (2, 3, Some(RiscvPrivilege::Machine))
+    );
+    assert_eq!(contexts[3].cpu.reg::<u32>().first().unwrap(), 3);
+    assert!(fdt.find_node("/timer@2004000").unwrap().plic().is_none());
+
+    let timer = fdt.find_node("/timer@2004000").unwrap().clint().unwrap();
+    assert_eq!(timer.kind(), ClintKind::MachineTimer);
+    assert_eq!(
+        timer.contexts().map(|c| (c.hart_id, c.cause)).collect::<std::vec::Vec<_>>(),
+        [(0, Some(7)), (3, Some(7))]
+    );
+
+    let imsic = fdt.find_node("/interrupt-controller@28000000").unwrap().imsic().unwrap();
+    assert_eq!((imsic.num_ids(), imsic.num_guest_ids(), imsic.guest_index_bits()), (255, 255, 3));
+    assert_eq!(imsic.hart_index_bits(), None);
+    assert_eq!(imsic.contexts().map(|c| c.hart_id).collect::<std::vec::Vec<_>>(), [0, 3]);
+
+    let aplic_m = fdt.find_node("/interrupt-controller@c000000").unwrap().aplic().unwrap();
+    assert_eq!(aplic_m.num_sources(), 96);
+    assert!(aplic_m.msi_parent().is_none());
+    assert_eq!(aplic_m.contexts().count(), 2);
+    let delegation = aplic_m.delegations().next().unwrap();
+    assert_eq!((delegation.child.name().unit_address, delegation.first, delegation.last), (Some("d000000"), 1, 63));
+
+    let aplic_s = fdt.find_node("/interrupt-controller@d000000").unwrap().aplic().unwrap();
+    assert_eq!(aplic_s.contexts().count(), 0);
+    assert_eq!(aplic_s.delegations().count(), 0);
+    let imsic = aplic_s.msi_parent().unwrap().iter().next().unwrap().provider.unwrap();
+    assert_eq!(imsic.name().unit_address, Some("28000000"));
+
+    let fdt = Fdt::new(TEST.as_slice()).unwrap();
+    let clint = fdt.find_node("/soc/clint@2000000").unwrap().clint().unwrap();
+    assert_eq!(clint.kind(), ClintKind::Clint);
+    assert_eq!(clint.contexts().map(|c| c.cause).collect::<std::vec::Vec<_>>(), [Some(3), Some(7)]);
+    let plic = fdt.find_node("/soc/plic@c000000").unwrap().plic().unwrap();
+    assert_eq!(
+        plic.contexts().map(|c| c.privilege()).collect::<std::vec::Vec<_>>(),
+        [Some(RiscvPrivilege::Machine), Some(RiscvPrivilege::Supervisor)]
+    );
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn resolve_interrupt_parent_from_root() {
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            #address-cells = <1>;
+            #size-cells = <1>;
+            interrupt-parent = <&intc>;
+
+            intc: interrupt-controller@1000 {
+                reg = <0x1000 0x100>;
+                interrupt-controller;
+                #interrupt-cells = <1>;
+            };
+
+            timer@2000 {
+                reg = <0x2000 0x100>;
+                interrupts = <7>;
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+
+    // `interrupt-parent` is inherited from the root node
+    let timer = fdt.find_node("/timer@2000").unwrap().resolve_interrupt(0).unwrap();
+    assert_eq!(timer.controller.name().unit_address, Some("1000"));
+    assert_eq!(&*timer.specifier, &[7]);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn resolve_interrupt_parent_deeply_nested() {
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            interrupt-parent = <&intc>;
+
+            intc: interrupt-controller {
+                interrupt-controller;
+                #interrupt-cells = <1>;
+            };
+
+            n0 { n1 { n2 { n3 { n4 { n5 { n6 { n7 { n8 { n9 { n10 { n11 { n12 { n13 { n14 { n15 { n16 { n17 {
+                device { interrupts = <9>; };
+            }; }; }; }; }; }; }; }; }; }; }; }; }; }; }; }; }; };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+
+    // `interrupt-parent` is inherited through more levels than `all_nodes`,
+    // and so `find_node`, can track
+    let mut device = fdt.find_node("/n0").unwrap();
+    for name in "n1/n2/n3/n4/n5/n6/n7/n8/n9/n10/n11/n12/n13/n14/n15/n16/n17/device".split('/') {
+        device = device.children().find(name).unwrap();
+    }
+
+    let device = device.resolve_interrupt(0).unwrap();
+    assert_eq!(device.controller.name().name, "interrupt-controller");
+    assert_eq!(&*device.specifier, &[9]);
 }
 
 #[test]
@@ -425,6 +1812,337 @@ fn memory_node() {
     assert_eq!(root.memory().reg().iter::<u64, u64>().count(), 1);
 }
 
+#[test]
+#[cfg(feature = "alloc")]
+fn all_memory() {
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            #address-cells = <2>;
+            #size-cells = <2>;
+
+            memory@80000000 {
+                device_type = "memory";
+                reg = <0x0 0x80000000 0x0 0x40000000>, <0x0 0xc0000000 0x0 0x10000>;
+                numa-node-id = <0>;
+            };
+
+            memory@880000000 {
+                device_type = "memory";
+                reg = <0x8 0x80000000 0x0 0x80000000>;
+                numa-node-id = <1>;
+            };
+
+            memory-controller {
+                reg = <0x0 0x1000 0x0 0x100>;
+            };
+
+            sram@10000 {
+                device_type = "memory";
+                reg = <0x0 0x10000 0x0 0x1000>;
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let root = fdt.root();
+
+    let nodes = root.all_memory().map(|m| (m.as_node().name(), m.numa_node_id())).collect::<std::vec::Vec<_>>();
+    assert_eq!(
+        nodes,
+        [
+            (NodeName { name: "memory", unit_address: Some("80000000") }, Some(0)),
+            (NodeName { name: "memory", unit_address: Some("880000000") }, Some(1)),
+            (NodeName { name: "sram", unit_address: Some("10000") }, None),
+        ]
+    );
+
+    let regions = root.all_memory().reg_entries::<u64, u64>().map(Result::unwrap).collect::<std::vec::Vec<_>>();
+    assert_eq!(
+        regions,
+        [
+            RegEntry { address: 0x8000_0000, len: 0x4000_0000 },
+            RegEntry { address: 0xc000_0000, len: 0x1_0000 },
+            RegEntry { address: 0x8_8000_0000, len: 0x8000_0000 },
+            RegEntry { address: 0x1_0000, len: 0x1000 },
+        ]
+    );
+    assert!(matches!(root.all_memory().reg_entries::<u32, u32>().nth(2), Some(Err(cell_collector::CollectCellsError))));
+
+    let mut ranges = [memory_map::MemoryRange::default(); 4];
+    let len = root.all_memory().merged_regions(&mut ranges);
+    assert_eq!(
+        ranges[..len],
+        [
+            memory_map::MemoryRange { address: 0x1_0000, size: 0x1000 },
+            memory_map::MemoryRange { address: 0x8000_0000, size: 0x4001_0000 },
+            memory_map::MemoryRange { address: 0x8_8000_0000, size: 0x8000_0000 },
+        ]
+    );
+    let fdt_fallible = Fdt::new_fallible(&blob).unwrap();
+    assert!(matches!(
+        fdt_fallible.root().unwrap().all_memory().unwrap().merged_regions(&mut ranges[..3]),
+        Err(FdtError::SliceTooSmall)
+    ));
+
+    let fdt = Fdt::new(TEST.as_slice()).unwrap();
+    assert_eq!(fdt.root().all_memory().count(), 1);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn memory_map() {
+    use memory_map::{CarveOut, CarveOutReason, MemoryRange};
+
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+        /memreserve/ 0x80000000 0x10000;
+
+        / {
+            #address-cells = <2>;
+            #size-cells = <2>;
+
+            chosen {
+                linux,initrd-start = <0x84000000>;
+                linux,initrd-end = <0x0 0x84800000>;
+            };
+
+            memory@c0000000 {
+                device_type = "memory";
+                reg = <0x0 0xc0000000 0x0 0x40000000>;
+            };
+
+            memory@80000000 {
+                device_type = "memory";
+                reg = <0x0 0x80000000 0x0 0x40000000>, <0x1 0x0 0x0 0x10000000>;
+            };
+
+            reserved-memory {
+                #address-cells = <2>;
+                #size-cells = <2>;
+                ranges;
+
+                firmware@80100000 {
+                    reg = <0x0 0x80100000 0x0 0x100000>;
+                    no-map;
+                };
+
+                disabled@90000000 {
+                    reg = <0x0 0x90000000 0x0 0x100000>;
+                    status = "disabled";
+                };
+
+                pool {
+                    size = <0x0 0x400000>;
+                };
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let map = fdt.memory_map().with_fdt_address(0x1_0800_0000);
+    let range = |address, size| MemoryRange { address, size };
+
+    assert_eq!(
+        map.carve_outs().collect::<std::vec::Vec<_>>(),
+        [
+            CarveOut { range: range(0x8000_0000, 0x1_0000), reason: CarveOutReason::MemoryReservation },
+            CarveOut {
+                range: range(0x8010_0000, 0x10_0000),
+                reason: CarveOutReason::ReservedMemory(NodeName { name: "firmware", unit_address: Some("80100000") }),
+            },
+            CarveOut { range: range(0x8400_0000, 0x80_0000), reason: CarveOutReason::Initrd },
+            CarveOut { range: range(0x1_0800_0000, fdt.total_size() as u64), reason: CarveOutReason::Devicetree },
+        ]
+    );
+
+    let mut ranges = [MemoryRange::default(); 4];
+    let len = map.usable(&mut ranges);
+    assert_eq!(
+        ranges[..len],
+        [
+            range(0x8001_0000, 0xf_0000),
+            range(0x8020_0000, 0x3e0_0000),
+            range(0x8480_0000, 0x8380_0000),
+            range(0x1_0800_0000 + fdt.total_size() as u64, 0x800_0000 - fdt.total_size() as u64),
+        ]
+    );
+
+    let fdt = Fdt::new_fallible(&blob).unwrap();
+    let map = fdt.memory_map().with_fdt_address(0x1_0800_0000);
+    assert!(matches!(map.usable(&mut ranges[..3]), Err(FdtError::SliceTooSmall)));
+    assert!(matches!(map.usable(&mut ranges[..2]), Err(FdtError::SliceTooSmall)));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn memory_map_empty_carve_outs() {
+    use memory_map::MemoryRange;
+
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+        /memreserve/ 0x80100000 0x0;
+
+        / {
+            #address-cells = <2>;
+            #size-cells = <2>;
+
+            chosen {
+                linux,initrd-start = <0x84000000>;
+                linux,initrd-end = <0x84000000>;
+            };
+
+            memory@80000000 {
+                device_type = "memory";
+                reg = <0x0 0x80000000 0x0 0x40000000>;
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let map = fdt.memory_map().with_fdt_address(0x1_0000_0000);
+
+    let mut ranges = [MemoryRange::default(); 1];
+    let len = map.usable(&mut ranges);
+    assert_eq!(ranges[..len], [MemoryRange { address: 0x8000_0000, size: 0x4000_0000 }]);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn plan_reserved_memory() {
+    use memory_map::{DynamicRegion, MemoryRange, PlanError, ReservedMemoryPlan};
+
+    let dts = |children: &str| {
+        dts::compile(&std::format!(
+            r#"
+            /dts-v1/;
+
+            / {{
+                #address-cells = <1>;
+                #size-cells = <1>;
+
+                memory@80000000 {{
+                    device_type = "memory";
+                    reg = <0x80000000 0x10000000>;
+                }};
+
+                reserved-memory {{
+                    #address-cells = <1>;
+                    #size-cells = <1>;
+                    ranges;
+
+                    static@8ff00000 {{
+                        reg = <0x8ff00000 0x100000>;
+                    }};
+
+                    {children}
+                }};
+            }};
+        "#
+        ))
+        .unwrap()
+    };
+
+    let blob = dts(r#"
+        linux,cma {
+            compatible = "shared-dma-pool";
+            size = <0x1000000>;
+            alignment = <0x400000>;
+            reusable;
+        };
+
+        dma-pool {
+            size = <0x100000>;
+            alloc-ranges = <0x80000000 0x1000000>, <0x84000000 0x1000000>;
+            no-map;
+        };
+
+        unaligned {
+            size = <0x800>;
+        };
+
+        disabled {
+            size = <0x100000>;
+            status = "disabled";
+        };
+    "#);
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let map = fdt.memory_map().with_fdt_address(0x4000_0000);
+    let range = |address, size| MemoryRange { address, size };
+
+    let mut usable = [MemoryRange::default(); 8];
+    let len = map.usable(&mut usable);
+    assert_eq!(usable[..len], [range(0x8000_0000, 0xff0_0000)]);
+
+    let name = NodeName { name: "", unit_address: None };
+    let mut regions = [DynamicRegion { name, range: MemoryRange::default(), no_map: false, reusable: false }; 3];
+    let plan = map.plan_reserved_memory(&mut usable, len, &mut regions).unwrap();
+    assert_eq!(plan, ReservedMemoryPlan { usable: 4, regions: 3 });
+    assert_eq!(
+        regions,
+        [
+            DynamicRegion {
+                name: NodeName { name: "linux,cma", unit_address: None },
+                range: range(0x8ec0_0000, 0x100_0000),
+                no_map: false,
+                reusable: true,
+            },
+            DynamicRegion {
+                name: NodeName { name: "dma-pool", unit_address: None },
+                range: range(0x84f0_0000, 0x10_0000),
+                no_map: true,
+                reusable: false,
+            },
+            DynamicRegion {
+                name: NodeName { name: "unaligned", unit_address: None },
+                range: range(0x8fef_f000, 0x800),
+                no_map: false,
+                reusable: false,
+            },
+        ]
+    );
+    assert_eq!(
+        usable[..plan.usable],
+        [
+            range(0x8000_0000, 0x4f0_0000),
+            range(0x8500_0000, 0x9c0_0000),
+            range(0x8fc0_0000, 0x2f_f000),
+            range(0x8fef_f800, 0x800),
+        ]
+    );
+
+    let blob = dts("too-big { size = <0x10000000>; };");
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let map = fdt.memory_map().with_fdt_address(0x4000_0000);
+    let len = map.usable(&mut usable);
+    assert!(matches!(
+        map.plan_reserved_memory(&mut usable, len, &mut regions),
+        Err(PlanError::DoesNotFit(NodeName { name: "too-big", unit_address: None }))
+    ));
+
+    let blob = dts("both { size = <0x1000>; no-map; reusable; };");
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let map = fdt.memory_map().with_fdt_address(0x4000_0000);
+    let len = map.usable(&mut usable);
+    assert!(matches!(
+        map.plan_reserved_memory(&mut usable, len, &mut regions),
+        Err(PlanError::NoMapAndReusable(NodeName { name: "both", unit_address: None }))
+    ));
+}
+
 #[test]
 fn interrupt_cells() {
     let fdt = Fdt::new(TEST.as_slice()).unwrap();
@@ -468,26 +2186,772 @@ fn cpu_map() {
 #[cfg(feature = "pretty-printing")]
 #[test]
 fn node_pretty_print() {
-    let expected = r#"cpu@0 {
-    phandle = <0x01>;
-    device_type = "cpu";
-    reg = <0x00>;
-    status = "okay";
-    compatible = "riscv";
-    riscv,isa = "rv64imafdcsu";
-    mmu-type = "riscv,sv48";
-
-    interrupt-controller {
-        #interrupt-cells = <0x01>;
-        interrupt-controller;
-        compatible = "riscv,cpu-intc";
-        phandle = <0x02>;
-    };
+    let expected = "cpu@0 {
+	phandle = <0x01>;
+	device_type = \"cpu\";
+	reg = <0x00>;
+	status = \"okay\";
+	compatible = \"riscv\";
+	riscv,isa = \"rv64imafdcsu\";
+	mmu-type = \"riscv,sv48\";
+
+	interrupt-controller {
+		#interrupt-cells = <0x01>;
+		interrupt-controller;
+		compatible = \"riscv,cpu-intc\";
+		phandle = <0x02>;
+	};
 };
-"#;
+";
 
     let fdt = Fdt::new(TEST.as_slice()).unwrap();
     let cpu = fdt.find_node_by_name("cpu").unwrap();
 
     assert_eq!(std::string::ToString::to_string(&cpu), expected);
 }
+
+fn memory_reservation_blob(reservations: &[(u64, u64)], terminated: bool) -> std::vec::Vec<u32> {
+    // The structs block is placed before the memory reservation block so that
+    // an unterminated reservation block runs into the end of the devicetree.
+    let structs = [1u32, 0, 2, 9];
+    let structs_offset = 40;
+    let reservations_offset = structs_offset + structs.len() as u32 * 4;
+
+    let mut reservation_block = std::vec::Vec::new();
+    let terminator = if terminated { &[(0, 0)][..] } else { &[] };
+    for &(address, size) in reservations.iter().chain(terminator) {
+        reservation_block.extend([(address >> 32) as u32, address as u32, (size >> 32) as u32, size as u32]);
+    }
+
+    let total_size = reservations_offset + reservation_block.len() as u32 * 4;
+    let header = [
+        0xd00dfeed,
+        total_size,
+        structs_offset,
+        total_size,
+        reservations_offset,
+        17,
+        16,
+        0,
+        0,
+        structs.len() as u32 * 4,
+    ];
+
+    header.into_iter().chain(structs).chain(reservation_block).map(u32::to_be).collect()
+}
+
+#[test]
+fn memory_reservations() {
+    let fdt = Fdt::new(TEST.as_slice()).unwrap();
+    assert_eq!(fdt.memory_reservations().count(), 0);
+
+    let blob = memory_reservation_blob(&[(0x8000_0000, 0x20_0000), (0x1_0000_0000, 0x1000)], true);
+    let expected = [
+        nodes::memory::MemoryReservation { address: 0x8000_0000, size: 0x20_0000 },
+        nodes::memory::MemoryReservation { address: 0x1_0000_0000, size: 0x1000 },
+    ];
+
+    let fdt = Fdt::new(&blob).unwrap();
+    assert_eq!(fdt.memory_reservations().collect::<std::vec::Vec<_>>(), expected);
+
+    let fdt = Fdt::new_unaligned_fallible(util::cast_slice(&blob)).unwrap();
+    assert_eq!(fdt.memory_reservations().collect::<Result<std::vec::Vec<_>, _>>().unwrap(), expected);
+}
+
+#[test]
+fn unterminated_memory_reservations() {
+    let blob = memory_reservation_blob(&[(0x8000_0000, 0x20_0000)], false);
+    let fdt = Fdt::new_fallible(&blob).unwrap();
+    let mut reservations = fdt.memory_reservations();
+
+    assert!(reservations.next().unwrap().is_ok());
+    assert!(matches!(reservations.next(), Some(Err(FdtError::ParseError(ParseError::UnexpectedEndOfData)))));
+    assert!(reservations.next().is_none());
+}
+
+fn flatten_properties<'a>(
+    fdt: &Fdt<'a, (parsing::aligned::AlignedParser<'a>, parsing::Panic)>,
+) -> std::vec::Vec<std::string::String> {
+    fdt.all_nodes()
+        .flat_map(|(depth, node)| {
+            node.properties()
+                .iter()
+                .map(|p| std::format!("{depth} {} {} {:?}", node.name(), p.name, p.value))
+                .collect::<std::vec::Vec<_>>()
+        })
+        .collect()
+}
+
+fn rebuild_node<'a>(
+    builder: &mut builder::FdtBuilder<'_>,
+    node: Node<'a, (parsing::aligned::AlignedParser<'a>, parsing::Panic)>,
+) {
+    builder.begin_node(&std::format!("{}", node.name())).unwrap();
+    for property in node.properties() {
+        builder.property(property.name, property.value).unwrap();
+    }
+
+    for child in node.children() {
+        rebuild_node(builder, child);
+    }
+
+    builder.end_node().unwrap();
+}
+
+#[test]
+fn builder_round_trip() {
+    let fdt = Fdt::new(TEST.as_slice()).unwrap();
+
+    let mut buffer = [0u32; 1024];
+    let mut builder = builder::FdtBuilder::new_aligned(&mut buffer);
+    builder.add_memory_reservation(0x8000_0000, 0x1000).unwrap();
+    builder.set_boot_cpuid(fdt.header().boot_cpuid);
+    rebuild_node(&mut builder, fdt.find_node("/").unwrap());
+    let size = builder.finish().unwrap();
+
+    let rebuilt = Fdt::new(&buffer).unwrap();
+    assert_eq!(rebuilt.total_size(), size);
+    assert_eq!(
+        rebuilt.memory_reservations().collect::<std::vec::Vec<_>>(),
+        [nodes::memory::MemoryReservation { address: 0x8000_0000, size: 0x1000 }]
+    );
+
+    assert_eq!(flatten_properties(&rebuilt), flatten_properties(&fdt));
+    assert_eq!(rebuilt.strings().count(), fdt.strings().count());
+}
+
+#[test]
+fn builder_properties() {
+    let mut buffer = [0u32; 128];
+    let mut builder = builder::FdtBuilder::new_aligned(&mut buffer);
+    builder.begin_node("/").unwrap();
+    builder.property_u32("#address-cells", 2).unwrap();
+    builder.property_u32("#size-cells", 1).unwrap();
+    builder.property_string_list("compatible", &["vendor,board", "vendor,soc"]).unwrap();
+    builder.begin_node("memory@80000000").unwrap();
+    builder.property_string("device_type", "memory").unwrap();
+    builder
+        .property_reg(
+            CellSizes { address_cells: 2, size_cells: 1 },
+            &[RegEntry { address: 0x8000_0000, len: 0x1000_0000 }, RegEntry { address: 0x1_0000_0000, len: 0x1000 }],
+        )
+        .unwrap();
+    builder.property_u64("size", 0x1234_5678_9abc).unwrap();
+    builder.property("hotpluggable", &[]).unwrap();
+    assert!(matches!(
+        builder.property_reg(CellSizes { address_cells: 1, size_cells: 1 }, &[RegEntry { address: 1 << 32, len: 0 }]),
+        Err(FdtError::CollectCellsError)
+    ));
+    assert!(matches!(
+        builder.property_reg(CellSizes { address_cells: 0, size_cells: 0 }, &[RegEntry { address: 0, len: 0 }]),
+        Err(FdtError::InvalidPropertyValue)
+    ));
+    builder.end_node().unwrap();
+    builder.begin_node("cpus").unwrap();
+    builder.property_u32("#address-cells", 1).unwrap();
+    builder.property_u32("cells", 0).unwrap();
+    builder.end_node().unwrap();
+    builder.end_node().unwrap();
+    assert!(matches!(builder.begin_node("another-root"), Err(FdtError::InvalidBuilderState)));
+    assert!(matches!(builder.add_memory_reservation(0, 0x1000), Err(FdtError::InvalidBuilderState)));
+    builder.finish().unwrap();
+
+    let fdt = Fdt::new(&buffer).unwrap();
+    let root = fdt.root();
+    assert_eq!(root.compatible().all().collect::<std::vec::Vec<_>>(), ["vendor,board", "vendor,soc"]);
+    assert_eq!(
+        root.memory().reg().iter::<u64, u64>().collect::<Result<std::vec::Vec<_>, _>>().unwrap(),
+        [RegEntry { address: 0x8000_0000, len: 0x1000_0000 }, RegEntry { address: 0x1_0000_0000, len: 0x1000 }]
+    );
+    assert!(root.memory().hotpluggable());
+    assert_eq!(root.memory().as_node().properties().find("size").unwrap().as_value::<u64>().unwrap(), 0x1234_5678_9abc);
+    // `cells` is a suffix of `#size-cells` and `#address-cells` is reused, so
+    // neither should need a new string
+    assert_eq!(
+        fdt.strings().collect::<std::vec::Vec<_>>(),
+        ["hotpluggable", "size", "reg", "device_type", "compatible", "#size-cells", "#address-cells"]
+    );
+}
+
+#[test]
+fn builder_slice_too_small() {
+    let mut buffer = [0u8; 96];
+    let mut builder = builder::FdtBuilder::new(&mut buffer);
+    builder.begin_node("").unwrap();
+    assert!(matches!(builder.property_string("model", "a-model-name-that-does-not-fit"), Err(FdtError::SliceTooSmall)));
+    assert!(matches!(builder.end_node(), Ok(())));
+
+    let mut buffer = [0u8; 32];
+    let mut builder = builder::FdtBuilder::new(&mut buffer);
+    assert!(matches!(builder.begin_node(""), Err(FdtError::SliceTooSmall)));
+}
+
+#[test]
+fn edit_inplace() {
+    let mut buffer = TEST.as_slice().to_vec();
+    let mut fdt = edit::FdtMut::new(&mut buffer).unwrap();
+
+    fdt.set_property_inplace("/cpus/cpu@0", "status", b"fail\0").unwrap();
+    fdt.set_property_u32_inplace("/soc/uart", "clock-frequency", 1_843_200).unwrap();
+    assert!(matches!(
+        fdt.set_property_inplace("/cpus/cpu@0", "status", b"disabled\0"),
+        Err(FdtError::PropertyLengthMismatch { expected: 5, found: 9 })
+    ));
+    assert!(matches!(fdt.set_property_inplace("/cpus/cpu@1", "status", b"fail\0"), Err(FdtError::NodeNotFound)));
+    assert!(matches!(fdt.set_property_inplace("/cpus/cpu@0", "statuss", b"fail\0"), Err(FdtError::PropertyNotFound)));
+
+    let fdt = Fdt::new(&buffer).unwrap();
+    let cpu = fdt.find_node("/cpus/cpu@0").unwrap();
+    assert_eq!(cpu.properties().find("status").unwrap().as_value::<&str>().unwrap(), "fail");
+    let uart = fdt.find_node("/soc/uart").unwrap();
+    assert_eq!(uart.properties().find("clock-frequency").unwrap().as_value::<u32>().unwrap(), 1_843_200);
+}
+
+#[test]
+fn edit_nop() {
+    let mut buffer = TEST.as_slice().to_vec();
+    let mut fdt = edit::FdtMut::new(&mut buffer).unwrap();
+
+    fdt.nop_property("/soc/pci", "interrupt-map").unwrap();
+    fdt.nop_node("/cpus/cpu@0").unwrap();
+    fdt.nop_node("/soc/clint").unwrap();
+    assert!(matches!(fdt.nop_node("/"), Err(FdtError::MissingParent)));
+    assert!(matches!(fdt.nop_property("/soc/pci", "interrupt-map"), Err(FdtError::PropertyNotFound)));
+
+    let fdt = Fdt::new(&buffer).unwrap();
+    assert!(fdt.find_node("/cpus/cpu@0").is_none());
+    assert!(fdt.find_node("/soc/clint").is_none());
+    assert!(fdt.find_node("/soc/plic").is_some());
+    assert_eq!(fdt.find_node("/cpus").unwrap().children().iter().count(), 1);
+
+    let pci = fdt.find_node("/soc/pci").unwrap();
+    assert!(pci.properties().find("interrupt-map").is_none());
+    assert_eq!(
+        pci.properties().iter().map(|p| p.name).collect::<std::vec::Vec<_>>(),
+        [
+            "interrupt-map-mask",
+            "ranges",
+            "reg",
+            "dma-coherent",
+            "bus-range",
+            "linux,pci-domain",
+            "device_type",
+            "compatible",
+            "#size-cells",
+            "#interrupt-cells",
+            "#address-cells"
+        ]
+    );
+    assert_eq!(fdt.root().all_nodes().count(), 24);
+}
+
+#[test]
+fn edit_resize() {
+    let mut buffer = TEST.as_slice().to_vec();
+    let original_size = buffer.len() * 4;
+    buffer.resize(buffer.len() + 256, 0);
+    let mut fdt = edit::FdtMut::new(&mut buffer).unwrap();
+
+    fdt.set_property_string("/chosen", "bootargs", "console=ttyS0,115200 earlycon").unwrap();
+    fdt.set_property_u64("/chosen", "linux,initrd-start", 0x8800_0000).unwrap();
+    fdt.set_property_u64("/chosen", "linux,initrd-end", 0x8900_0000).unwrap();
+    fdt.add_node("/", "reserved-memory").unwrap();
+    fdt.set_property_u32("/reserved-memory", "#address-cells", 2).unwrap();
+    fdt.add_node("/reserved-memory", "mmode_resv0@80000000").unwrap();
+    fdt.set_property("/reserved-memory/mmode_resv0@80000000", "no-map", &[]).unwrap();
+    fdt.remove_property("/soc/pci", "interrupt-map").unwrap();
+    fdt.remove_node("/soc/clint").unwrap();
+
+    assert!(matches!(fdt.add_node("/", "reserved-memory"), Err(FdtError::NodeAlreadyExists)));
+    assert!(matches!(fdt.add_node("/", "a/b"), Err(FdtError::InvalidNodeName)));
+    assert!(matches!(fdt.remove_node("/"), Err(FdtError::MissingParent)));
+    assert!(matches!(fdt.remove_property("/soc/pci", "interrupt-map"), Err(FdtError::PropertyNotFound)));
+
+    let total_size = fdt.total_size();
+    let fdt = Fdt::new(&buffer).unwrap();
+    assert_eq!(fdt.total_size(), total_size);
+    assert!(total_size < original_size);
+
+    assert_eq!(fdt.root().chosen().bootargs(), Some("console=ttyS0,115200 earlycon"));
+    let chosen = fdt.find_node("/chosen").unwrap();
+    assert_eq!(chosen.properties().find("linux,initrd-start").unwrap().as_value::<u64>().unwrap(), 0x8800_0000);
+    assert_eq!(chosen.properties().find("linux,initrd-end").unwrap().as_value::<u64>().unwrap(), 0x8900_0000);
+
+    let reserved = fdt.find_node("/reserved-memory").unwrap();
+    assert_eq!(reserved.properties().find("#address-cells").unwrap().as_value::<u32>().unwrap(), 2);
+    let resv = reserved.children().find("mmode_resv0@80000000").unwrap();
+    assert_eq!(resv.properties().find("no-map").unwrap().value, &[]);
+
+    assert!(fdt.find_node("/soc/clint").is_none());
+    assert!(fdt.find_node("/soc/pci").unwrap().properties().find("interrupt-map").is_none());
+    assert_eq!(fdt.root().all_nodes().count(), 28);
+
+    // Shrinking a property back down should leave the rest of the tree intact
+    let mut fdt = edit::FdtMut::new(&mut buffer).unwrap();
+    fdt.set_property_string("/chosen", "bootargs", "").unwrap();
+    let fdt = Fdt::new(&buffer).unwrap();
+    assert_eq!(fdt.root().chosen().bootargs(), Some(""));
+    assert!(fdt.find_node("/reserved-memory/mmode_resv0@80000000").is_some());
+}
+
+#[test]
+fn edit_resize_slice_too_small() {
+    let mut buffer = TEST.as_slice().to_vec();
+    buffer.resize(buffer.len() + 4, 0);
+    let original = buffer.clone();
+    let mut fdt = edit::FdtMut::new(&mut buffer).unwrap();
+
+    assert!(matches!(fdt.set_property("/chosen", "a-new-property-name", &[0; 8]), Err(FdtError::SliceTooSmall)));
+    assert!(matches!(
+        fdt.set_property_string("/chosen", "bootargs", "console=ttyS0,115200"),
+        Err(FdtError::SliceTooSmall)
+    ));
+    assert!(matches!(fdt.add_node("/", "reserved-memory"), Err(FdtError::SliceTooSmall)));
+    assert_eq!(buffer, original);
+}
+
+#[cfg(feature = "alloc")]
+fn aligned(bytes: &[u8]) -> std::vec::Vec<u32> {
+    bytes
+        .chunks(4)
+        .map(|chunk| {
+            let mut word = [0; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u32::from_ne_bytes(word)
+        })
+        .collect()
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn dts_compile_matches_dtc() {
+    let pairs: [(&str, &[u8]); 3] = [
+        (include_str!("../dts/test.dts"), util::cast_slice(TEST.as_slice())),
+        (include_str!("../dts/issue-3.dts"), ISSUE_3),
+        (include_str!("../dts/sifive.dts"), SIFIVE),
+    ];
+
+    for (source, dtb) in pairs {
+        let compiled = aligned(&dts::compile(source).unwrap());
+        let expected = aligned(dtb);
+        let compiled = Fdt::new(&compiled).unwrap();
+        let expected = Fdt::new(&expected).unwrap();
+
+        assert_eq!(flatten_properties(&compiled), flatten_properties(&expected));
+        assert_eq!(
+            compiled.memory_reservations().collect::<std::vec::Vec<_>>(),
+            expected.memory_reservations().collect::<std::vec::Vec<_>>()
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn dts_compile() {
+    let source = r#"
+        /dts-v1/;
+
+        /memreserve/ 0x80000000 0x10000;
+        /memreserve/ (0x1 << 32) (64 * 1024);
+
+        / {
+            #address-cells = <2>;
+            #size-cells = <(1 + 1)>;
+            model = "board";
+            compatible = "vendor,board", "vendor,soc";
+
+            aliases {
+                serial0 = &uart0;
+                serial1 = &{/soc/serial@2000};
+            };
+
+            soc {
+                intc: interrupt-controller@1000 {
+                    interrupt-controller;
+                    #interrupt-cells = <1>;
+                };
+
+                uart0: serial@1000 {
+                    interrupt-parent = <&intc>;
+                    interrupts = <'a' (-1) (10 > 5 ? 0x10 : 0x20)>;
+                    mac = [00 11 2233 44];
+                    bytes = /bits/ 8 <0x12 0xff>, /bits/ 16 <0x3456>;
+                    wide = /bits/ 64 <0x123456789>;
+                    removed;
+                };
+
+                serial@2000 {
+                    status = "disabled";
+                };
+
+                gone {
+                };
+            };
+        };
+
+        &uart0 {
+            /delete-property/ removed;
+            status = "okay\n";
+        };
+
+        / {
+            soc {
+                /delete-node/ gone;
+                serial@2000 {
+                    phandle-user = <&uart0 1>;
+                };
+            };
+        };
+    "#;
+
+    let compiled = aligned(&dts::compile(source).unwrap());
+    let fdt = Fdt::new(&compiled).unwrap();
+
+    assert_eq!(
+        fdt.memory_reservations().collect::<std::vec::Vec<_>>(),
+        [
+            nodes::memory::MemoryReservation { address: 0x8000_0000, size: 0x10000 },
+            nodes::memory::MemoryReservation { address: 0x1_0000_0000, size: 0x10000 }
+        ]
+    );
+
+    let root = fdt.root();
+    assert_eq!(root.model(), "board");
+    assert_eq!(root.compatible().all().collect::<std::vec::Vec<_>>(), ["vendor,board", "vendor,soc"]);
+    assert_eq!(fdt.find_node("/").unwrap().properties().find("#size-cells").unwrap().as_value::<u32>().unwrap(), 2);
+
+    assert_eq!(root.aliases().unwrap().resolve("serial0").unwrap().name().name, "serial");
+    assert_eq!(root.aliases().unwrap().resolve("serial1").unwrap().name().unit_address, Some("2000"));
+
+    let intc = fdt.find_node("/soc/interrupt-controller@1000").unwrap();
+    let uart = fdt.find_node("/soc/serial@1000").unwrap();
+    assert_eq!(intc.properties().find("phandle").unwrap().as_value::<u32>().unwrap(), 1);
+    assert_eq!(uart.properties().find("phandle").unwrap().as_value::<u32>().unwrap(), 2);
+    assert_eq!(uart.property::<properties::interrupts::InterruptParent>().unwrap().name(), intc.name());
+
+    let properties = uart.properties();
+    assert_eq!(properties.find("interrupts").unwrap().value, [0, 0, 0, 0x61, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0x10]);
+    assert_eq!(properties.find("mac").unwrap().value, [0x00, 0x11, 0x22, 0x33, 0x44]);
+    assert_eq!(properties.find("bytes").unwrap().value, [0x12, 0xff, 0x34, 0x56]);
+    assert_eq!(properties.find("wide").unwrap().as_value::<u64>().unwrap(), 0x1_2345_6789);
+    assert_eq!(properties.find("status").unwrap().value, b"okay\n\0");
+    assert!(properties.find("removed").is_none());
+
+    let serial = fdt.find_node("/soc/serial@2000").unwrap();
+    assert_eq!(serial.properties().find("phandle-user").unwrap().value, [0, 0, 0, 2, 0, 0, 0, 1]);
+    assert!(fdt.find_node("/soc/gone").is_none());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn dts_compile_errors() {
+    let error = dts::compile("/ { };").unwrap_err();
+    assert!(matches!(error.kind, dts::DtsErrorKind::MissingVersion));
+
+    let error = dts::compile("/dts-v1/;\n/ {\n\tprop = <&missing>;\n};").unwrap_err();
+    assert!(matches!(error.kind, dts::DtsErrorKind::UndefinedLabel(ref label) if label == "missing"));
+    assert_eq!((error.line, error.column), (3, 10));
+
+    let error = dts::compile("/dts-v1/;\n/ { prop = /bits/ 8 <256>; };").unwrap_err();
+    assert!(matches!(error.kind, dts::DtsErrorKind::ValueOutOfRange));
+
+    let error = dts::compile("/dts-v1/;\n/ { a: x { }; a: y { }; };").unwrap_err();
+    assert!(matches!(error.kind, dts::DtsErrorKind::DuplicateLabel(ref label) if label == "a"));
+
+    let error = dts::compile("/dts-v1/;\n/ { prop = <1 2>").unwrap_err();
+    assert!(matches!(error.kind, dts::DtsErrorKind::Expected(_)));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn symbols() {
+    let blob = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            soc {
+                serial@1000 { };
+                serial@2000 { };
+            };
+
+            __symbols__ {
+                uart0 = "/soc/serial@1000";
+                uart1 = "/soc/serial@2000";
+                removed = "/soc/serial@3000";
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let blob = aligned(&blob);
+    let fdt = Fdt::new(&blob).unwrap();
+    let root = fdt.root();
+
+    assert_eq!(root.find_node_by_label("uart1").unwrap().name().unit_address, Some("2000"));
+    assert!(root.find_node_by_label("uart2").is_none());
+    assert!(root.find_node_by_label("removed").is_none());
+
+    assert_eq!(root.symbols().unwrap().resolve_path("uart0"), Some("/soc/serial@1000"));
+    let all = root
+        .symbols()
+        .unwrap()
+        .iter()
+        .map(|(label, node)| (label, node.map(|n| n.name())))
+        .collect::<std::vec::Vec<_>>();
+    assert_eq!(
+        all,
+        [
+            ("uart0", Some(NodeName { name: "serial", unit_address: Some("1000") })),
+            ("uart1", Some(NodeName { name: "serial", unit_address: Some("2000") })),
+            ("removed", None),
+        ]
+    );
+
+    assert!(Fdt::new(TEST.as_slice()).unwrap().root().find_node_by_label("uart0").is_none());
+}
+
+#[cfg(feature = "alloc")]
+const OVERLAY_BASE: &str = r#"
+    /dts-v1/;
+
+    / {
+        soc {
+            interrupt-controller {
+                phandle = <1>;
+            };
+
+            serial@1000 {
+                interrupt-parent = <1>;
+                status = "disabled";
+                phandle = <2>;
+            };
+        };
+
+        __symbols__ {
+            intc = "/soc/interrupt-controller";
+            uart0 = "/soc/serial@1000";
+        };
+    };
+"#;
+
+#[test]
+#[cfg(feature = "alloc")]
+fn edit_apply_overlay() {
+    let overlay = dts::compile(
+        r#"
+        /dts-v1/;
+
+        / {
+            fragment@0 {
+                target = <0xffffffff>;
+
+                __overlay__ {
+                    status = "okay";
+                    interrupt-parent = <0xffffffff>;
+
+                    child {
+                        phandle = <1>;
+                    };
+
+                    consumer {
+                        ref = <1 5>;
+                    };
+                };
+            };
+
+            fragment@1 {
+                target-path = "/";
+
+                __overlay__ {
+                    leds {
+                        phandle = <2>;
+                    };
+                };
+            };
+
+            __symbols__ {
+                new_child = "/fragment@0/__overlay__/child";
+                leds = "/fragment@1/__overlay__/leds";
+            };
+
+            __fixups__ {
+                uart0 = "/fragment@0:target:0";
+                intc = "/fragment@0/__overlay__:interrupt-parent:0";
+            };
+
+            __local_fixups__ {
+                fragment@0 {
+                    __overlay__ {
+                        consumer {
+                            ref = <0>;
+                        };
+                    };
+                };
+            };
+        };
+    "#,
+    )
+    .unwrap();
+    let overlay = aligned(&overlay);
+    let overlay = Fdt::new(&overlay).unwrap();
+
+    let mut buffer = aligned(&dts::compile(OVERLAY_BASE).unwrap());
+    buffer.resize(buffer.len() + 128, 0);
+    let mut fdt = edit::FdtMut::new(&mut buffer).unwrap();
+    fdt.apply_overlay(&overlay).unwrap();
+
+    let fdt = Fdt::new(&buffer).unwrap();
+    let uart = fdt.find_node("/soc/serial@1000").unwrap();
+    assert_eq!(uart.properties().find("status").unwrap().as_value::<&str>().unwrap(), "okay");
+    assert_eq!(uart.properties().find("interrupt-parent").unwrap().as_value::<u32>().unwrap(), 1);
+
+    let child = fdt.find_node("/soc/serial@1000/child").unwrap();
+    assert_eq!(child.properties().find("phandle").unwrap().as_value::<u32>().unwrap(), 3);
+    let consumer = fdt.find_node("/soc/serial@1000/consumer").unwrap();
+    assert_eq!(consumer.properties().find("ref").unwrap().value, &[0, 0, 0, 3, 0, 0, 0, 5]);
+    let leds = fdt.find_node("/leds").unwrap();
+    assert_eq!(leds.properties().find("phandle").unwrap().as_value::<u32>().unwrap(), 4);
+
+    let symbols = fdt.find_node("/__symbols__").unwrap();
+    assert_eq!(symbols.properties().find("intc").unwrap().as_value::<&str>().unwrap(), "/soc/interrupt-controller");
+    assert_eq!(symbols.properties().find("new_child").unwrap().as_value::<&str>().unwrap(), "/soc/serial@1000/child");
+    assert_eq!(symbols.properties().find("leds").unwrap().as_value::<&str>().unwrap(), "/leds");
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn edit_apply_overlay_errors() {
+    let apply = |source: &str, check: &dyn Fn(edit::overlay::OverlayError<'_>)| {
+        let overlay = aligned(&dts::compile(source).unwrap());
+        let overlay = Fdt::new(&overlay).unwrap();
+
+        let mut buffer = aligned(&dts::compile(OVERLAY_BASE).unwrap());
+        buffer.resize(buffer.len() + 128, 0);
+        let original = buffer.clone();
+        let mut fdt = edit::FdtMut::new(&mut buffer).unwrap();
+        check(fdt.apply_overlay(&overlay).unwrap_err());
+        assert_eq!(buffer, original);
+    };
+
+    apply(
+        r#"/dts-v1/;
+        / {
+            fragment@0 { target = <0xffffffff>; __overlay__ { status = "okay"; }; };
+            __fixups__ { missing = "/fragment@0:target:0"; };
+        };"#,
+        &|e| assert!(matches!(e, edit::overlay::OverlayError::UnresolvedLabel("missing"))),
+    );
+
+    apply(
+        r#"/dts-v1/;
+        / {
+            fragment@0 { target-path = "/soc/i2c"; __overlay__ { status = "okay"; }; };
+        };"#,
+        &|e| assert!(matches!(e, edit::overlay::OverlayError::TargetPathNotFound { path: "/soc/i2c", .. })),
+    );
+
+    apply(
+        r#"/dts-v1/;
+        / {
+            fragment@0 { target = <7>; __overlay__ { status = "okay"; }; };
+        };"#,
+        &|e| assert!(matches!(e, edit::overlay::OverlayError::TargetPhandleNotFound { phandle: 7, .. })),
+    );
+
+    apply(
+        r#"/dts-v1/;
+        / {
+            fragment@0 { __overlay__ { status = "okay"; }; };
+        };"#,
+        &|e| assert!(matches!(e, edit::overlay::OverlayError::MissingTarget { .. })),
+    );
+
+    // The first fragment is valid, so a bad local fixup in the second must be
+    // caught before anything is merged
+    apply(
+        r#"/dts-v1/;
+        / {
+            fragment@0 { target-path = "/soc"; __overlay__ { status = "okay"; }; };
+            fragment@1 { target-path = "/soc"; __overlay__ { node { ref = <1>; }; }; };
+            __local_fixups__ { fragment@1 { __overlay__ { node { ref = <4>; }; }; }; };
+        };"#,
+        &|e| assert!(matches!(e, edit::overlay::OverlayError::InvalidFixup("ref"))),
+    );
+
+    apply(
+        r#"/dts-v1/;
+        / {
+            fragment@0 { target-path = "/soc"; __overlay__ { status = "okay"; }; };
+            fragment@1 { target-path = "/soc"; __overlay__ { ref = <0xffffffff>; }; };
+            __fixups__ { intc = "/fragment@1/__overlay__:ref:8"; };
+        };"#,
+        &|e| assert!(matches!(e, edit::overlay::OverlayError::InvalidFixup("intc"))),
+    );
+
+    // Renumbering past the base devicetree's phandles must not wrap around
+    apply(
+        r#"/dts-v1/;
+        / {
+            fragment@0 { target-path = "/soc"; __overlay__ { status = "okay"; }; };
+            fragment@1 { target-path = "/soc"; __overlay__ { node { phandle = <0xfffffffe>; }; }; };
+        };"#,
+        &|e| assert!(matches!(e, edit::overlay::OverlayError::Fdt(FdtError::InvalidPropertyValue))),
+    );
+
+    apply(
+        r#"/dts-v1/;
+        / {
+            fragment@0 { target-path = "/soc"; __overlay__ { status = "okay"; }; };
+            fragment@1 { target-path = "/soc"; __overlay__ { node { ref = <0xffffffff>; }; }; };
+            __local_fixups__ { fragment@1 { __overlay__ { node { ref = <0>; }; }; }; };
+        };"#,
+        &|e| assert!(matches!(e, edit::overlay::OverlayError::Fdt(FdtError::InvalidPropertyValue))),
+    );
+}
+
+#[cfg(feature = "pretty-printing")]
+#[test]
+fn fdt_display_dts() {
+    let fdt = Fdt::new(TEST.as_slice()).unwrap();
+    let dts = std::string::ToString::to_string(&fdt);
+
+    assert!(dts.starts_with("/dts-v1/;\n\n/ {\n\t#address-cells = <0x02>;\n"));
+    assert!(dts.contains("\tchosen {\n\t\tbootargs = [00];\n\t\tstdout-path = \"/soc/uart@10000000:115200\";\n"));
+    assert!(dts.contains("\t\t\t\tcore0 {\n\t\t\t\t\tcpu = <0x01>;\n"));
+
+    let dts = std::string::ToString::to_string(&fdt.display_dts().phandle_labels(true));
+    assert!(dts.contains("\t\tcpu_1: cpu@0 {\n"));
+    assert!(dts.contains("\t\t\tinterrupt_controller_2: interrupt-controller {\n"));
+    assert!(dts.contains("\t\t\t\t\tcpu = <&cpu_1>;\n"));
+
+    let blob = memory_reservation_blob(&[(0x8000_0000, 0x20_0000)], true);
+    let fdt = Fdt::new(&blob).unwrap();
+    assert_eq!(
+        std::string::ToString::to_string(&fdt),
+        "/dts-v1/;\n\n/memreserve/\t0x0000000080000000 0x0000000000200000;\n/ {\n};\n"
+    );
+}
+
+#[cfg(all(feature = "pretty-printing", feature = "alloc"))]
+#[test]
+fn fdt_display_dts_round_trip() {
+    let sifive = aligned(SIFIVE);
+    let reserved = memory_reservation_blob(&[(0x8000_0000, 0x20_0000), (0x1_0000_0000, 0x1000)], true);
+
+    for blob in [TEST.as_slice(), &sifive, &reserved] {
+        let fdt = Fdt::new(blob).unwrap();
+
+        for labels in [false, true] {
+            let dts = std::string::ToString::to_string(&fdt.display_dts().phandle_labels(labels));
+            let compiled = aligned(&dts::compile(&dts).unwrap());
+            let compiled = Fdt::new(&compiled).unwrap();
+
+            assert_eq!(flatten_properties(&compiled), flatten_properties(&fdt));
+            assert_eq!(
+                compiled.memory_reservations().collect::<std::vec::Vec<_>>(),
+                fdt.memory_reservations().collect::<std::vec::Vec<_>>()
+            );
+        }
+    }
+}
@@ -3,3 +3,15 @@ pub fn cast_slice(s: &[u32]) -> &[u8] {
     // they have lower alignment requirements and there is no padding
     unsafe { core::slice::from_raw_parts(s.as_ptr().cast(), core::mem::size_of_val(s)) }
 }
+
+pub fn cast_slice_mut(s: &mut [u32]) -> &mut [u8] {
+    // SAFETY: see `cast_slice`; every bit pattern is also a valid `u32`, so
+    // writes through the returned slice can't produce invalid values
+    unsafe { core::slice::from_raw_parts_mut(s.as_mut_ptr().cast(), core::mem::size_of_val(s)) }
+}
+
+/// Round `len` up to the next multiple of 4, the alignment of structure block
+/// tokens.
+pub fn align4(len: usize) -> usize {
+    (len + 3) & !3
+}